@@ -3,6 +3,38 @@ set -euo pipefail
 
 cd -- "$(dirname "$0")/.."
 
+# --against CMD adds a third cat to every case, e.g. --against "coreutils cat"
+# for uutils. CMD replaces ./wcat/wcat in the wcat command line; if its first
+# word is not an executable it is reported and skipped.
+against=""
+while [[ $# -gt 0 ]]; do
+  case "$1" in
+    --against)
+      if [[ $# -lt 2 ]]; then
+        echo "--against needs a command, e.g. --against /usr/bin/uu-cat" >&2
+        exit 1
+      fi
+      against="$2"
+      shift 2
+      ;;
+    --against=*)
+      against="${1#--against=}"
+      shift
+      ;;
+    *)
+      echo "usage: $0 [--against CMD]" >&2
+      exit 1
+      ;;
+  esac
+done
+if [[ -n "$against" ]]; then
+  read -r against_bin _ <<<"$against"
+  if ! command -v -- "$against_bin" >/dev/null 2>&1; then
+    echo "--against: $against_bin not found, benchmarking wcat and cat only" >&2
+    against=""
+  fi
+fi
+
 if ! command -v hyperfine >/dev/null 2>&1; then
   echo "hyperfine not installed (https://github.com/sharkdp/hyperfine)" >&2
   exit 1
@@ -18,12 +50,14 @@ printf 'one\n\n\nthree\n\n\n' > "$fixtures_dir/blanks.txt"
 printf 'col1\tcol2\nline\t2\n' > "$fixtures_dir/tabs.txt"
 printf 'plain\ncontrol:\x01here\nesc:\x1bX\n' > "$fixtures_dir/control.txt"
 printf 'no newline' > "$fixtures_dir/no_newline.txt"
+seq -f 'line %.0f' 1000000 > "$fixtures_dir/million.txt"
 
 tabs_file="$fixtures_dir/tabs.txt"
 blanks_file="$fixtures_dir/blanks.txt"
 control_file="$fixtures_dir/control.txt"
 no_nl_file="$fixtures_dir/no_newline.txt"
 small_file="$fixtures_dir/small.txt"
+million_file="$fixtures_dir/million.txt"
 
 warmup="${WCAT_BENCH_WARMUP:-2}"
 runs="${WCAT_BENCH_RUNS:-}"
@@ -44,16 +78,29 @@ bench_case() {
   local cout="$out_dir/cat_${case_idx}.out"
   local wbench="$bench_out_dir/wcat_${case_idx}.out"
   local cbench="$bench_out_dir/cat_${case_idx}.out"
+  local acmd="" aout="$out_dir/against_${case_idx}.out"
+  local abench="$bench_out_dir/against_${case_idx}.out"
+  local null_cmds=("$wcmd > /dev/null" "$ccmd > /dev/null")
+  local disk_cmds=("$wcmd > \"$wbench\"" "$ccmd > \"$cbench\"")
+  if [[ -n "$against" ]]; then
+    acmd="${wcmd//.\/wcat\/wcat/$against}"
+    null_cmds+=("$acmd > /dev/null")
+    disk_cmds+=("$acmd > \"$abench\"")
+  fi
 
   echo
   echo "== $label =="
   echo "-- /dev/null --"
-  hyperfine "${hyperfine_args[@]}" "$wcmd > /dev/null" "$ccmd > /dev/null"
+  hyperfine "${hyperfine_args[@]}" "${null_cmds[@]}"
   echo "-- disk output --"
-  hyperfine "${hyperfine_args[@]}" "$wcmd > \"$wbench\"" "$ccmd > \"$cbench\""
+  hyperfine "${hyperfine_args[@]}" "${disk_cmds[@]}"
   bash -c "$wcmd" > "$wout"
   bash -c "$ccmd" > "$cout"
   diff -u "$wout" "$cout"
+  if [[ -n "$acmd" ]]; then
+    bash -c "$acmd" > "$aout"
+    diff -u "$aout" "$cout"
+  fi
 
   case_idx=$((case_idx + 1))
 }
@@ -64,6 +111,9 @@ bench_case "plain bench_big.txt" "./wcat/wcat wcat/bench_big.txt" "cat wcat/benc
 bench_case "plain testfile" "./wcat/wcat wcat/testfile" "cat wcat/testfile"
 bench_case "multi small2 + big" "./wcat/wcat wcat/small2.txt wcat/big.txt" "cat wcat/small2.txt wcat/big.txt"
 bench_case "stdin big.txt" "cat wcat/big.txt | ./wcat/wcat -" "cat wcat/big.txt | cat -"
+# Sequential-readahead hint (posix_fadvise) on a read()-streamed regular file.
+bench_case "plain million.txt" "./wcat/wcat \"$million_file\"" "cat \"$million_file\""
+bench_case "-n million.txt" "./wcat/wcat -n \"$million_file\"" "cat -n \"$million_file\""
 
 bench_case "-n big.txt" "./wcat/wcat -n wcat/big.txt" "cat -n wcat/big.txt"
 bench_case "-b blanks.txt" "./wcat/wcat -b \"$blanks_file\"" "cat -b \"$blanks_file\""
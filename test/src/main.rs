@@ -1,16 +1,21 @@
 use anyhow::{bail, Context, Result};
 use clap::{Parser, Subcommand};
-use nix::unistd::mkfifo;
-use rand::RngCore;
-use std::collections::HashSet;
+use nix::sys::signal::{kill, Signal};
+use nix::unistd::{mkfifo, Pid};
+use rand::rngs::StdRng;
+use rand::{Rng, RngCore, SeedableRng};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
 use std::fs::{self, File};
 use std::os::unix::fs::symlink;
+use std::os::unix::fs::FileExt;
 use std::os::unix::fs::PermissionsExt;
-use std::os::unix::process::CommandExt;
-use std::io::Write;
+use std::os::unix::process::{CommandExt, ExitStatusExt};
+use std::io::{Read, Seek, Write};
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Instant;
 use tempfile::{NamedTempFile, TempDir};
 use walkdir::WalkDir;
 
@@ -28,15 +33,91 @@ enum Commands {
         /// Only run tests whose name contains this filter
         #[arg(short, long)]
         filter: Option<String>,
+        /// Only run tests carrying this tag (fifo, error, pipe, stdin, binary,
+        /// long-option, extension, tooling, large, matrix, bufsize, slow, syscalls,
+        /// serial)
+        #[arg(long)]
+        tag: Option<String>,
+        /// Also run cases tagged `slow`, which are skipped by default
+        #[arg(long, default_value_t = false)]
+        slow: bool,
+        /// Also run cases tagged `syscalls`, which count wcat's write calls
+        /// under `strace -c` (and skip when strace is not installed)
+        #[arg(long, default_value_t = false)]
+        check_syscalls: bool,
+        /// Run cases sorted by name instead of declaration order, so logs diff cleanly
+        #[arg(long, default_value_t = false)]
+        sort: bool,
+        /// Finish with a `SUMMARY passed=.. failed=.. total=.. filtered=..` line for scripts;
+        /// total counts the cases left after --filter/--tag
+        #[arg(long, default_value_t = false)]
+        summary_machine: bool,
+        /// Write `[{name, status, duration_ms}, ..]` for every executed case to this file
+        #[arg(long, value_name = "PATH")]
+        trace_json: Option<PathBuf>,
+        /// Run only this fraction (0.0-1.0) of the matrix cases, picked by a fixed
+        /// seed so the same cases run every time; other cases are unaffected
+        #[arg(long, value_name = "FRACTION", value_parser = parse_fraction)]
+        matrix_sample: Option<f64>,
         /// Print per-test execution details
         #[arg(short, long, default_value_t = false)]
         verbose: bool,
+        /// Test this wcat binary instead of assembling wcat/wcat.asm
+        #[arg(long)]
+        wcat: Option<PathBuf>,
+        /// Kill any child process still running after this many seconds
+        #[arg(long, default_value_t = 120)]
+        timeout: u64,
+        /// Run this many cases at once; cases tagged `serial` still run alone
+        #[arg(long, visible_alias = "jobs", value_name = "N", default_value_t = 1,
+              value_parser = clap::value_parser!(u64).range(1..))]
+        threads: u64,
+    },
+    /// Run a handful of high-signal cases (files, stdin, -n, -A, fifo, errors)
+    /// as a quick pre-push check
+    Smoke {
+        /// Test this wcat binary instead of assembling wcat/wcat.asm
+        #[arg(long)]
+        wcat: Option<PathBuf>,
     },
     /// Rewrite .asm files into processed/ without stripping pure comment lines
     ProcessAsm {
         /// Output directory (defaults to processed)
         #[arg(short, long, default_value = "processed")]
         output: PathBuf,
+        /// Re-assemble each processed file with nasm and fail on errors
+        #[arg(long, default_value_t = false)]
+        check: bool,
+        /// Rewrite the .asm sources themselves instead of writing to the output directory
+        #[arg(long, default_value_t = false, conflicts_with = "output")]
+        in_place: bool,
+        /// Omit comment-only lines and lines left empty by stripping; lines
+        /// already blank in the source are kept
+        #[arg(long, default_value_t = false)]
+        drop_empty: bool,
+        /// Character that starts a comment, e.g. `#` for GAS or `@` for ARM;
+        /// string literals are still skipped when looking for it
+        #[arg(long, value_name = "C", default_value_t = ';', value_parser = parse_comment_char)]
+        comment_char: char,
+        /// Write nothing; print a unified diff of what processing would change
+        /// and fail if any file is not already clean
+        #[arg(long, default_value_t = false, conflicts_with_all = ["output", "in_place", "check"])]
+        diff: bool,
+    },
+    /// Report per-file comment density of the .asm sources
+    Stats,
+    /// Compare wcat and cat on random options and stdin; the first divergence
+    /// is shrunk to a minimal argv and input and reported
+    Fuzz {
+        /// How many random cases to try
+        #[arg(long, default_value_t = 500)]
+        iterations: u64,
+        /// Seed for the case generator (printed when omitted, to replay a run)
+        #[arg(long)]
+        seed: Option<u64>,
+        /// Fuzz this wcat binary instead of assembling wcat/wcat.asm
+        #[arg(long)]
+        wcat: Option<PathBuf>,
     },
 }
 
@@ -44,71 +125,287 @@ fn main() -> Result<()> {
     let cli = Cli::parse();
     let command = cli.command.unwrap_or(Commands::Tests {
         filter: None,
+        tag: None,
+        slow: false,
+        check_syscalls: false,
+        sort: false,
+        summary_machine: false,
+        trace_json: None,
+        matrix_sample: None,
         verbose: false,
+        wcat: None,
+        timeout: 120,
+        threads: 1,
     });
 
     match command {
-        Commands::Tests { filter, verbose } => {
+        Commands::Tests {
+            filter,
+            tag,
+            slow,
+            check_syscalls,
+            sort,
+            summary_machine,
+            trace_json,
+            matrix_sample,
+            verbose,
+            wcat,
+            timeout,
+            threads,
+        } => {
             VERBOSE.store(verbose, Ordering::Relaxed);
-            run_tests(filter)
+            CHILD_TIMEOUT_SECS.store(timeout, Ordering::Relaxed);
+            isolate_child_env();
+            let slow = slow || tag.as_deref() == Some("slow");
+            let check_syscalls = check_syscalls || tag.as_deref() == Some("syscalls");
+            let options = RunOptions {
+                filter,
+                tag,
+                slow,
+                check_syscalls,
+                sort,
+                summary_machine,
+                trace_json,
+                matrix_sample,
+                threads: threads as usize,
+            };
+            run_tests(options, wcat)
+        }
+        Commands::ProcessAsm {
+            drop_empty,
+            comment_char,
+            diff: true,
+            ..
+        } => diff_asm(drop_empty, comment_char),
+        Commands::ProcessAsm {
+            output,
+            check,
+            in_place,
+            drop_empty,
+            comment_char,
+            diff: false,
+        } => process_asm(output, check, in_place, drop_empty, comment_char),
+        Commands::Smoke { wcat } => {
+            isolate_child_env();
+            run_smoke(wcat)
+        }
+        Commands::Stats => asm_stats(),
+        Commands::Fuzz {
+            iterations,
+            seed,
+            wcat,
+        } => {
+            isolate_child_env();
+            run_fuzz(iterations, seed, wcat)
         }
-        Commands::ProcessAsm { output } => process_asm(output),
     }
 }
 
+/// Keep the caller's environment from skewing comparisons: both binaries run
+/// without default options. Every child inherits this; `clean_env` also pins
+/// the C locale.
+fn isolate_child_env() {
+    // WCAT_OPTIONS would decorate only wcat's output.
+    std::env::remove_var("WCAT_OPTIONS");
+}
+
 // --------------------- Shared harness --------------------------------------
 struct Harness {
     wcat: PathBuf,
     cat: PathBuf,
+    /// System tac for `--reverse` checks; those are skipped without it.
+    tac: Option<PathBuf>,
+    /// System uniq for `--dedupe` checks; those are skipped without it.
+    uniq: Option<PathBuf>,
+    /// strace for `--check-syscalls`; those cases are skipped without it.
+    strace: Option<PathBuf>,
+    /// help2man for the man page check; skipped without it.
+    help2man: Option<PathBuf>,
     fixtures: Fixtures,
 }
 
-type TestCase = (&'static str, Box<dyn Fn(&Harness) -> Result<()>>);
+/// Mirrors BUFFER_SIZE in wcat.asm (the read() chunk size).
+const WCAT_BUFFER_SIZE: usize = 262144;
+
+/// Name, tags for `--tag` selection, and the check itself. Cases tagged
+/// `serial` touch process-wide state and never overlap another case.
+type TestCase = (
+    &'static str,
+    &'static [&'static str],
+    Box<dyn Fn(&Harness) -> Result<()> + Send + Sync>,
+);
 
 static VERBOSE: AtomicBool = AtomicBool::new(false);
+static CHILD_TIMEOUT_SECS: AtomicU64 = AtomicU64::new(120);
+
+thread_local! {
+    /// Private directory of the case running on this thread, if any.
+    static CASE_SCRATCH: std::cell::RefCell<Option<PathBuf>> = const { std::cell::RefCell::new(None) };
+}
 
 impl Harness {
-    fn new() -> Result<Self> {
+    fn new(wcat_override: Option<PathBuf>) -> Result<Self> {
         let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
             .parent()
             .context("expected test/ to have a parent")?
             .to_path_buf();
-        let wcat = root.join("wcat/wcat");
         let cat = which::which("cat").context("system cat not found")?;
-
-        ensure_wcat_built(&root, &wcat)?;
+        let wcat = match wcat_override {
+            Some(path) => fs::canonicalize(&path)
+                .with_context(|| format!("--wcat binary {} not found", path.display()))?,
+            None => {
+                let wcat = root.join("wcat/wcat");
+                ensure_wcat_built(&root, &wcat)?;
+                wcat
+            }
+        };
         let fixtures = Fixtures::new()?;
 
         Ok(Self {
             wcat,
             cat,
+            tac: which::which("tac").ok(),
+            uniq: which::which("uniq").ok(),
+            strace: which::which("strace").ok(),
+            help2man: which::which("help2man").ok(),
             fixtures,
         })
     }
 
+    /// Where a case creates its own files. Each case gets a fresh directory,
+    /// so fixed names like `missing.txt` never collide under `--threads`;
+    /// outside the runner this is the fixture directory.
+    fn scratch(&self) -> PathBuf {
+        CASE_SCRATCH
+            .with(|dir| dir.borrow().clone())
+            .unwrap_or_else(|| self.fixtures.dir.path().to_path_buf())
+    }
+
     fn compare_with_cat(&self, args: &[&str], input: Option<&[u8]>) -> Result<()> {
-        let wcat_out = run_cmd_with_arg0(&self.wcat, args, input, Some(&self.cat))?;
-        let cat_out = run_cmd(&self.cat, args, input)?;
+        self.compare_with_cat_env(args, input, &[])
+    }
+
+    /// compare_with_cat with `envs` set for both binaries, on top of the same
+    /// clean environment.
+    fn compare_with_cat_env(
+        &self,
+        args: &[&str],
+        input: Option<&[u8]>,
+        envs: &[(&str, &str)],
+    ) -> Result<()> {
+        let wcat_out = run_cmd_with_arg0(&self.wcat, args, input, Some(&self.cat), envs)?;
+        let cat_out = run_cmd_with_arg0(&self.cat, args, input, None, envs)?;
         if wcat_out.stdout != cat_out.stdout
             || wcat_out.stderr != cat_out.stderr
-            || wcat_out.status.code() != cat_out.status.code()
+            || exit_of(&wcat_out.status) != exit_of(&cat_out.status)
         {
+            let env = if envs.is_empty() { String::new() } else { format!(" with env {envs:?}") };
             bail!(
-                "output mismatch for args {:?}\n=== wcat stdout ===\n{}\n=== cat stdout ===\n{}\n=== wcat stderr ===\n{}\n=== cat stderr ===\n{}\n=== wcat status ===\n{:?}\n=== cat status ===\n{:?}",
+                "output mismatch for args {:?}{env}\n{}",
                 args,
-                String::from_utf8_lossy(&wcat_out.stdout),
-                String::from_utf8_lossy(&cat_out.stdout),
-                String::from_utf8_lossy(&wcat_out.stderr),
-                String::from_utf8_lossy(&cat_out.stderr),
-                wcat_out.status.code(),
-                cat_out.status.code()
+                mismatch_report(&wcat_out, &cat_out)
+            );
+        }
+        self.compare_output_files_with_cat(args, input, envs)?;
+        Ok(())
+    }
+
+    /// Compare the byte stream of stdout and stderr sharing one pipe, so a
+    /// diagnostic written before (or after) the output it should follow shows
+    /// up as a mismatch. Both binaries run as `cat` so the prefixes agree.
+    fn compare_merged_with_cat(&self, args: &[&str]) -> Result<()> {
+        let (wcat_stream, wcat_status) = run_cmd_merged(&self.wcat, args, Path::new("cat"))?;
+        let (cat_stream, cat_status) = run_cmd_merged(&self.cat, args, Path::new("cat"))?;
+        if wcat_stream != cat_stream || exit_of(&wcat_status) != exit_of(&cat_status) {
+            bail!(
+                "merged stdout+stderr mismatch for args {:?}\n{}status: wcat {} vs cat {}",
+                args,
+                describe_diff("stream", ("wcat", &wcat_stream), ("cat", &cat_stream)),
+                wcat_status,
+                cat_status
+            );
+        }
+        Ok(())
+    }
+
+    /// Check `wcat --reverse ARGS` against tac over the operands, piped
+    /// through `cat OPTIONS` when any options are given.
+    fn compare_with_tac(&self, args: &[&str], input: Option<&[u8]>) -> Result<()> {
+        let Some(tac) = &self.tac else {
+            println!("[SKIP] tac not available");
+            return Ok(());
+        };
+        let (options, operands): (Vec<&str>, Vec<&str>) =
+            args.iter().partition(|a| a.len() > 1 && a.starts_with('-'));
+        let reversed = run_cmd(tac, &operands, input)?;
+        let expected = if options.is_empty() {
+            reversed.stdout
+        } else {
+            run_cmd(&self.cat, &options, Some(&reversed.stdout))?.stdout
+        };
+        let mut wcat_args = vec!["--reverse"];
+        wcat_args.extend(args);
+        let out = run_cmd(&self.wcat, &wcat_args, input)?;
+        if out.stdout != expected || exit_of(&out.status) != exit_of(&reversed.status) {
+            bail!(
+                "output mismatch against tac for args {:?}\n=== wcat stdout ===\n{}\n=== expected ===\n{}\n=== wcat stderr ===\n{}\n=== wcat status ===\n{:?}\n=== tac status ===\n{:?}",
+                wcat_args,
+                String::from_utf8_lossy(&out.stdout),
+                String::from_utf8_lossy(&expected),
+                String::from_utf8_lossy(&out.stderr),
+                out.status.code(),
+                reversed.status.code()
+            );
+        }
+        Ok(())
+    }
+
+    /// Check `wcat --dedupe ARGS` against `cat [-s] OPERANDS | uniq | cat OPTIONS`.
+    /// uniq terminates an unterminated last line it prints; wcat, like cat,
+    /// leaves it as it was, so that newline is not expected.
+    fn compare_with_uniq(&self, args: &[&str], input: Option<&[u8]>) -> Result<()> {
+        let Some(uniq) = &self.uniq else {
+            println!("[SKIP] uniq not available");
+            return Ok(());
+        };
+        let (options, operands): (Vec<&str>, Vec<&str>) =
+            args.iter().partition(|a| a.len() > 1 && a.starts_with('-'));
+        let (squeeze, options): (Vec<&str>, Vec<&str>) =
+            options.into_iter().partition(|&o| o == "-s");
+        let joined = run_cmd(&self.cat, &[squeeze, operands].concat(), input)?;
+        let mut expected = run_cmd(uniq, &[], Some(&joined.stdout))?.stdout;
+        let data = &joined.stdout;
+        if !data.is_empty() && !data.ends_with(b"\n") {
+            let lines: Vec<&[u8]> = data.split(|&b| b == b'\n').collect();
+            if lines.len() < 2 || lines[lines.len() - 2] != lines[lines.len() - 1] {
+                expected.pop();
+            }
+        }
+        if !options.is_empty() {
+            expected = run_cmd(&self.cat, &options, Some(&expected))?.stdout;
+        }
+        let mut wcat_args = vec!["--dedupe"];
+        wcat_args.extend(args);
+        let out = run_cmd(&self.wcat, &wcat_args, input)?;
+        if out.stdout != expected || exit_of(&out.status) != exit_of(&joined.status) {
+            bail!(
+                "output mismatch against uniq for args {:?}\n=== wcat stdout ===\n{}\n=== expected ===\n{}\n=== wcat stderr ===\n{}\n=== wcat status ===\n{:?}",
+                wcat_args,
+                String::from_utf8_lossy(&out.stdout),
+                String::from_utf8_lossy(&expected),
+                String::from_utf8_lossy(&out.stderr),
+                out.status.code()
             );
         }
-        self.compare_output_files_with_cat(args, input)?;
         Ok(())
     }
 
-    fn compare_output_files_with_cat(&self, args: &[&str], input: Option<&[u8]>) -> Result<()> {
+    fn compare_output_files_with_cat(
+        &self,
+        args: &[&str],
+        input: Option<&[u8]>,
+        envs: &[(&str, &str)],
+    ) -> Result<()> {
         let wcat_file = NamedTempFile::new_in(self.fixtures.dir.path())?;
         let cat_file = NamedTempFile::new_in(self.fixtures.dir.path())?;
         run_cmd_to_file(
@@ -116,17 +413,47 @@ impl Harness {
             args,
             input,
             Some(&self.cat),
+            envs,
             wcat_file.path(),
         )?;
-        run_cmd_to_file(&self.cat, args, input, None, cat_file.path())?;
+        run_cmd_to_file(&self.cat, args, input, None, envs, cat_file.path())?;
         let wcat_bytes = fs::read(wcat_file.path())?;
         let cat_bytes = fs::read(cat_file.path())?;
         if wcat_bytes != cat_bytes {
             bail!(
-                "file output mismatch for args {:?} (wcat {}B vs cat {}B)",
+                "file output mismatch for args {:?}\n{}",
                 args,
-                wcat_bytes.len(),
-                cat_bytes.len()
+                describe_diff("file", ("wcat", &wcat_bytes), ("cat", &cat_bytes))
+            );
+        }
+        Ok(())
+    }
+
+    /// Run wcat alone (for wcat-only options) and check stdout and a zero exit.
+    fn expect_wcat(&self, args: &[&str], input: Option<&[u8]>, expected: &[u8]) -> Result<()> {
+        let out = run_cmd(&self.wcat, args, input)?;
+        if out.stdout != expected || !out.status.success() {
+            bail!(
+                "unexpected wcat output for args {:?}\n=== expected ===\n{}\n=== got ===\n{}\n=== stderr ===\n{}\n=== status ===\n{:?}",
+                args,
+                String::from_utf8_lossy(expected),
+                String::from_utf8_lossy(&out.stdout),
+                String::from_utf8_lossy(&out.stderr),
+                out.status.code()
+            );
+        }
+        Ok(())
+    }
+
+    /// Run wcat alone and check it fails with exit 1 and a diagnostic containing `needle`.
+    fn expect_wcat_error(&self, args: &[&str], input: Option<&[u8]>, needle: &str) -> Result<()> {
+        let out = run_cmd(&self.wcat, args, input)?;
+        let stderr = String::from_utf8_lossy(&out.stderr);
+        if out.status.code() != Some(1) || !stderr.contains(needle) {
+            bail!(
+                "expected failure containing {needle:?} for args {:?}, got status {:?} stderr {stderr:?}",
+                args,
+                out.status.code()
             );
         }
         Ok(())
@@ -151,6 +478,10 @@ struct Fixtures {
     huge: PathBuf,
     control: PathBuf,
     binary: PathBuf,
+    sparse: PathBuf,
+    utf8_seam: PathBuf,
+    cr_only: PathBuf,
+    whitespace: PathBuf,
     dir_path: PathBuf,
 }
 
@@ -192,6 +523,21 @@ impl Fixtures {
         let mut binary = vec![0u8; 512];
         rand::thread_rng().fill_bytes(&mut binary);
         fs::write(p("binary.bin"), &binary)?;
+        // A 3 MiB hole followed by a little data: long NUL runs spanning many
+        // read() chunks, with a large apparent size but almost no blocks.
+        let sparse = File::create(p("sparse.bin"))?;
+        sparse.set_len(3 << 20)?;
+        sparse.write_all_at(b"after the hole\n", 3 << 20)?;
+        // A 3-byte UTF-8 character whose first byte is the last byte of the
+        // first read() chunk.
+        let mut utf8_seam = vec![b'a'; WCAT_BUFFER_SIZE - 1];
+        utf8_seam.extend_from_slice("\u{20ac} more text\nnext line \u{e9}\n".as_bytes());
+        fs::write(p("utf8_seam.txt"), &utf8_seam)?;
+        // Classic Mac line endings: cat treats '\r' as an ordinary byte.
+        fs::write(p("cr_only.txt"), b"a\rb\rc\r")?;
+        // Empty, space-only, tab-only and mixed-blank lines: only the empty
+        // ones are blank to cat -b and -s.
+        fs::write(p("whitespace.txt"), b"text\n\n \n\t\n\n \t \n\n\nend\n  ")?;
         fs::create_dir(p("adir"))?;
 
         Ok(Self {
@@ -212,21 +558,112 @@ impl Fixtures {
             huge: p("huge.txt"),
             control: p("control.txt"),
             binary: p("binary.bin"),
+            sparse: p("sparse.bin"),
+            utf8_seam: p("utf8_seam.txt"),
+            cr_only: p("cr_only.txt"),
+            whitespace: p("whitespace.txt"),
             dir_path: p("adir"),
         })
     }
 }
 
 // --------------------- Test runner ----------------------------------------
-fn run_tests(filter: Option<String>) -> Result<()> {
-    let harness = Harness::new()?;
-    let mut cases: Vec<TestCase> = vec![
+/// Which cases `run_tests` executes and how it reports them.
+struct RunOptions {
+    filter: Option<String>,
+    tag: Option<String>,
+    slow: bool,
+    check_syscalls: bool,
+    sort: bool,
+    summary_machine: bool,
+    trace_json: Option<PathBuf>,
+    matrix_sample: Option<f64>,
+    threads: usize,
+}
+
+/// A comment delimiter must not be whitespace or something the scanner
+/// already gives meaning to (quotes, `\` continuations).
+fn parse_comment_char(value: &str) -> std::result::Result<char, String> {
+    let mut chars = value.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c.is_ascii_graphic() && !matches!(c, '\'' | '"' | '`' | '\\') => Ok(c),
+        _ => Err(format!("expected one printable non-quote character, got '{value}'")),
+    }
+}
+
+/// Seed mixed into each matrix case name when `--matrix-sample` picks cases.
+const MATRIX_SAMPLE_SEED: &[u8] = b"wcat-matrix-sample-v1";
+
+fn parse_fraction(value: &str) -> std::result::Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(f) if (0.0..=1.0).contains(&f) => Ok(f),
+        _ => Err(format!("expected a number between 0.0 and 1.0, got '{value}'")),
+    }
+}
+
+/// Whether `name` falls inside the sampled `fraction`; a pure function of the
+/// name so a given fraction always selects the same cases.
+fn matrix_case_sampled(name: &str, fraction: f64) -> bool {
+    let digest = Sha256::new()
+        .chain_update(MATRIX_SAMPLE_SEED)
+        .chain_update(name.as_bytes())
+        .finalize();
+    let bucket = u64::from_be_bytes(digest[..8].try_into().unwrap());
+    (bucket as f64) < fraction * u64::MAX as f64
+}
+
+/// Cases `smoke` runs: one per area that breaks first when wcat regresses.
+const SMOKE_CASES: &[&str] = &[
+    "single file",
+    "multiple files",
+    "stdin only",
+    "-n option",
+    "-A shortcut",
+    "binary passthrough",
+    "-T fifo fast path",
+    "missing file error",
+    "bad option error",
+    "directory operand error",
+];
+
+fn run_smoke(wcat: Option<PathBuf>) -> Result<()> {
+    let started = Instant::now();
+    let harness = Harness::new(wcat)?;
+    let mut cases = build_cases();
+    cases.retain(|(name, _, _)| SMOKE_CASES.contains(name));
+    if cases.len() != SMOKE_CASES.len() {
+        let missing: Vec<_> = SMOKE_CASES.iter().filter(|name| !cases.iter().any(|c| c.0 == **name)).collect();
+        bail!("smoke cases no longer defined: {missing:?}");
+    }
+    let failed: Vec<&str> = cases
+        .iter()
+        .enumerate()
+        .filter(|(index, case)| run_case(&harness, *index, case).0.is_err())
+        .map(|(_, case)| case.0)
+        .collect();
+    println!(
+        "\nsmoke: {}/{} passed in {:.2}s",
+        cases.len() - failed.len(),
+        cases.len(),
+        started.elapsed().as_secs_f64()
+    );
+    if !failed.is_empty() {
+        bail!("smoke failures: {}", failed.join(", "));
+    }
+    Ok(())
+}
+
+/// Every hand-written case, in declaration order; the matrix is added on top.
+fn build_cases() -> Vec<TestCase> {
+    vec![
         (
             "single file",
+            &[],
             Box::new(|h| h.compare_with_cat(&[h.fixtures.sample_a.to_str().unwrap()], None)),
         ),
         (
             "multiple files",
+            &[],
             Box::new(|h| {
                 h.compare_with_cat(
                     &[
@@ -240,10 +677,12 @@ fn run_tests(filter: Option<String>) -> Result<()> {
         ),
         (
             "stdin only",
+            &["stdin"],
             Box::new(|h| h.compare_with_cat(&["-"], Some(&h.fixtures.stdin_data))),
         ),
         (
             "dash operand",
+            &["stdin"],
             Box::new(|h| {
                 h.compare_with_cat(
                     &[
@@ -257,14 +696,17 @@ fn run_tests(filter: Option<String>) -> Result<()> {
         ),
         (
             "dash filename",
+            &[],
             Box::new(|h| h.compare_with_cat(&["--", h.fixtures.dash_name.to_str().unwrap()], None)),
         ),
         (
             "-n option",
+            &[],
             Box::new(|h| h.compare_with_cat(&["-n", h.fixtures.blank.to_str().unwrap()], None)),
         ),
         (
             "-n across files",
+            &[],
             Box::new(|h| {
                 h.compare_with_cat(
                     &[
@@ -278,149 +720,185 @@ fn run_tests(filter: Option<String>) -> Result<()> {
         ),
         (
             "-E option",
+            &[],
             Box::new(|h| h.compare_with_cat(&["-E", h.fixtures.blank.to_str().unwrap()], None)),
         ),
         (
             "-T option (file)",
+            &[],
             Box::new(|h| h.compare_with_cat(&["-T", h.fixtures.tabs.to_str().unwrap()], None)),
         ),
         (
             "-T option (stdin)",
+            &["stdin"],
             Box::new(|h| h.compare_with_cat(&["-T", "-"], Some(&fs::read(&h.fixtures.tabs)?))),
         ),
         (
             "-E option large",
+            &["large"],
             Box::new(|h| h.compare_with_cat(&["-E", h.fixtures.large.to_str().unwrap()], None)),
         ),
         (
             "-E option huge",
+            &["large"],
             Box::new(|h| h.compare_with_cat(&["-E", h.fixtures.huge.to_str().unwrap()], None)),
         ),
-        ("-T fifo fast path", Box::new(test_fifo_tabs)),
+        ("-T fifo fast path", &["fifo"], Box::new(test_fifo_tabs)),
         (
             "-nET combo",
+            &[],
             Box::new(|h| h.compare_with_cat(&["-nET", h.fixtures.tabs.to_str().unwrap()], None)),
         ),
         (
             "-EnT combo order",
+            &[],
             Box::new(|h| h.compare_with_cat(&["-EnT", h.fixtures.tabs.to_str().unwrap()], None)),
         ),
         (
             "stdin numbered",
+            &["stdin"],
             Box::new(|h| h.compare_with_cat(&["-n", "-"], Some(&h.fixtures.stdin_data))),
         ),
         (
             "empty file",
+            &[],
             Box::new(|h| h.compare_with_cat(&[h.fixtures.empty.to_str().unwrap()], None)),
         ),
         (
             "no newline + -E",
+            &[],
             Box::new(|h| {
                 h.compare_with_cat(&["-E", h.fixtures.no_newline.to_str().unwrap()], None)
             }),
         ),
         (
             "large file streaming",
+            &["large"],
             Box::new(|h| h.compare_with_cat(&[h.fixtures.large.to_str().unwrap()], None)),
         ),
         (
             "-n large file",
+            &["large"],
             Box::new(|h| h.compare_with_cat(&["-n", h.fixtures.large.to_str().unwrap()], None)),
         ),
         (
             "dash filename with numbering",
+            &[],
             Box::new(|h| {
                 h.compare_with_cat(&["-n", "--", h.fixtures.dash_name.to_str().unwrap()], None)
             }),
         ),
         (
             "bundle -nb",
+            &[],
             Box::new(|h| h.compare_with_cat(&["-nb", h.fixtures.blank.to_str().unwrap()], None)),
         ),
         (
             "bundle -bn",
+            &[],
             Box::new(|h| h.compare_with_cat(&["-bn", h.fixtures.blank.to_str().unwrap()], None)),
         ),
         (
             "bundle -ns",
+            &[],
             Box::new(|h| h.compare_with_cat(&["-ns", h.fixtures.blank.to_str().unwrap()], None)),
         ),
         (
             "bundle -sn",
+            &[],
             Box::new(|h| h.compare_with_cat(&["-sn", h.fixtures.blank.to_str().unwrap()], None)),
         ),
         (
             "-n empty file",
+            &[],
             Box::new(|h| h.compare_with_cat(&["-n", h.fixtures.empty.to_str().unwrap()], None)),
         ),
         (
             "-b empty file",
+            &[],
             Box::new(|h| h.compare_with_cat(&["-b", h.fixtures.empty.to_str().unwrap()], None)),
         ),
         (
             "-s empty file",
+            &[],
             Box::new(|h| h.compare_with_cat(&["-s", h.fixtures.empty.to_str().unwrap()], None)),
         ),
         (
             "-v with tabs file",
+            &[],
             Box::new(|h| h.compare_with_cat(&["-v", h.fixtures.tabs.to_str().unwrap()], None)),
         ),
         (
             "-T with no tabs",
+            &[],
             Box::new(|h| h.compare_with_cat(&["-T", h.fixtures.sample_a.to_str().unwrap()], None)),
         ),
         (
             "-E with tabs file",
+            &[],
             Box::new(|h| h.compare_with_cat(&["-E", h.fixtures.tabs.to_str().unwrap()], None)),
         ),
         (
             "-A with tabs file",
+            &[],
             Box::new(|h| h.compare_with_cat(&["-A", h.fixtures.tabs.to_str().unwrap()], None)),
         ),
         (
             "-e with no newline",
+            &[],
             Box::new(|h| {
                 h.compare_with_cat(&["-e", h.fixtures.no_newline.to_str().unwrap()], None)
             }),
         ),
         (
             "-t with tabs via stdin",
+            &["stdin"],
             Box::new(|h| h.compare_with_cat(&["-t", "-"], Some(&fs::read(&h.fixtures.tabs)?))),
         ),
         (
             "-b option",
+            &[],
             Box::new(|h| h.compare_with_cat(&["-b", h.fixtures.blank.to_str().unwrap()], None)),
         ),
         (
             "-s option",
+            &[],
             Box::new(|h| h.compare_with_cat(&["-s", h.fixtures.blank.to_str().unwrap()], None)),
         ),
         (
             "-v option",
+            &[],
             Box::new(|h| h.compare_with_cat(&["-v", h.fixtures.control.to_str().unwrap()], None)),
         ),
         (
             "-A shortcut",
+            &[],
             Box::new(|h| h.compare_with_cat(&["-A", h.fixtures.control.to_str().unwrap()], None)),
         ),
         (
             "-e shortcut",
+            &[],
             Box::new(|h| h.compare_with_cat(&["-e", h.fixtures.control.to_str().unwrap()], None)),
         ),
         (
             "-t shortcut",
+            &[],
             Box::new(|h| h.compare_with_cat(&["-t", h.fixtures.tabs.to_str().unwrap()], None)),
         ),
         (
             "-u option",
+            &[],
             Box::new(|h| h.compare_with_cat(&["-u", h.fixtures.sample_a.to_str().unwrap()], None)),
         ),
+        ("-u on binary and control bytes", &["binary"], Box::new(test_unbuffered_binary)),
         (
             "bundled options -bnEs",
+            &[],
             Box::new(|h| h.compare_with_cat(&["-bnEs", h.fixtures.blank.to_str().unwrap()], None)),
         ),
         (
             "option-like operand after file",
+            &[],
             Box::new(|h| {
                 h.compare_with_cat(
                     &[
@@ -433,146 +911,206 @@ fn run_tests(filter: Option<String>) -> Result<()> {
         ),
         (
             "-- stops option parsing",
+            &[],
             Box::new(|h| {
                 h.compare_with_cat(&["--", h.fixtures.dash_v_file.to_str().unwrap()], None)
             }),
         ),
         (
             "-- mid-argv parsing",
+            &[],
             Box::new(test_mid_argv_double_dash),
         ),
         (
             "stdin then option-like operand",
+            &["stdin"],
             Box::new(test_stdin_then_option_operand),
         ),
         (
             "multiple -- markers",
+            &[],
             Box::new(test_multiple_double_dash),
         ),
         (
             "redundant -n flags",
+            &[],
             Box::new(|h| {
                 h.compare_with_cat(&["-n", "-n", h.fixtures.sample_a.to_str().unwrap()], None)
             }),
         ),
         (
             "options after operand parsed",
+            &[],
             Box::new(|h| {
                 h.compare_with_cat(&[h.fixtures.sample_a.to_str().unwrap(), "-n"], None)
             }),
         ),
+        ("POSIXLY_CORRECT stops at the first operand", &[], Box::new(test_posixly_correct)),
+        (
+            "POSIXLY_CORRECT options before operands",
+            &[],
+            Box::new(|h| {
+                let args = ["-n", "-E", "-", h.fixtures.sample_a.to_str().unwrap()];
+                let envs = [("POSIXLY_CORRECT", "1")];
+                h.compare_with_cat_env(&args, Some(&h.fixtures.stdin_data), &envs)
+            }),
+        ),
         (
             "long option after operand",
+            &["long-option"],
             Box::new(|h| {
                 h.compare_with_cat(&[h.fixtures.sample_a.to_str().unwrap(), "--number"], None)
             }),
         ),
         (
             "long option stdin",
+            &["stdin", "long-option"],
             Box::new(|h| h.compare_with_cat(&["--number", "-"], Some(&h.fixtures.stdin_data))),
         ),
-        ("long options", Box::new(test_long_options)),
-        ("option permutation mixed", Box::new(test_option_permutation_mixed)),
-        ("option permutation stdin", Box::new(test_option_permutation_stdin)),
-        ("double dash no operands", Box::new(test_double_dash_no_operands)),
-        ("invalid long option", Box::new(test_invalid_long_option)),
-        ("invalid long option equals", Box::new(test_invalid_long_option_equals)),
-        ("unknown long option equals", Box::new(test_unknown_long_option_equals)),
-        ("long option disallow arg", Box::new(test_long_option_disallow_arg)),
+        ("long options", &["long-option"], Box::new(test_long_options)),
+        ("option permutation mixed", &[], Box::new(test_option_permutation_mixed)),
+        ("option permutation stdin", &["stdin"], Box::new(test_option_permutation_stdin)),
+        ("double dash no operands", &[], Box::new(test_double_dash_no_operands)),
+        ("invalid long option", &["error", "long-option"], Box::new(test_invalid_long_option)),
+        ("invalid long option equals", &["error", "long-option"], Box::new(test_invalid_long_option_equals)),
+        ("unknown long option equals", &["error", "long-option"], Box::new(test_unknown_long_option_equals)),
+        ("long option disallow arg", &["error", "long-option"], Box::new(test_long_option_disallow_arg)),
         (
             "huge file numbering",
+            &["large"],
             Box::new(|h| h.compare_with_cat(&["-n", h.fixtures.huge.to_str().unwrap()], None)),
         ),
         (
             "binary passthrough",
+            &["binary"],
             Box::new(|h| h.compare_with_cat(&[h.fixtures.binary.to_str().unwrap()], None)),
         ),
-        ("plain stdout fifo fast path", Box::new(test_fifo_plain)),
-        ("-n fifo fast path", Box::new(test_fifo_numbered)),
-        ("-v fifo fast path", Box::new(test_fifo_visible)),
-        ("fifo streaming", Box::new(test_fifo_stream)),
-        ("--help switch", Box::new(test_help_output)),
-        ("--version switch", Box::new(test_version_output)),
-        ("--help stdout closed", Box::new(test_help_stdout_closed)),
-        ("ENOENT vs EACCES messaging", Box::new(test_enoent_vs_eacces)),
-        ("directory operand error", Box::new(test_directory_operand)),
-        ("very long path ENAMETOOLONG", Box::new(test_enametoolong)),
-        ("missing file error", Box::new(test_missing_file)),
-        ("missing among files", Box::new(test_missing_among_files)),
-        ("bad option error", Box::new(test_bad_option)),
+        ("plain stdout fifo fast path", &["fifo"], Box::new(test_fifo_plain)),
+        ("-n fifo fast path", &["fifo"], Box::new(test_fifo_numbered)),
+        ("-v fifo fast path", &["fifo"], Box::new(test_fifo_visible)),
+        ("fifo streaming", &["fifo"], Box::new(test_fifo_stream)),
+        ("fifo -u incremental output", &["fifo", "stdin"], Box::new(test_fifo_unbuffered_incremental)),
+        ("fifo O_NONBLOCK stdin", &["fifo", "stdin"], Box::new(test_fifo_nonblocking_stdin)),
+        ("--help switch", &["long-option"], Box::new(test_help_output)),
+        ("--version switch", &["long-option"], Box::new(test_version_output)),
+        ("--help snapshot", &["long-option"], Box::new(test_help_snapshot)),
+        ("argv[0] stays out of --help/--version", &["long-option", "error"], Box::new(test_info_ignores_arg0)),
+        ("--help through help2man", &["long-option"], Box::new(test_help2man)),
+        ("--help stdout closed", &["error", "long-option"], Box::new(test_help_stdout_closed)),
+        ("--help/--version closed stdout", &["error", "long-option"], Box::new(test_info_closed_stdout)),
+        ("closed stderr", &["error"], Box::new(test_closed_stderr)),
+        ("--help/--version precedence", &["long-option"], Box::new(test_info_precedence)),
+        ("--help/--version locale independent", &["long-option"], Box::new(test_info_locale)),
+        ("diff report locates a text difference", &["harness"], Box::new(test_diff_report_text)),
+        ("diff report on early EOF", &["harness"], Box::new(test_diff_report_eof)),
+        ("diff report on binary and long lines", &["harness"], Box::new(test_diff_report_binary)),
+        ("fuzz minimizer isolates flag and byte", &["harness"], Box::new(test_minimize_flag_and_byte)),
+        ("fuzz minimizer keeps what is needed", &["harness"], Box::new(test_minimize_keeps_needed)),
+        ("strace summary parsing", &["harness"], Box::new(test_strace_summary_parsing)),
+        ("write syscalls per byte", &["syscalls", "large"], Box::new(test_write_syscall_count)),
+        ("ENOENT vs EACCES messaging", &["error"], Box::new(test_enoent_vs_eacces)),
+        ("unreadable directory operand", &["error"], Box::new(test_unreadable_directory)),
+        ("directory operand error", &["error"], Box::new(test_directory_operand)),
+        ("very long path ENAMETOOLONG", &["error"], Box::new(test_enametoolong)),
+        ("missing file error", &["error"], Box::new(test_missing_file)),
+        ("missing among files", &["error"], Box::new(test_missing_among_files)),
+        ("errors interleaved with output", &["error"], Box::new(test_merged_error_order)),
+        ("bad option error", &["error"], Box::new(test_bad_option)),
         // Extra coverage beyond original shell suite
         (
             "-bs combo",
+            &[],
             Box::new(|h| {
                 h.compare_with_cat(&["-b", "-s", h.fixtures.blank.to_str().unwrap()], None)
             }),
         ),
         (
             "-- then -n filename",
+            &[],
             Box::new(|h| h.compare_with_cat(&["--", h.fixtures.option_like.to_str().unwrap()], None)),
         ),
         (
             "-nT combo",
+            &[],
             Box::new(|h| h.compare_with_cat(&["-nT", h.fixtures.tabs.to_str().unwrap()], None)),
         ),
         (
             "stdin huge numbering",
+            &["stdin", "large"],
             Box::new(|h| h.compare_with_cat(&["-n", "-"], Some(&fs::read(&h.fixtures.huge)?))),
         ),
         (
             "stdin empty",
+            &["stdin"],
             Box::new(|h| h.compare_with_cat(&["-"], Some(&[]))),
         ),
         (
             "dash file before options",
+            &[],
             Box::new(test_dash_file_before_options),
         ),
         (
             "literal dash filename",
+            &[],
             Box::new(test_literal_dash_filename),
         ),
         (
             "double stdin operands",
+            &["stdin"],
             Box::new(test_double_stdin_operands),
         ),
         (
             "stdin via -- then file",
+            &["stdin"],
             Box::new(test_double_dash_then_stdin_and_file),
         ),
         (
             "broken pipe write error",
+            &["error", "pipe"],
             Box::new(test_broken_pipe_write_error),
         ),
+        (
+            "SIGPIPE death vs EPIPE exit",
+            &["error", "pipe"],
+            Box::new(test_sigpipe_parity),
+        ),
         (
             "fifo decorated -vE",
+            &["fifo"],
             Box::new(test_fifo_decorated),
         ),
         (
             "binary passthrough pipe",
+            &["pipe", "binary"],
             Box::new(test_binary_passthrough),
         ),
         (
             "mixed stdin and file numbering",
+            &["stdin"],
             Box::new(test_mixed_stdin_file_numbering),
         ),
         (
             "no newline + -n",
+            &[],
             Box::new(|h| {
                 h.compare_with_cat(&["-n", h.fixtures.no_newline.to_str().unwrap()], None)
             }),
         ),
         (
             "no newline + -b",
+            &[],
             Box::new(|h| {
                 h.compare_with_cat(&["-b", h.fixtures.no_newline.to_str().unwrap()], None)
             }),
         ),
         (
             "-s with no blanks",
+            &[],
             Box::new(|h| h.compare_with_cat(&["-s", h.fixtures.sample_a.to_str().unwrap()], None)),
         ),
         (
             "combo -ns across files",
+            &[],
             Box::new(|h| {
                 h.compare_with_cat(
                     &[
@@ -586,64 +1124,79 @@ fn run_tests(filter: Option<String>) -> Result<()> {
         ),
         (
             "binary with -v",
+            &["binary"],
             Box::new(|h| h.compare_with_cat(&["-v", h.fixtures.binary.to_str().unwrap()], None)),
         ),
         (
             "binary with -A",
+            &["binary"],
             Box::new(|h| h.compare_with_cat(&["-A", h.fixtures.binary.to_str().unwrap()], None)),
         ),
-        ("visible DEL", Box::new(test_visible_del)),
-        ("line state across files", Box::new(test_line_state_across_files)),
-        ("squeeze across files", Box::new(test_squeeze_across_files)),
-        ("number nonblank across files", Box::new(test_b_across_files)),
-        ("squeeze + no newline boundary", Box::new(test_squeeze_no_newline_boundary)),
-        ("large line numbers", Box::new(test_large_line_numbers)),
+        ("visible DEL", &[], Box::new(test_visible_del)),
+        ("line state across files", &[], Box::new(test_line_state_across_files)),
+        ("show-ends seam after no-newline file", &[], Box::new(test_show_ends_seam)),
+        ("squeeze across files", &[], Box::new(test_squeeze_across_files)),
+        ("number nonblank across files", &[], Box::new(test_b_across_files)),
+        ("squeeze + no newline boundary", &[], Box::new(test_squeeze_no_newline_boundary)),
+        ("large line numbers", &["large"], Box::new(test_large_line_numbers)),
+        ("line number width growth", &["large"], Box::new(test_number_width_growth)),
         (
             "combo -nE",
+            &[],
             Box::new(|h| h.compare_with_cat(&["-nE", h.fixtures.blank.to_str().unwrap()], None)),
         ),
         (
             "combo -bE",
+            &[],
             Box::new(|h| h.compare_with_cat(&["-bE", h.fixtures.blank.to_str().unwrap()], None)),
         ),
         (
             "combo -bT",
+            &[],
             Box::new(|h| h.compare_with_cat(&["-bT", h.fixtures.tabs.to_str().unwrap()], None)),
         ),
         (
             "combo -sE",
+            &[],
             Box::new(|h| h.compare_with_cat(&["-sE", h.fixtures.blank.to_str().unwrap()], None)),
         ),
         (
             "combo -sT",
+            &[],
             Box::new(|h| h.compare_with_cat(&["-sT", h.fixtures.tabs.to_str().unwrap()], None)),
         ),
         (
             "combo -A -s",
+            &[],
             Box::new(|h| h.compare_with_cat(&["-As", h.fixtures.blank.to_str().unwrap()], None)),
         ),
         (
             "combo -e -s",
+            &[],
             Box::new(|h| h.compare_with_cat(&["-es", h.fixtures.blank.to_str().unwrap()], None)),
         ),
         (
             "combo -t -s",
+            &[],
             Box::new(|h| h.compare_with_cat(&["-ts", h.fixtures.blank.to_str().unwrap()], None)),
         ),
         (
             "order -b then -n",
+            &[],
             Box::new(|h| {
                 h.compare_with_cat(&["-b", "-n", h.fixtures.blank.to_str().unwrap()], None)
             }),
         ),
         (
             "order -n then -b",
+            &[],
             Box::new(|h| {
                 h.compare_with_cat(&["-n", "-b", h.fixtures.blank.to_str().unwrap()], None)
             }),
         ),
         (
             "order --number then --number-nonblank",
+            &["long-option"],
             Box::new(|h| {
                 h.compare_with_cat(
                     &["--number", "--number-nonblank", h.fixtures.blank.to_str().unwrap()],
@@ -653,6 +1206,7 @@ fn run_tests(filter: Option<String>) -> Result<()> {
         ),
         (
             "order --number-nonblank then --number",
+            &["long-option"],
             Box::new(|h| {
                 h.compare_with_cat(
                     &["--number-nonblank", "--number", h.fixtures.blank.to_str().unwrap()],
@@ -662,98 +1216,112 @@ fn run_tests(filter: Option<String>) -> Result<()> {
         ),
         (
             "order -s then -n",
+            &[],
             Box::new(|h| {
                 h.compare_with_cat(&["-s", "-n", h.fixtures.blank.to_str().unwrap()], None)
             }),
         ),
         (
             "order -n then -s",
+            &[],
             Box::new(|h| {
                 h.compare_with_cat(&["-n", "-s", h.fixtures.blank.to_str().unwrap()], None)
             }),
         ),
         (
             "order -E then -T",
+            &[],
             Box::new(|h| h.compare_with_cat(&["-E", "-T", h.fixtures.tabs.to_str().unwrap()], None)),
         ),
         (
             "order -T then -E",
+            &[],
             Box::new(|h| h.compare_with_cat(&["-T", "-E", h.fixtures.tabs.to_str().unwrap()], None)),
         ),
         (
             "combo -nsvE",
+            &[],
             Box::new(|h| h.compare_with_cat(&["-nsvE", h.fixtures.control.to_str().unwrap()], None)),
         ),
         (
             "combo -bsvE",
+            &[],
             Box::new(|h| h.compare_with_cat(&["-bsvE", h.fixtures.control.to_str().unwrap()], None)),
         ),
-        ("file named --help with --", Box::new(test_file_named_help)),
-        ("file named --version with --", Box::new(test_file_named_version)),
-        ("double dash then dash", Box::new(test_double_dash_then_dash)),
-        ("stdin with option between dashes", Box::new(test_option_between_dashes)),
-        ("space in filename", Box::new(test_space_in_filename)),
+        ("file named --help with --", &[], Box::new(test_file_named_help)),
+        ("file named --version with --", &[], Box::new(test_file_named_version)),
+        ("files named like long options without --", &["long-option"], Box::new(test_long_option_named_files)),
+        ("double dash then dash", &[], Box::new(test_double_dash_then_dash)),
+        ("stdin with option between dashes", &["stdin"], Box::new(test_option_between_dashes)),
+        ("space in filename", &[], Box::new(test_space_in_filename)),
         (
             "multiple stdin operands with options",
+            &["stdin"],
             Box::new(test_multiple_stdin_operands_with_options),
         ),
-        ("ENOTDIR path", Box::new(test_enotdir_path)),
-        ("ELOOP symlink", Box::new(test_eloop_symlink)),
-        ("directory operand with -n", Box::new(test_directory_operand_numbered)),
-        ("directory operand with -v", Box::new(test_directory_operand_visible)),
-        ("missing file with -n", Box::new(test_missing_file_numbered)),
+        ("ENOTDIR path", &["error"], Box::new(test_enotdir_path)),
+        ("ELOOP symlink", &["error"], Box::new(test_eloop_symlink)),
+        ("socket operand ENXIO", &["error"], Box::new(test_socket_operand)),
+        ("directory operand with -n", &["error"], Box::new(test_directory_operand_numbered)),
+        ("directory operand with -v", &["error"], Box::new(test_directory_operand_visible)),
+        ("missing file with -n", &["error"], Box::new(test_missing_file_numbered)),
         (
             "missing file with -v among files",
+            &["error"],
             Box::new(test_missing_file_visible_among_files),
         ),
-        ("visible CR", Box::new(test_visible_cr)),
-        ("visible NUL", Box::new(test_visible_nul)),
-        ("visible 0xFF", Box::new(test_visible_ff)),
-        ("tabs without newline -T", Box::new(test_tabs_no_newline_t)),
-        ("tabs without newline -A", Box::new(test_tabs_no_newline_a)),
-        ("long line no newline -n", Box::new(test_long_line_no_newline_number)),
-        ("only newlines file -s", Box::new(test_only_newlines_file_s)),
-        ("stdin only newlines -s", Box::new(test_stdin_only_newlines_s)),
-        ("stdin empty with -n", Box::new(test_stdin_empty_numbered)),
-        ("binary with -T", Box::new(test_binary_show_tabs)),
-        ("binary with -E", Box::new(test_binary_show_ends)),
-        ("fifo squeeze blank", Box::new(test_fifo_squeeze_blank)),
-        ("fifo show ends", Box::new(test_fifo_show_ends)),
-        ("fifo numbered show ends", Box::new(test_fifo_number_show_ends)),
-        ("--show-ends stdin", Box::new(|h| {
+        ("visible CR", &[], Box::new(test_visible_cr)),
+        ("visible NUL", &["binary"], Box::new(test_visible_nul)),
+        ("visible 0xFF", &["binary"], Box::new(test_visible_ff)),
+        ("tabs without newline -T", &[], Box::new(test_tabs_no_newline_t)),
+        ("leading tab with -n -T", &[], Box::new(test_number_then_show_tabs)),
+        ("tabs without newline -A", &[], Box::new(test_tabs_no_newline_a)),
+        ("long line no newline -n", &[], Box::new(test_long_line_no_newline_number)),
+        ("line spanning many reads", &["large"], Box::new(test_line_spanning_reads)),
+        ("/proc files with zero st_size", &[], Box::new(test_proc_zero_size)),
+        ("only newlines file -s", &[], Box::new(test_only_newlines_file_s)),
+        ("stdin only newlines -s", &["stdin"], Box::new(test_stdin_only_newlines_s)),
+        ("stdin empty with -n", &["stdin"], Box::new(test_stdin_empty_numbered)),
+        ("stdin empty with decorations", &["stdin"], Box::new(test_stdin_empty_decorated)),
+        ("binary with -T", &["binary"], Box::new(test_binary_show_tabs)),
+        ("binary with -E", &["binary"], Box::new(test_binary_show_ends)),
+        ("fifo squeeze blank", &["fifo"], Box::new(test_fifo_squeeze_blank)),
+        ("fifo show ends", &["fifo"], Box::new(test_fifo_show_ends)),
+        ("fifo numbered show ends", &["fifo"], Box::new(test_fifo_number_show_ends)),
+        ("--show-ends stdin", &["stdin", "long-option"], Box::new(|h| {
             h.compare_with_cat(&["--show-ends", "-"], Some(&h.fixtures.stdin_data))
         })),
-        ("--show-tabs stdin", Box::new(|h| {
+        ("--show-tabs stdin", &["stdin", "long-option"], Box::new(|h| {
             h.compare_with_cat(&["--show-tabs", "-"], Some(&fs::read(&h.fixtures.tabs)?))
         })),
-        ("--show-nonprinting stdin", Box::new(|h| {
+        ("--show-nonprinting stdin", &["stdin", "long-option"], Box::new(|h| {
             h.compare_with_cat(&["--show-nonprinting", "-"], Some(&fs::read(&h.fixtures.control)?))
         })),
-        ("--show-all stdin", Box::new(|h| {
+        ("--show-all stdin", &["stdin", "long-option"], Box::new(|h| {
             h.compare_with_cat(&["--show-all", "-"], Some(&fs::read(&h.fixtures.control)?))
         })),
-        ("--number-nonblank stdin", Box::new(|h| {
+        ("--number-nonblank stdin", &["stdin", "long-option"], Box::new(|h| {
             h.compare_with_cat(&["--number-nonblank", "-"], Some(&fs::read(&h.fixtures.blank)?))
         })),
-        ("--squeeze-blank stdin", Box::new(|h| {
+        ("--squeeze-blank stdin", &["stdin", "long-option"], Box::new(|h| {
             h.compare_with_cat(&["--squeeze-blank", "-"], Some(&fs::read(&h.fixtures.blank)?))
         })),
-        ("repeat -n", Box::new(|h| {
+        ("repeat -n", &[], Box::new(|h| {
             h.compare_with_cat(&["-nn", h.fixtures.blank.to_str().unwrap()], None)
         })),
-        ("--number --show-ends", Box::new(|h| {
+        ("--number --show-ends", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &["--number", "--show-ends", h.fixtures.blank.to_str().unwrap()],
                 None,
             )
         })),
-        ("--number --show-tabs", Box::new(|h| {
+        ("--number --show-tabs", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &["--number", "--show-tabs", h.fixtures.tabs.to_str().unwrap()],
                 None,
             )
         })),
-        ("--number --show-nonprinting", Box::new(|h| {
+        ("--number --show-nonprinting", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &[
                     "--number",
@@ -763,13 +1331,13 @@ fn run_tests(filter: Option<String>) -> Result<()> {
                 None,
             )
         })),
-        ("--number --show-all", Box::new(|h| {
+        ("--number --show-all", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &["--number", "--show-all", h.fixtures.control.to_str().unwrap()],
                 None,
             )
         })),
-        ("--number-nonblank --show-ends", Box::new(|h| {
+        ("--number-nonblank --show-ends", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &[
                     "--number-nonblank",
@@ -779,7 +1347,7 @@ fn run_tests(filter: Option<String>) -> Result<()> {
                 None,
             )
         })),
-        ("--number-nonblank --show-tabs", Box::new(|h| {
+        ("--number-nonblank --show-tabs", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &[
                     "--number-nonblank",
@@ -789,7 +1357,7 @@ fn run_tests(filter: Option<String>) -> Result<()> {
                 None,
             )
         })),
-        ("--number-nonblank --show-nonprinting", Box::new(|h| {
+        ("--number-nonblank --show-nonprinting", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &[
                     "--number-nonblank",
@@ -799,7 +1367,7 @@ fn run_tests(filter: Option<String>) -> Result<()> {
                 None,
             )
         })),
-        ("--number-nonblank --show-all", Box::new(|h| {
+        ("--number-nonblank --show-all", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &[
                     "--number-nonblank",
@@ -809,13 +1377,13 @@ fn run_tests(filter: Option<String>) -> Result<()> {
                 None,
             )
         })),
-        ("--squeeze-blank --number", Box::new(|h| {
+        ("--squeeze-blank --number", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &["--squeeze-blank", "--number", h.fixtures.blank.to_str().unwrap()],
                 None,
             )
         })),
-        ("--squeeze-blank --number-nonblank", Box::new(|h| {
+        ("--squeeze-blank --number-nonblank", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &[
                     "--squeeze-blank",
@@ -825,7 +1393,7 @@ fn run_tests(filter: Option<String>) -> Result<()> {
                 None,
             )
         })),
-        ("--squeeze-blank --show-ends", Box::new(|h| {
+        ("--squeeze-blank --show-ends", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &[
                     "--squeeze-blank",
@@ -835,7 +1403,7 @@ fn run_tests(filter: Option<String>) -> Result<()> {
                 None,
             )
         })),
-        ("--squeeze-blank --show-tabs", Box::new(|h| {
+        ("--squeeze-blank --show-tabs", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &[
                     "--squeeze-blank",
@@ -845,7 +1413,7 @@ fn run_tests(filter: Option<String>) -> Result<()> {
                 None,
             )
         })),
-        ("--squeeze-blank --show-nonprinting", Box::new(|h| {
+        ("--squeeze-blank --show-nonprinting", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &[
                     "--squeeze-blank",
@@ -855,7 +1423,7 @@ fn run_tests(filter: Option<String>) -> Result<()> {
                 None,
             )
         })),
-        ("--squeeze-blank --show-all", Box::new(|h| {
+        ("--squeeze-blank --show-all", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &[
                     "--squeeze-blank",
@@ -865,67 +1433,67 @@ fn run_tests(filter: Option<String>) -> Result<()> {
                 None,
             )
         })),
-        ("stdin + file --number", Box::new(|h| {
+        ("stdin + file --number", &["stdin", "long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &["--number", "-", h.fixtures.sample_a.to_str().unwrap()],
                 Some(&h.fixtures.stdin_data),
             )
         })),
-        ("stdin + file --number-nonblank", Box::new(|h| {
+        ("stdin + file --number-nonblank", &["stdin", "long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &["--number-nonblank", "-", h.fixtures.sample_a.to_str().unwrap()],
                 Some(&fs::read(&h.fixtures.blank)?),
             )
         })),
-        ("stdin + file --show-ends", Box::new(|h| {
+        ("stdin + file --show-ends", &["stdin", "long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &["--show-ends", "-", h.fixtures.sample_a.to_str().unwrap()],
                 Some(&h.fixtures.stdin_data),
             )
         })),
-        ("stdin + file --show-tabs", Box::new(|h| {
+        ("stdin + file --show-tabs", &["stdin", "long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &["--show-tabs", "-", h.fixtures.sample_a.to_str().unwrap()],
                 Some(&fs::read(&h.fixtures.tabs)?),
             )
         })),
-        ("stdin + file --show-nonprinting", Box::new(|h| {
+        ("stdin + file --show-nonprinting", &["stdin", "long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &["--show-nonprinting", "-", h.fixtures.sample_a.to_str().unwrap()],
                 Some(&fs::read(&h.fixtures.control)?),
             )
         })),
-        ("stdin + file --show-all", Box::new(|h| {
+        ("stdin + file --show-all", &["stdin", "long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &["--show-all", "-", h.fixtures.sample_a.to_str().unwrap()],
                 Some(&fs::read(&h.fixtures.control)?),
             )
         })),
-        ("number across empty then data", Box::new(|h| {
-            let empty = h.fixtures.dir.path().join("empty_then_data.txt");
+        ("number across empty then data", &[], Box::new(|h| {
+            let empty = h.scratch().join("empty_then_data.txt");
             fs::write(&empty, b"")?;
             h.compare_with_cat(
                 &["-n", empty.to_str().unwrap(), h.fixtures.sample_a.to_str().unwrap()],
                 None,
             )
         })),
-        ("number-nonblank across empty then data", Box::new(|h| {
-            let empty = h.fixtures.dir.path().join("empty_then_data_b.txt");
+        ("number-nonblank across empty then data", &[], Box::new(|h| {
+            let empty = h.scratch().join("empty_then_data_b.txt");
             fs::write(&empty, b"")?;
             h.compare_with_cat(
                 &["-b", empty.to_str().unwrap(), h.fixtures.sample_a.to_str().unwrap()],
                 None,
             )
         })),
-        ("squeeze across empty then blank", Box::new(|h| {
-            let empty = h.fixtures.dir.path().join("empty_then_blank.txt");
+        ("squeeze across empty then blank", &[], Box::new(|h| {
+            let empty = h.scratch().join("empty_then_blank.txt");
             fs::write(&empty, b"")?;
             h.compare_with_cat(
                 &["-s", empty.to_str().unwrap(), h.fixtures.blank.to_str().unwrap()],
                 None,
             )
         })),
-        ("show-ends across no-newline then file", Box::new(|h| {
+        ("show-ends across no-newline then file", &[], Box::new(|h| {
             h.compare_with_cat(
                 &[
                     "-E",
@@ -935,7 +1503,7 @@ fn run_tests(filter: Option<String>) -> Result<()> {
                 None,
             )
         })),
-        ("number across no-newline then file", Box::new(|h| {
+        ("number across no-newline then file", &[], Box::new(|h| {
             h.compare_with_cat(
                 &[
                     "-n",
@@ -945,7 +1513,7 @@ fn run_tests(filter: Option<String>) -> Result<()> {
                 None,
             )
         })),
-        ("number-nonblank across no-newline then file", Box::new(|h| {
+        ("number-nonblank across no-newline then file", &[], Box::new(|h| {
             h.compare_with_cat(
                 &[
                     "-b",
@@ -955,120 +1523,122 @@ fn run_tests(filter: Option<String>) -> Result<()> {
                 None,
             )
         })),
-        ("symlink to file", Box::new(test_symlink_to_file)),
-        ("symlink to directory", Box::new(test_symlink_to_dir)),
-        ("hardlink to file", Box::new(test_hardlink_to_file)),
-        ("dev null operand", Box::new(|h| h.compare_with_cat(&["/dev/null"], None))),
-        ("fifo number nonblank", Box::new(test_fifo_number_nonblank)),
-        ("file named --show-ends with --", Box::new(|h| {
-            let path = h.fixtures.dir.path().join("--show-ends");
+        ("symlink to file", &[], Box::new(test_symlink_to_file)),
+        ("symlink to directory", &["error"], Box::new(test_symlink_to_dir)),
+        ("hardlink to file", &[], Box::new(test_hardlink_to_file)),
+        ("dev null operand", &[], Box::new(|h| h.compare_with_cat(&["/dev/null"], None))),
+        ("--max-bytes stops /dev/zero", &["extension"], Box::new(test_max_bytes_dev_zero)),
+        ("character device operands", &[], Box::new(test_char_device_operand)),
+        ("fifo number nonblank", &["fifo"], Box::new(test_fifo_number_nonblank)),
+        ("file named --show-ends with --", &[], Box::new(|h| {
+            let path = h.scratch().join("--show-ends");
             fs::write(&path, b"show ends file\n")?;
             h.compare_with_cat(&["--", path.to_str().unwrap()], None)
         })),
-        ("crlf file -E", Box::new(|h| {
-            let path = h.fixtures.dir.path().join("crlf_e.txt");
+        ("crlf file -E", &[], Box::new(|h| {
+            let path = h.scratch().join("crlf_e.txt");
             fs::write(&path, b"one\\r\\ntwo\\r\\n")?;
             h.compare_with_cat(&["-E", path.to_str().unwrap()], None)
         })),
-        ("crlf file -v", Box::new(|h| {
-            let path = h.fixtures.dir.path().join("crlf_v.txt");
+        ("crlf file -v", &[], Box::new(|h| {
+            let path = h.scratch().join("crlf_v.txt");
             fs::write(&path, b"one\\r\\ntwo\\r\\n")?;
             h.compare_with_cat(&["-v", path.to_str().unwrap()], None)
         })),
-        ("tabs + control -A", Box::new(|h| {
-            let path = h.fixtures.dir.path().join("tabs_control.txt");
+        ("tabs + control -A", &["binary"], Box::new(|h| {
+            let path = h.scratch().join("tabs_control.txt");
             fs::write(&path, b"tab\\t\\x01\\n")?;
             h.compare_with_cat(&["-A", path.to_str().unwrap()], None)
         })),
-        ("utf8 bytes -v", Box::new(|h| {
-            let path = h.fixtures.dir.path().join("utf8_v.txt");
+        ("utf8 bytes -v", &["binary"], Box::new(|h| {
+            let path = h.scratch().join("utf8_v.txt");
             fs::write(&path, [0xc3, 0xa9, b'\n'])?;
             h.compare_with_cat(&["-v", path.to_str().unwrap()], None)
         })),
-        ("nul file -A", Box::new(|h| {
-            let path = h.fixtures.dir.path().join("nul_a.txt");
+        ("nul file -A", &["binary"], Box::new(|h| {
+            let path = h.scratch().join("nul_a.txt");
             fs::write(&path, b"nul\\0end\\n")?;
             h.compare_with_cat(&["-A", path.to_str().unwrap()], None)
         })),
-        ("redundant --number -n", Box::new(|h| {
+        ("redundant --number -n", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &["--number", "-n", h.fixtures.blank.to_str().unwrap()],
                 None,
             )
         })),
-        ("redundant --number-nonblank -b", Box::new(|h| {
+        ("redundant --number-nonblank -b", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &["--number-nonblank", "-b", h.fixtures.blank.to_str().unwrap()],
                 None,
             )
         })),
-        ("redundant --squeeze-blank -s", Box::new(|h| {
+        ("redundant --squeeze-blank -s", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &["--squeeze-blank", "-s", h.fixtures.blank.to_str().unwrap()],
                 None,
             )
         })),
-        ("redundant --show-ends -E", Box::new(|h| {
+        ("redundant --show-ends -E", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &["--show-ends", "-E", h.fixtures.blank.to_str().unwrap()],
                 None,
             )
         })),
-        ("redundant --show-tabs -T", Box::new(|h| {
+        ("redundant --show-tabs -T", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &["--show-tabs", "-T", h.fixtures.tabs.to_str().unwrap()],
                 None,
             )
         })),
-        ("redundant --show-nonprinting -v", Box::new(|h| {
+        ("redundant --show-nonprinting -v", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &["--show-nonprinting", "-v", h.fixtures.control.to_str().unwrap()],
                 None,
             )
         })),
-        ("redundant --show-all -A", Box::new(|h| {
+        ("redundant --show-all -A", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &["--show-all", "-A", h.fixtures.control.to_str().unwrap()],
                 None,
             )
         })),
-        ("combo -e -t", Box::new(|h| {
+        ("combo -e -t", &[], Box::new(|h| {
             h.compare_with_cat(&["-e", "-t", h.fixtures.tabs.to_str().unwrap()], None)
         })),
-        ("combo -t -e", Box::new(|h| {
+        ("combo -t -e", &[], Box::new(|h| {
             h.compare_with_cat(&["-t", "-e", h.fixtures.tabs.to_str().unwrap()], None)
         })),
-        ("combo -e -n", Box::new(|h| {
+        ("combo -e -n", &[], Box::new(|h| {
             h.compare_with_cat(&["-e", "-n", h.fixtures.blank.to_str().unwrap()], None)
         })),
-        ("combo -t -n", Box::new(|h| {
+        ("combo -t -n", &[], Box::new(|h| {
             h.compare_with_cat(&["-t", "-n", h.fixtures.tabs.to_str().unwrap()], None)
         })),
-        ("combo -e -b", Box::new(|h| {
+        ("combo -e -b", &[], Box::new(|h| {
             h.compare_with_cat(&["-e", "-b", h.fixtures.blank.to_str().unwrap()], None)
         })),
-        ("combo -t -b", Box::new(|h| {
+        ("combo -t -b", &[], Box::new(|h| {
             h.compare_with_cat(&["-t", "-b", h.fixtures.tabs.to_str().unwrap()], None)
         })),
-        ("combo -u -n", Box::new(|h| {
+        ("combo -u -n", &[], Box::new(|h| {
             h.compare_with_cat(&["-u", "-n", h.fixtures.blank.to_str().unwrap()], None)
         })),
-        ("combo -u -b", Box::new(|h| {
+        ("combo -u -b", &[], Box::new(|h| {
             h.compare_with_cat(&["-u", "-b", h.fixtures.blank.to_str().unwrap()], None)
         })),
-        ("combo -u -s", Box::new(|h| {
+        ("combo -u -s", &[], Box::new(|h| {
             h.compare_with_cat(&["-u", "-s", h.fixtures.blank.to_str().unwrap()], None)
         })),
-        ("combo -u -v", Box::new(|h| {
+        ("combo -u -v", &[], Box::new(|h| {
             h.compare_with_cat(&["-u", "-v", h.fixtures.control.to_str().unwrap()], None)
         })),
-        ("combo -u --show-all", Box::new(|h| {
+        ("combo -u --show-all", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &["-u", "--show-all", h.fixtures.control.to_str().unwrap()],
                 None,
             )
         })),
-        ("file stdin file --number", Box::new(|h| {
+        ("file stdin file --number", &["stdin", "long-option"], Box::new(|h| {
             let mut stdin_payload = Vec::new();
             stdin_payload.extend_from_slice(b"stdin line 1\\n");
             stdin_payload.extend_from_slice(b"stdin line 2\\n");
@@ -1082,7 +1652,7 @@ fn run_tests(filter: Option<String>) -> Result<()> {
                 Some(&stdin_payload),
             )
         })),
-        ("file stdin file --number-nonblank", Box::new(|h| {
+        ("file stdin file --number-nonblank", &["stdin", "long-option"], Box::new(|h| {
             let mut stdin_payload = Vec::new();
             stdin_payload.extend_from_slice(b"\\nstdin\\n\\n");
             h.compare_with_cat(
@@ -1095,7 +1665,7 @@ fn run_tests(filter: Option<String>) -> Result<()> {
                 Some(&stdin_payload),
             )
         })),
-        ("file stdin file --squeeze-blank", Box::new(|h| {
+        ("file stdin file --squeeze-blank", &["stdin", "long-option"], Box::new(|h| {
             let mut stdin_payload = Vec::new();
             stdin_payload.extend_from_slice(b"line1\\n\\n\\nline2\\n");
             h.compare_with_cat(
@@ -1108,7 +1678,7 @@ fn run_tests(filter: Option<String>) -> Result<()> {
                 Some(&stdin_payload),
             )
         })),
-        ("file stdin file --show-ends", Box::new(|h| {
+        ("file stdin file --show-ends", &["stdin", "long-option"], Box::new(|h| {
             let stdin_payload = b"stdin\\n".to_vec();
             h.compare_with_cat(
                 &[
@@ -1120,7 +1690,7 @@ fn run_tests(filter: Option<String>) -> Result<()> {
                 Some(&stdin_payload),
             )
         })),
-        ("file stdin file --show-tabs", Box::new(|h| {
+        ("file stdin file --show-tabs", &["stdin", "long-option"], Box::new(|h| {
             let tabs = fs::read(&h.fixtures.tabs)?;
             h.compare_with_cat(
                 &[
@@ -1132,7 +1702,7 @@ fn run_tests(filter: Option<String>) -> Result<()> {
                 Some(&tabs),
             )
         })),
-        ("file stdin file --show-all", Box::new(|h| {
+        ("file stdin file --show-all", &["stdin", "long-option"], Box::new(|h| {
             let control = fs::read(&h.fixtures.control)?;
             h.compare_with_cat(
                 &[
@@ -1144,93 +1714,93 @@ fn run_tests(filter: Option<String>) -> Result<()> {
                 Some(&control),
             )
         })),
-        ("file named --number with --", Box::new(|h| {
-            let path = h.fixtures.dir.path().join("--number");
+        ("file named --number with --", &[], Box::new(|h| {
+            let path = h.scratch().join("--number");
             fs::write(&path, b"number file\\n")?;
             h.compare_with_cat(&["--", path.to_str().unwrap()], None)
         })),
-        ("file named --show-tabs with --", Box::new(|h| {
-            let path = h.fixtures.dir.path().join("--show-tabs");
+        ("file named --show-tabs with --", &[], Box::new(|h| {
+            let path = h.scratch().join("--show-tabs");
             fs::write(&path, b"tabs file\\n")?;
             h.compare_with_cat(&["--", path.to_str().unwrap()], None)
         })),
-        ("file named --squeeze-blank with --", Box::new(|h| {
-            let path = h.fixtures.dir.path().join("--squeeze-blank");
+        ("file named --squeeze-blank with --", &[], Box::new(|h| {
+            let path = h.scratch().join("--squeeze-blank");
             fs::write(&path, b"squeeze file\\n")?;
             h.compare_with_cat(&["--", path.to_str().unwrap()], None)
         })),
-        ("file named -e with --", Box::new(|h| {
-            let path = h.fixtures.dir.path().join("-e");
+        ("file named -e with --", &[], Box::new(|h| {
+            let path = h.scratch().join("-e");
             fs::write(&path, b"dash e file\\n")?;
             h.compare_with_cat(&["--", path.to_str().unwrap()], None)
         })),
-        ("file named -- with -n", Box::new(|h| {
-            let path = h.fixtures.dir.path().join("--");
+        ("file named -- with -n", &[], Box::new(|h| {
+            let path = h.scratch().join("--");
             fs::write(&path, b"double dash file\\n")?;
             h.compare_with_cat(&["-n", "--", path.to_str().unwrap()], None)
         })),
-        ("file named --number with -n", Box::new(|h| {
-            let path = h.fixtures.dir.path().join("--number");
+        ("file named --number with -n", &[], Box::new(|h| {
+            let path = h.scratch().join("--number");
             fs::write(&path, b"number file\\n")?;
             h.compare_with_cat(&["-n", "--", path.to_str().unwrap()], None)
         })),
-        ("stdin -A tabs", Box::new(|h| {
+        ("stdin -A tabs", &["stdin"], Box::new(|h| {
             let tabs = fs::read(&h.fixtures.tabs)?;
             h.compare_with_cat(&["-A", "-"], Some(&tabs))
         })),
-        ("stdin -b blanks", Box::new(|h| {
+        ("stdin -b blanks", &["stdin"], Box::new(|h| {
             let blank = fs::read(&h.fixtures.blank)?;
             h.compare_with_cat(&["-b", "-"], Some(&blank))
         })),
-        ("stdin -nE blanks", Box::new(|h| {
+        ("stdin -nE blanks", &["stdin"], Box::new(|h| {
             let blank = fs::read(&h.fixtures.blank)?;
             h.compare_with_cat(&["-nE", "-"], Some(&blank))
         })),
-        ("stdin -bE blanks", Box::new(|h| {
+        ("stdin -bE blanks", &["stdin"], Box::new(|h| {
             let blank = fs::read(&h.fixtures.blank)?;
             h.compare_with_cat(&["-bE", "-"], Some(&blank))
         })),
-        ("stdin -sE blanks", Box::new(|h| {
+        ("stdin -sE blanks", &["stdin"], Box::new(|h| {
             let blank = fs::read(&h.fixtures.blank)?;
             h.compare_with_cat(&["-sE", "-"], Some(&blank))
         })),
-        ("stdin -v binary", Box::new(|h| {
+        ("stdin -v binary", &["stdin", "binary"], Box::new(|h| {
             let binary = fs::read(&h.fixtures.binary)?;
             h.compare_with_cat(&["-v", "-"], Some(&binary))
         })),
-        ("stdin --show-ends no newline", Box::new(|h| {
+        ("stdin --show-ends no newline", &["stdin", "long-option"], Box::new(|h| {
             let no_newline = fs::read(&h.fixtures.no_newline)?;
             h.compare_with_cat(&["--show-ends", "-"], Some(&no_newline))
         })),
-        ("crlf file -A", Box::new(|h| {
-            let path = h.fixtures.dir.path().join("crlf_a.txt");
+        ("crlf file -A", &[], Box::new(|h| {
+            let path = h.scratch().join("crlf_a.txt");
             fs::write(&path, b"one\\r\\ntwo\\r\\n")?;
             h.compare_with_cat(&["-A", path.to_str().unwrap()], None)
         })),
-        ("trailing spaces -E", Box::new(|h| {
-            let path = h.fixtures.dir.path().join("trail_spaces.txt");
+        ("trailing spaces -E", &[], Box::new(|h| {
+            let path = h.scratch().join("trail_spaces.txt");
             fs::write(&path, b"space  \\t \\nnext line  \\n")?;
             h.compare_with_cat(&["-E", path.to_str().unwrap()], None)
         })),
-        ("leading blanks -b", Box::new(|h| {
-            let path = h.fixtures.dir.path().join("leading_blanks.txt");
+        ("leading blanks -b", &[], Box::new(|h| {
+            let path = h.scratch().join("leading_blanks.txt");
             fs::write(&path, b"\\n\\nstart\\n\\nend\\n")?;
             h.compare_with_cat(&["-b", path.to_str().unwrap()], None)
         })),
-        ("only tabs -T", Box::new(|h| {
-            let path = h.fixtures.dir.path().join("only_tabs.txt");
+        ("only tabs -T", &[], Box::new(|h| {
+            let path = h.scratch().join("only_tabs.txt");
             fs::write(&path, b"\\t\\t\\n\\tend\\n")?;
             h.compare_with_cat(&["-T", path.to_str().unwrap()], None)
         })),
-        ("tabs + blanks -sT", Box::new(|h| {
-            let path = h.fixtures.dir.path().join("tabs_blanks.txt");
+        ("tabs + blanks -sT", &[], Box::new(|h| {
+            let path = h.scratch().join("tabs_blanks.txt");
             fs::write(&path, b"\\n\\n\\tcol\\n\\n\\n")?;
             h.compare_with_cat(&["-sT", path.to_str().unwrap()], None)
         })),
-        ("squeeze across three files", Box::new(|h| {
-            let a = h.fixtures.dir.path().join("squeeze_three_a.txt");
-            let b = h.fixtures.dir.path().join("squeeze_three_b.txt");
-            let c = h.fixtures.dir.path().join("squeeze_three_c.txt");
+        ("squeeze across three files", &[], Box::new(|h| {
+            let a = h.scratch().join("squeeze_three_a.txt");
+            let b = h.scratch().join("squeeze_three_b.txt");
+            let c = h.scratch().join("squeeze_three_c.txt");
             fs::write(&a, b"line1\\n\\n")?;
             fs::write(&b, b"\\n\\nline2\\n")?;
             fs::write(&c, b"\\n\\nline3\\n")?;
@@ -1244,22 +1814,22 @@ fn run_tests(filter: Option<String>) -> Result<()> {
                 None,
             )
         })),
-        ("no newline + -A", Box::new(|h| {
+        ("no newline + -A", &[], Box::new(|h| {
             h.compare_with_cat(&["-A", h.fixtures.no_newline.to_str().unwrap()], None)
         })),
-        ("visible formfeed", Box::new(|h| {
-            let path = h.fixtures.dir.path().join("formfeed.txt");
+        ("visible formfeed", &[], Box::new(|h| {
+            let path = h.scratch().join("formfeed.txt");
             fs::write(&path, b"form\\x0cfeed\\n")?;
             h.compare_with_cat(&["-v", path.to_str().unwrap()], None)
         })),
-        ("symlink chain", Box::new(test_symlink_chain)),
-        ("relative symlink", Box::new(test_relative_symlink)),
-        ("fifo show all", Box::new(test_fifo_show_all)),
-        ("fifo show tabs and ends", Box::new(test_fifo_show_tabs_ends)),
-        ("directory operand with -E", Box::new(|h| {
+        ("symlink chain", &[], Box::new(test_symlink_chain)),
+        ("relative symlink", &[], Box::new(test_relative_symlink)),
+        ("fifo show all", &["fifo"], Box::new(test_fifo_show_all)),
+        ("fifo show tabs and ends", &["fifo"], Box::new(test_fifo_show_tabs_ends)),
+        ("directory operand with -E", &["error"], Box::new(|h| {
             h.compare_with_cat(&["-E", h.fixtures.dir_path.to_str().unwrap()], None)
         })),
-        ("redundant --show-ends --show-ends", Box::new(|h| {
+        ("redundant --show-ends --show-ends", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &["--show-ends", "--show-ends", h.fixtures.blank.to_str().unwrap()],
                 None,
@@ -1267,6 +1837,7 @@ fn run_tests(filter: Option<String>) -> Result<()> {
         })),
         (
             "long combo --show-nonprinting --show-ends",
+            &["long-option"],
             Box::new(|h| {
                 h.compare_with_cat(
                     &[
@@ -1280,6 +1851,7 @@ fn run_tests(filter: Option<String>) -> Result<()> {
         ),
         (
             "long combo --show-nonprinting --show-tabs",
+            &["long-option"],
             Box::new(|h| {
                 h.compare_with_cat(
                     &[
@@ -1291,13 +1863,13 @@ fn run_tests(filter: Option<String>) -> Result<()> {
                 )
             }),
         ),
-        ("long combo --show-all --number", Box::new(|h| {
+        ("long combo --show-all --number", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &["--show-all", "--number", h.fixtures.control.to_str().unwrap()],
                 None,
             )
         })),
-        ("long combo --show-all --number-nonblank", Box::new(|h| {
+        ("long combo --show-all --number-nonblank", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &[
                     "--show-all",
@@ -1307,35 +1879,35 @@ fn run_tests(filter: Option<String>) -> Result<()> {
                 None,
             )
         })),
-        ("long combo --show-tabs --number", Box::new(|h| {
+        ("long combo --show-tabs --number", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &["--show-tabs", "--number", h.fixtures.tabs.to_str().unwrap()],
                 None,
             )
         })),
-        ("combo -A -n", Box::new(|h| {
+        ("combo -A -n", &[], Box::new(|h| {
             h.compare_with_cat(&["-A", "-n", h.fixtures.control.to_str().unwrap()], None)
         })),
-        ("combo -A -b", Box::new(|h| {
+        ("combo -A -b", &[], Box::new(|h| {
             h.compare_with_cat(&["-A", "-b", h.fixtures.control.to_str().unwrap()], None)
         })),
-        ("stdin -A control", Box::new(|h| {
+        ("stdin -A control", &["stdin", "binary"], Box::new(|h| {
             let control = fs::read(&h.fixtures.control)?;
             h.compare_with_cat(&["-A", "-"], Some(&control))
         })),
-        ("--show-ends with -n", Box::new(|h| {
+        ("--show-ends with -n", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &["--show-ends", "-n", h.fixtures.blank.to_str().unwrap()],
                 None,
             )
         })),
-        ("--show-tabs with -n", Box::new(|h| {
+        ("--show-tabs with -n", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &["--show-tabs", "-n", h.fixtures.tabs.to_str().unwrap()],
                 None,
             )
         })),
-        ("--show-nonprinting with -n", Box::new(|h| {
+        ("--show-nonprinting with -n", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &[
                     "--show-nonprinting",
@@ -1345,25 +1917,25 @@ fn run_tests(filter: Option<String>) -> Result<()> {
                 None,
             )
         })),
-        ("--show-all with -n", Box::new(|h| {
+        ("--show-all with -n", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &["--show-all", "-n", h.fixtures.control.to_str().unwrap()],
                 None,
             )
         })),
-        ("--show-ends with -b", Box::new(|h| {
+        ("--show-ends with -b", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &["--show-ends", "-b", h.fixtures.blank.to_str().unwrap()],
                 None,
             )
         })),
-        ("--show-tabs with -b", Box::new(|h| {
+        ("--show-tabs with -b", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &["--show-tabs", "-b", h.fixtures.tabs.to_str().unwrap()],
                 None,
             )
         })),
-        ("--show-nonprinting with -b", Box::new(|h| {
+        ("--show-nonprinting with -b", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &[
                     "--show-nonprinting",
@@ -1373,37 +1945,37 @@ fn run_tests(filter: Option<String>) -> Result<()> {
                 None,
             )
         })),
-        ("--show-all with -b", Box::new(|h| {
+        ("--show-all with -b", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &["--show-all", "-b", h.fixtures.control.to_str().unwrap()],
                 None,
             )
         })),
-        ("-s with --number", Box::new(|h| {
+        ("-s with --number", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &["-s", "--number", h.fixtures.blank.to_str().unwrap()],
                 None,
             )
         })),
-        ("-s with --number-nonblank", Box::new(|h| {
+        ("-s with --number-nonblank", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &["-s", "--number-nonblank", h.fixtures.blank.to_str().unwrap()],
                 None,
             )
         })),
-        ("-s with --show-ends", Box::new(|h| {
+        ("-s with --show-ends", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &["-s", "--show-ends", h.fixtures.blank.to_str().unwrap()],
                 None,
             )
         })),
-        ("-s with --show-tabs", Box::new(|h| {
+        ("-s with --show-tabs", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &["-s", "--show-tabs", h.fixtures.tabs.to_str().unwrap()],
                 None,
             )
         })),
-        ("-s with --show-nonprinting", Box::new(|h| {
+        ("-s with --show-nonprinting", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &[
                     "-s",
@@ -1413,137 +1985,546 @@ fn run_tests(filter: Option<String>) -> Result<()> {
                 None,
             )
         })),
-        ("-s with --show-all", Box::new(|h| {
+        ("-s with --show-all", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &["-s", "--show-all", h.fixtures.control.to_str().unwrap()],
                 None,
             )
         })),
-        ("double stdin --number", Box::new(|h| {
+        ("double stdin --number", &["stdin", "long-option"], Box::new(|h| {
             h.compare_with_cat(&["--number", "-", "-"], Some(&h.fixtures.stdin_data))
         })),
-        ("--show-nonprinting binary", Box::new(|h| {
+        ("--show-nonprinting binary", &["binary", "long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &["--show-nonprinting", h.fixtures.binary.to_str().unwrap()],
                 None,
             )
         })),
-        ("--show-all binary", Box::new(|h| {
+        ("--show-all binary", &["binary", "long-option"], Box::new(|h| {
             h.compare_with_cat(&["--show-all", h.fixtures.binary.to_str().unwrap()], None)
         })),
-        ("--show-tabs with no tabs", Box::new(|h| {
+        ("--show-tabs with no tabs", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &["--show-tabs", h.fixtures.sample_a.to_str().unwrap()],
                 None,
             )
         })),
-        ("--show-ends no newline file", Box::new(|h| {
+        ("--show-ends no newline file", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &["--show-ends", h.fixtures.no_newline.to_str().unwrap()],
                 None,
             )
         })),
-        ("--number empty file", Box::new(|h| {
+        ("--number empty file", &["long-option"], Box::new(|h| {
             h.compare_with_cat(&["--number", h.fixtures.empty.to_str().unwrap()], None)
         })),
-        ("--number-nonblank empty file", Box::new(|h| {
+        ("--number-nonblank empty file", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &["--number-nonblank", h.fixtures.empty.to_str().unwrap()],
                 None,
             )
         })),
-        ("--squeeze-blank empty file", Box::new(|h| {
+        ("--squeeze-blank empty file", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &["--squeeze-blank", h.fixtures.empty.to_str().unwrap()],
                 None,
             )
         })),
-        ("--show-all empty file", Box::new(|h| {
+        ("--show-all empty file", &["long-option"], Box::new(|h| {
             h.compare_with_cat(&["--show-all", h.fixtures.empty.to_str().unwrap()], None)
         })),
-        ("--show-nonprinting empty file", Box::new(|h| {
+        ("--show-nonprinting empty file", &["long-option"], Box::new(|h| {
             h.compare_with_cat(
                 &["--show-nonprinting", h.fixtures.empty.to_str().unwrap()],
                 None,
             )
         })),
-        ("only newlines file -b", Box::new(|h| {
-            let path = h.fixtures.dir.path().join("only_newlines_b.txt");
+        ("only newlines file -b", &[], Box::new(|h| {
+            let path = h.scratch().join("only_newlines_b.txt");
             fs::write(&path, b"\n\n\n\n")?;
             h.compare_with_cat(&["-b", path.to_str().unwrap()], None)
         })),
-        ("stdin only newlines -b", Box::new(|h| {
+        ("stdin only newlines -b", &["stdin"], Box::new(|h| {
             h.compare_with_cat(&["-b", "-"], Some(b"\n\n\n"))
         })),
-        ("only newlines file -n", Box::new(|h| {
-            let path = h.fixtures.dir.path().join("only_newlines_n.txt");
+        ("only newlines file -n", &[], Box::new(|h| {
+            let path = h.scratch().join("only_newlines_n.txt");
             fs::write(&path, b"\n\n\n\n")?;
             h.compare_with_cat(&["-n", path.to_str().unwrap()], None)
         })),
-        ("stdin only newlines -n", Box::new(|h| {
+        ("stdin only newlines -n", &["stdin"], Box::new(|h| {
             h.compare_with_cat(&["-n", "-"], Some(b"\n\n\n"))
         })),
-        ("stdin only newlines --number-nonblank", Box::new(|h| {
+        ("stdin only newlines --number-nonblank", &["stdin", "long-option"], Box::new(|h| {
             h.compare_with_cat(&["--number-nonblank", "-"], Some(b"\n\n\n"))
         })),
-        ("stdin only newlines --show-ends", Box::new(|h| {
+        ("stdin only newlines --show-ends", &["stdin", "long-option"], Box::new(|h| {
             h.compare_with_cat(&["--show-ends", "-"], Some(b"\n\n\n"))
         })),
-        ("file named -A with --", Box::new(|h| {
-            let path = h.fixtures.dir.path().join("-A");
+        ("file named -A with --", &[], Box::new(|h| {
+            let path = h.scratch().join("-A");
             fs::write(&path, b"dash A file\n")?;
             h.compare_with_cat(&["--", path.to_str().unwrap()], None)
         })),
-        ("file named --show-all with --", Box::new(|h| {
-            let path = h.fixtures.dir.path().join("--show-all");
+        ("file named --show-all with --", &[], Box::new(|h| {
+            let path = h.scratch().join("--show-all");
             fs::write(&path, b"show all file\n")?;
             h.compare_with_cat(&["--", path.to_str().unwrap()], None)
         })),
-        ("file named --show-nonprinting with --", Box::new(|h| {
-            let path = h.fixtures.dir.path().join("--show-nonprinting");
+        ("file named --show-nonprinting with --", &[], Box::new(|h| {
+            let path = h.scratch().join("--show-nonprinting");
             fs::write(&path, b"show nonprinting file\n")?;
             h.compare_with_cat(&["--", path.to_str().unwrap()], None)
         })),
-        ("bad option bundle", Box::new(test_bad_option_bundle)),
+        ("bad option bundle", &["error"], Box::new(test_bad_option_bundle)),
+        ("stdout to /dev/full", &["error"], Box::new(test_dev_full_write_error)),
+        ("slow pipe reader backpressure", &["pipe", "large"], Box::new(test_slow_pipe_reader)),
+        ("file growing during read", &["pipe", "large"], Box::new(test_growing_file)),
+        ("hung wcat is killed at the deadline", &["tooling", "serial"], Box::new(test_child_timeout)),
+        ("10,000 small operands", &["slow"], Box::new(test_many_operands)),
+        ("--threads matches a serial run", &["tooling"], Box::new(test_parallel_matches_serial)),
+        ("failing --tag run exits non-zero", &["tooling"], Box::new(test_selected_run_status)),
+        ("--summary-machine counts the selected cases", &["tooling"], Box::new(test_summary_machine_filtered)),
+        ("many operands under a low fd limit", &["error"], Box::new(test_operands_fd_limit)),
+        (
+            "sparse file plain",
+            &["binary"],
+            Box::new(|h| h.compare_with_cat(&[h.fixtures.sparse.to_str().unwrap()], None)),
+        ),
+        (
+            "sparse file -v",
+            &["binary"],
+            Box::new(|h| h.compare_with_cat(&["-v", h.fixtures.sparse.to_str().unwrap()], None)),
+        ),
+        (
+            "sparse file -A",
+            &["binary"],
+            Box::new(|h| h.compare_with_cat(&["-A", h.fixtures.sparse.to_str().unwrap()], None)),
+        ),
+        ("-T run ending on a read chunk boundary", &["large"], Box::new(test_tabs_run_at_chunk_boundary)),
+        (
+            "utf-8 split across read chunks plain",
+            &["binary", "large"],
+            Box::new(|h| h.compare_with_cat(&[h.fixtures.utf8_seam.to_str().unwrap()], None)),
+        ),
+        (
+            "utf-8 split across read chunks -v",
+            &["binary", "large"],
+            Box::new(|h| h.compare_with_cat(&["-v", h.fixtures.utf8_seam.to_str().unwrap()], None)),
+        ),
+        (
+            "utf-8 split across read chunks -A",
+            &["binary", "large"],
+            Box::new(|h| h.compare_with_cat(&["-A", h.fixtures.utf8_seam.to_str().unwrap()], None)),
+        ),
+        (
+            "utf-8 split across read chunks -nv",
+            &["binary", "large"],
+            Box::new(|h| h.compare_with_cat(&["-nv", h.fixtures.utf8_seam.to_str().unwrap()], None)),
+        ),
+        (
+            "cr-only line endings plain",
+            &[],
+            Box::new(|h| h.compare_with_cat(&[h.fixtures.cr_only.to_str().unwrap()], None)),
+        ),
+        (
+            "cr-only line endings -v",
+            &[],
+            Box::new(|h| h.compare_with_cat(&["-v", h.fixtures.cr_only.to_str().unwrap()], None)),
+        ),
+        (
+            "cr-only line endings -n",
+            &[],
+            Box::new(|h| h.compare_with_cat(&["-n", h.fixtures.cr_only.to_str().unwrap()], None)),
+        ),
+        (
+            "cr-only line endings -s",
+            &[],
+            Box::new(|h| h.compare_with_cat(&["-s", h.fixtures.cr_only.to_str().unwrap()], None)),
+        ),
+        (
+            "whitespace-only lines -b",
+            &[],
+            Box::new(|h| h.compare_with_cat(&["-b", h.fixtures.whitespace.to_str().unwrap()], None)),
+        ),
+        (
+            "whitespace-only lines -bE",
+            &[],
+            Box::new(|h| h.compare_with_cat(&["-bE", h.fixtures.whitespace.to_str().unwrap()], None)),
+        ),
+        (
+            "whitespace-only lines -bT",
+            &[],
+            Box::new(|h| h.compare_with_cat(&["-bT", h.fixtures.whitespace.to_str().unwrap()], None)),
+        ),
+        (
+            "whitespace-only lines -b stdin",
+            &["stdin"],
+            Box::new(|h| h.compare_with_cat(&["-b", "-"], Some(&fs::read(&h.fixtures.whitespace)?))),
+        ),
+        ("whitespace-only lines -s", &[], Box::new(test_whitespace_squeeze)),
         (
             "process asm keeps comment-only lines",
+            &["tooling"],
             Box::new(test_comment_preservation),
         ),
-    ];
+        (
+            "process asm keeps semicolons in strings",
+            &["tooling"],
+            Box::new(test_comment_in_string_literal),
+        ),
+        ("process asm --check re-assembles", &["tooling"], Box::new(test_process_asm_check)),
+        (
+            "process asm joins continued lines",
+            &["tooling"],
+            Box::new(test_comment_line_continuation),
+        ),
+        ("process asm --in-place rewrites atomically", &["tooling"], Box::new(test_process_asm_in_place)),
+        ("asm stats counts comments", &["tooling"], Box::new(test_asm_comment_stats)),
+        ("process asm --drop-empty", &["tooling"], Box::new(test_drop_empty_lines)),
+        ("process asm --diff reports changes", &["tooling"], Box::new(test_process_asm_diff)),
+        ("process asm --comment-char", &["tooling"], Box::new(test_custom_comment_char)),
+        ("build toolchain selection", &["tooling"], Box::new(test_toolchain_selection)),
+        ("build linker selection", &["tooling"], Box::new(test_linker_selection)),
+        ("build skips touched-but-unchanged source", &["tooling"], Box::new(test_rebuild_uses_content_hash)),
+        ("build step errors carry tool output", &["tooling"], Box::new(test_build_step_error)),
+        ("squeeze-limit=1 matches -s", &["extension"], Box::new(test_squeeze_limit_one)),
+        ("squeeze-limit=2 keeps short runs", &["extension"], Box::new(|h| {
+            h.expect_wcat(
+                &["--squeeze-limit=2", h.fixtures.blank.to_str().unwrap()],
+                None,
+                b"one\n\n\nthree\n\n\n",
+            )
+        })),
+        ("squeeze-limit=2 caps long runs", &["extension"], Box::new(|h| {
+            h.expect_wcat(&["--squeeze-limit=2", "-"], Some(b"a\n\n\n\n\nb\n"), b"a\n\n\nb\n")
+        })),
+        ("squeeze-limit=0 drops blank lines", &["extension"], Box::new(|h| {
+            h.expect_wcat(
+                &["--squeeze-limit=0", h.fixtures.blank.to_str().unwrap()],
+                None,
+                b"one\nthree\n",
+            )
+        })),
+        ("squeeze-limit with -n", &["extension"], Box::new(|h| {
+            h.expect_wcat(
+                &["-n", "--squeeze-limit=2", "-"],
+                Some(b"a\n\n\n\nb\n"),
+                b"     1\ta\n     2\t\n     3\t\n     4\tb\n",
+            )
+        })),
+        ("squeeze-limit with -T", &["extension"], Box::new(|h| {
+            h.expect_wcat(&["-T", "--squeeze-limit=2", "-"], Some(b"\ta\n\n\n\n\n"), b"^Ia\n\n\n")
+        })),
+        ("squeeze-limit with -v", &["extension"], Box::new(|h| {
+            h.expect_wcat(&["-v", "--squeeze-limit=1", "-"], Some(b"\x01\n\n\n\n"), b"^A\n\n")
+        })),
+        ("squeeze-limit across files", &["extension"], Box::new(test_squeeze_limit_across_files)),
+        ("-s after squeeze-limit resets it", &["extension"], Box::new(|h| {
+            h.expect_wcat(&["--squeeze-limit=3", "-s", "-"], Some(b"a\n\n\n\nb\n"), b"a\n\nb\n")
+        })),
+        ("squeeze-limit missing value", &["error", "extension"], Box::new(|h| {
+            h.expect_wcat_error(&["--squeeze-limit"], None, "option '--squeeze-limit' requires an argument")
+        })),
+        ("stats leaves stdout untouched", &["extension"], Box::new(test_stats_stdout_unchanged)),
+        ("stats summary format", &["extension"], Box::new(test_stats_summary)),
+        ("checksum matches crc32 of output", &["extension"], Box::new(test_checksum_matches_output)),
+        ("null separator plain", &["extension"], Box::new(|h| {
+            h.expect_wcat(
+                &["--null", h.fixtures.blank.to_str().unwrap()],
+                None,
+                b"one\0\0\0three\0\0\0",
+            )
+        })),
+        ("null separator with -n", &["extension"], Box::new(|h| {
+            h.expect_wcat(
+                &["-n", "--null", h.fixtures.tabs.to_str().unwrap()],
+                None,
+                b"     1\tcol1\tcol2\0     2\tline\t2\0",
+            )
+        })),
+        ("null separator with -sE", &["extension"], Box::new(|h| {
+            h.expect_wcat(
+                &["--null", "-sE", h.fixtures.blank.to_str().unwrap()],
+                None,
+                b"one$\0$\0three$\0$\0",
+            )
+        })),
+        ("null separator keeps last record open", &["extension"], Box::new(|h| {
+            let args = [
+                "--null",
+                h.fixtures.sample_a.to_str().unwrap(),
+                h.fixtures.no_newline.to_str().unwrap(),
+            ];
+            h.expect_wcat(&args, None, b"alpha\0no newline")
+        })),
+        ("null separator with -v", &["extension"], Box::new(|h| {
+            h.expect_wcat(&["--null", "-v", "-"], Some(b"a\x00b\nc\n"), b"a^@b\0c\0")
+        })),
+        ("line-range slice across files", &["extension"], Box::new(|h| {
+            let args = [
+                "--line-range=2,4",
+                h.fixtures.sample_a.to_str().unwrap(),
+                h.fixtures.sample_b.to_str().unwrap(),
+                h.fixtures.blank.to_str().unwrap(),
+            ];
+            h.expect_wcat(&args, None, b"beta\none\n\n")
+        })),
+        ("line-range keeps original numbers", &["extension"], Box::new(|h| {
+            h.expect_wcat(
+                &["-n", "--line-range=1499,1501", h.fixtures.large.to_str().unwrap()],
+                None,
+                b"  1499\tline 1499\n  1500\tline 1500\n  1501\tline 1501\n",
+            )
+        })),
+        ("line-range -b counts skipped text lines", &["extension"], Box::new(|h| {
+            h.expect_wcat(
+                &["-b", "--line-range=4,6", h.fixtures.blank.to_str().unwrap()],
+                None,
+                b"     2\tthree\n\n\n",
+            )
+        })),
+        ("line-range past end of input", &["extension"], Box::new(|h| {
+            h.expect_wcat(
+                &["--line-range=2999,5000", h.fixtures.large.to_str().unwrap()],
+                None,
+                b"line 2999\nline 3000\n",
+            )
+        })),
+        ("line-range unterminated last line", &["extension"], Box::new(|h| {
+            h.expect_wcat(
+                &["--line-range=2,9", "-E", "-"],
+                Some(b"one\ntwo\nthree"),
+                b"two$\nthree",
+            )
+        })),
+        ("line-range invalid bounds", &["error", "extension"], Box::new(|h| {
+            for value in ["--line-range=0,2", "--line-range=5,2", "--line-range=3", "--line-range=a,b"] {
+                let shown = value.trim_start_matches("--line-range=");
+                h.expect_wcat_error(&[value], None, &format!("invalid argument '{shown}' for '--line-range'"))?;
+            }
+            h.expect_wcat_error(&["--line-range"], None, "option '--line-range' requires an argument")
+        })),
+        ("preallocate file output matches cat", &["extension"], Box::new(test_preallocate_parity)),
+        ("progress markers on stderr", &["extension"], Box::new(test_progress_markers)),
+        ("progress every 64 MiB", &["extension", "large"], Box::new(test_progress_interval)),
+        ("progress invalid value", &["error", "extension"], Box::new(|h| {
+            h.expect_wcat_error(&["--progress=sometimes"], None, "invalid argument 'sometimes' for '--progress'")
+        })),
+        ("files-from matches explicit operands", &["extension"], Box::new(test_files_from_golden)),
+        ("files-from missing list", &["error", "extension"], Box::new(|h| {
+            let list = h.scratch().join("no_such_list");
+            let out = run_cmd(&h.wcat, &[&format!("--files-from={}", list.display())], None)?;
+            if out.status.code() != Some(1) || !String::from_utf8_lossy(&out.stderr).contains("No such file or directory") {
+                bail!("missing --files-from list not reported: {:?}", String::from_utf8_lossy(&out.stderr));
+            }
+            h.expect_wcat_error(&["--files-from"], None, "option '--files-from' requires an argument")
+        })),
+        ("binary-safe on a terminal", &["extension", "binary"], Box::new(test_binary_safe_tty)),
+        ("pager on a terminal only", &["extension"], Box::new(test_pager)),
+        ("byte-offset prefixes", &["extension"], Box::new(test_byte_offset)),
+        ("binary-safe redirected passthrough", &["extension", "binary"], Box::new(|h| {
+            let args = ["--binary-safe", h.fixtures.binary.to_str().unwrap()];
+            h.expect_wcat(&args, None, &fs::read(&h.fixtures.binary)?)
+        })),
+        ("merge-stdin replays each dash", &["extension", "stdin"], Box::new(test_merge_stdin_replay)),
+        ("since-mtime skips older files", &["extension"], Box::new(test_since_mtime)),
+        ("quiet-errors keeps only the exit status", &["extension", "error"], Box::new(test_quiet_errors)),
+        ("ignore-missing skips only ENOENT", &["extension", "error"], Box::new(test_ignore_missing)),
+        ("reverse matches tac", &["extension"], Box::new(test_reverse_like_tac)),
+        ("dedupe matches uniq", &["extension"], Box::new(test_dedupe_like_uniq)),
+        ("dedupe runs after squeeze", &["extension"], Box::new(|h| {
+            h.expect_wcat(&["--dedupe", "--squeeze-limit=0"], Some(b"a\n\n\na\nb\n"), b"a\nb\n")
+        })),
+        ("normalize trims and squeezes", &["extension"], Box::new(test_normalize)),
+        ("reverse vs tac (blank.txt)", &["extension"], Box::new(|h| {
+            reverse_fixture_with_tac(h, &h.fixtures.blank)
+        })),
+        ("reverse vs tac (no_newline.txt)", &["extension"], Box::new(|h| {
+            reverse_fixture_with_tac(h, &h.fixtures.no_newline)
+        })),
+        ("reverse vs tac (large.txt)", &["extension", "large"], Box::new(|h| {
+            reverse_fixture_with_tac(h, &h.fixtures.large)
+        })),
+        ("color=always line numbers", &["extension"], Box::new(test_color_always)),
+        ("color=auto to a pipe is plain -n", &["extension", "pipe"], Box::new(|h| {
+            let blank = h.fixtures.blank.to_str().unwrap();
+            let expected = run_cmd(&h.cat, &["-n", blank], None)?.stdout;
+            h.expect_wcat(&["--color=auto", "-n", blank], None, &expected)?;
+            h.expect_wcat(&["--color=lines", "-n", blank], None, &expected)
+        })),
+        ("color file diagnostics", &["error", "extension"], Box::new(test_color_diagnostics)),
+        ("color bad value", &["error", "extension"], Box::new(|h| {
+            h.expect_wcat_error(&["--color=blue"], None, "invalid argument 'blue' for '--color'")
+        })),
+        ("nonprinting-style=hex", &["extension", "binary"], Box::new(test_nonprinting_hex)),
+        ("WCAT_OPTIONS defaults", &["extension"], Box::new(test_wcat_options_env)),
+        ("dry-run operand check", &["extension", "error"], Box::new(test_dry_run)),
+        ("count-only totals", &["extension"], Box::new(test_count_only)),
+        ("epipe-exit code", &["extension", "pipe"], Box::new(test_epipe_exit)),
+        ("strip-trailing-cr", &["extension"], Box::new(test_strip_trailing_cr)),
+        ("number width -w", &["extension"], Box::new(test_number_width)),
+        ("repeat operands", &["extension", "pipe"], Box::new(test_repeat)),
+        ("headers like tail -v", &["extension"], Box::new(test_headers)),
+        ("tab-marker replaces ^I", &["extension"], Box::new(test_tab_marker)),
+        ("end-marker replaces $", &["extension"], Box::new(test_end_marker)),
+        ("ensure-newline", &["extension"], Box::new(test_ensure_newline)),
+        ("exit-ok keeps diagnostics", &["extension", "error"], Box::new(test_exit_ok)),
+        ("long option values", &["extension"], Box::new(test_long_option_values)),
+        ("buffer=line flushes each line", &["extension", "pipe"], Box::new(test_buffer_line)),
+        ("preallocate to a pipe", &["extension"], Box::new(|h| {
+            let args = ["--preallocate", h.fixtures.sample_a.to_str().unwrap()];
+            h.expect_wcat(&args, None, &fs::read(&h.fixtures.sample_a)?)
+        })),
+        ("squeeze-limit bad value", &["error", "extension"], Box::new(|h| {
+            h.expect_wcat_error(
+                &["--squeeze-limit=2x"],
+                None,
+                "invalid argument '2x' for '--squeeze-limit'",
+            )
+        })),
+    ]
+}
 
-    add_matrix_cases(&mut cases);
+fn run_tests(options: RunOptions, wcat: Option<PathBuf>) -> Result<()> {
+    let RunOptions {
+        filter,
+        tag,
+        slow,
+        check_syscalls,
+        sort,
+        summary_machine,
+        trace_json,
+        matrix_sample,
+        threads,
+    } = options;
+    // Matrix specs are plain data, so they are built while fixtures are set up.
+    let matrix_specs = std::thread::spawn(matrix_specs);
+    let harness = Harness::new(wcat)?;
+    let mut cases = build_cases();
+
+    let matrix_specs = matrix_specs
+        .join()
+        .map_err(|_| anyhow::anyhow!("building the matrix specs panicked"))?;
+    add_matrix_cases(&mut cases, matrix_specs);
+    add_bufsize_cases(&mut cases);
+    if let Some(fraction) = matrix_sample {
+        let generated = cases.iter().filter(|(_, tags, _)| tags.contains(&"matrix")).count();
+        cases.retain(|(name, tags, _)| {
+            !tags.contains(&"matrix") || matrix_case_sampled(name, fraction)
+        });
+        let selected = cases.iter().filter(|(_, tags, _)| tags.contains(&"matrix")).count();
+        println!("matrix sample {fraction}: {selected}/{generated} matrix cases selected");
+    }
+    if !slow {
+        cases.retain(|(_, tags, _)| !tags.contains(&"slow"));
+    }
+    if !check_syscalls {
+        cases.retain(|(_, tags, _)| !tags.contains(&"syscalls"));
+    }
+    if sort {
+        cases.sort_by(|a, b| a.0.cmp(b.0));
+    }
 
     let total = cases.len();
+    let selective = filter.is_some() || tag.is_some();
+    cases.retain(|(name, tags, _)| {
+        filter.as_ref().is_none_or(|f| name.contains(f.as_str()))
+            && tag.as_ref().is_none_or(|t| tags.contains(&t.as_str()))
+    });
+    let selected = cases.len();
+    let outcomes = if threads > 1 {
+        let (serial, parallel): (Vec<_>, Vec<_>) =
+            cases.iter().enumerate().partition(|(_, (_, tags, _))| tags.contains(&"serial"));
+        let results = std::sync::Mutex::new(Vec::with_capacity(cases.len()));
+        let next = std::sync::atomic::AtomicUsize::new(0);
+        std::thread::scope(|scope| {
+            for _ in 0..threads.min(parallel.len()) {
+                scope.spawn(|| {
+                    while let Some(&(index, case)) = parallel.get(next.fetch_add(1, Ordering::Relaxed)) {
+                        let outcome = run_case(&harness, index, case);
+                        results.lock().unwrap().push((index, outcome));
+                    }
+                });
+            }
+        });
+        let mut results = results.into_inner().unwrap();
+        results.extend(serial.into_iter().map(|(index, case)| (index, run_case(&harness, index, case))));
+        results.sort_by_key(|(index, _)| *index);
+        results.into_iter().map(|(_, outcome)| outcome).collect()
+    } else {
+        cases.iter().enumerate().map(|(index, case)| run_case(&harness, index, case)).collect::<Vec<_>>()
+    };
     let mut passed = 0usize;
-    for (name, case) in cases.drain(..) {
-        if let Some(f) = &filter {
-            if !name.contains(f) {
-                continue;
+    let mut failures: Vec<(String, String)> = Vec::new();
+    let mut trace = Vec::new();
+    for (case, (result, duration_ms)) in cases.iter().zip(outcomes) {
+        let name = case.0;
+        trace.push(serde_json::json!({
+            "name": name,
+            "status": if result.is_ok() { "pass" } else { "fail" },
+            "duration_ms": duration_ms,
+        }));
+        match result {
+            Ok(()) => passed += 1,
+            Err(message) => {
+                let first_line = message.lines().next().unwrap_or_default().to_string();
+                failures.push((name.to_string(), first_line));
             }
         }
-        if VERBOSE.load(Ordering::Relaxed) {
-            println!("[RUN ] {name}");
-        }
-        match case(&harness) {
-            Ok(_) => {
-                passed += 1;
-                println!("[PASS] {name}");
-            }
-            Err(e) => {
-                println!("[FAIL] {name}: {e:#}");
-            }
+    }
+    if let Some(path) = &trace_json {
+        let json = serde_json::to_string_pretty(&trace)?;
+        fs::write(path, json + "\n").with_context(|| format!("writing {}", path.display()))?;
+    }
+    if !failures.is_empty() {
+        println!("\nFailures:");
+        for (name, message) in &failures {
+            println!("  {name}: {message}");
         }
     }
     println!(
         "\n{passed}/{total} tests executed{}.",
-        if filter.is_some() { " (filtered)" } else { "" }
+        if selective { " (filtered)" } else { "" }
     );
-    if passed == total || filter.is_some() {
+    if summary_machine {
+        println!(
+            "SUMMARY passed={passed} failed={} total={selected} filtered={selective}",
+            failures.len()
+        );
+    }
+    if failures.is_empty() {
         return Ok(());
     }
     bail!("failures encountered");
 }
 
+/// Run one case in a scratch directory of its own, removed afterwards, and
+/// report it. Returns the failure text, if any, and the duration in ms.
+fn run_case(harness: &Harness, index: usize, (name, _, case): &TestCase) -> (Result<(), String>, f64) {
+    let scratch = harness.fixtures.dir.path().join(format!("case-{index}"));
+    if let Err(e) = fs::create_dir(&scratch) {
+        let message = format!("creating {}: {e}", scratch.display());
+        println!("[FAIL] {name}: {message}");
+        return (Err(message), 0.0);
+    }
+    CASE_SCRATCH.with(|dir| *dir.borrow_mut() = Some(scratch.clone()));
+    if VERBOSE.load(Ordering::Relaxed) {
+        println!("[RUN ] {name}");
+    }
+    let started = Instant::now();
+    let result = case(harness).map_err(|e| format!("{e:#}"));
+    let duration_ms = started.elapsed().as_secs_f64() * 1000.0;
+    CASE_SCRATCH.with(|dir| *dir.borrow_mut() = None);
+    // Best effort: a case may leave an unreadable directory behind.
+    let _ = fs::remove_dir_all(&scratch);
+    match &result {
+        Ok(()) => println!("[PASS] {name}"),
+        Err(message) => println!("[FAIL] {name}: {message}"),
+    }
+    (result, duration_ms)
+}
+
 // --------------------- Matrix coverage -----------------------------------
 #[derive(Clone, Copy)]
 enum FixtureKey {
@@ -1556,7 +2537,8 @@ enum FixtureKey {
     Binary,
 }
 
-fn add_matrix_cases(cases: &mut Vec<TestCase>) {
+/// Every distinct option list the matrix covers, with its display label.
+fn matrix_specs() -> Vec<(String, Vec<String>)> {
     let mut specs: Vec<(String, Vec<String>)> = Vec::new();
     let mut seen = HashSet::<String>::new();
 
@@ -1649,11 +2631,16 @@ fn add_matrix_cases(cases: &mut Vec<TestCase>) {
             opts.into_iter().map(|s| s.to_string()).collect(),
         );
     }
+    specs
+}
 
+/// Turn each spec into its file, multi-file, stdin and stdin+file cases, then
+/// add the binary-fixture cases.
+fn add_matrix_cases(cases: &mut Vec<TestCase>, specs: Vec<(String, Vec<String>)>) {
     for (label, opts) in specs {
         let opts_single = opts.clone();
         let name: &'static str = Box::leak(format!("matrix file {label}").into_boxed_str());
-        cases.push((name, Box::new(move |h| {
+        cases.push((name, &["matrix"], Box::new(move |h| {
             let args = build_args_with_file(h, &opts_single);
             let args_ref: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
             h.compare_with_cat(&args_ref, None)
@@ -1661,7 +2648,7 @@ fn add_matrix_cases(cases: &mut Vec<TestCase>) {
 
         let opts_multi = opts.clone();
         let name: &'static str = Box::leak(format!("matrix multi {label}").into_boxed_str());
-        cases.push((name, Box::new(move |h| {
+        cases.push((name, &["matrix"], Box::new(move |h| {
             let args = build_args_with_multi(h, &opts_multi);
             let args_ref: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
             h.compare_with_cat(&args_ref, None)
@@ -1669,7 +2656,7 @@ fn add_matrix_cases(cases: &mut Vec<TestCase>) {
 
         let opts_stdin = opts.clone();
         let name: &'static str = Box::leak(format!("matrix stdin {label}").into_boxed_str());
-        cases.push((name, Box::new(move |h| {
+        cases.push((name, &["matrix", "stdin"], Box::new(move |h| {
             let input_key = pick_fixture_key(&opts_stdin);
             let input = fixture_bytes(h, input_key)?;
             let args = build_args_with_stdin(&opts_stdin);
@@ -1679,7 +2666,7 @@ fn add_matrix_cases(cases: &mut Vec<TestCase>) {
 
         let opts_stdin_file = opts.clone();
         let name: &'static str = Box::leak(format!("matrix stdin+file {label}").into_boxed_str());
-        cases.push((name, Box::new(move |h| {
+        cases.push((name, &["matrix", "stdin"], Box::new(move |h| {
             let input_key = pick_fixture_key(&opts_stdin_file);
             let input = fixture_bytes(h, input_key)?;
             let args = build_args_with_stdin_file(h, &opts_stdin_file);
@@ -1703,7 +2690,7 @@ fn add_matrix_cases(cases: &mut Vec<TestCase>) {
         let label: &'static str =
             Box::leak(format!("matrix binary {}", opts.join(" ")).into_boxed_str());
         let opts = opts.into_iter().map(|s| s.to_string()).collect::<Vec<_>>();
-        cases.push((label, Box::new(move |h| {
+        cases.push((label, &["matrix", "binary"], Box::new(move |h| {
             let args = build_args_with_specific_file(h, &opts, FixtureKey::Binary);
             let args_ref: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
             h.compare_with_cat(&args_ref, None)
@@ -1711,6 +2698,49 @@ fn add_matrix_cases(cases: &mut Vec<TestCase>) {
     }
 }
 
+/// Input sizes for the bufsize cases: one short of a full read() chunk,
+/// exactly one chunk, and one byte into the second.
+const BUFSIZE_BOUNDARIES: &[usize] =
+    &[WCAT_BUFFER_SIZE - 1, WCAT_BUFFER_SIZE, WCAT_BUFFER_SIZE + 1];
+
+/// Bytes with their own rendering under -nvET, placed on the chunk seam.
+const BUFSIZE_SEAM_BYTES: &[(u8, &str)] =
+    &[(b'\n', "newline"), (b'\t', "tab"), (b'\r', "CR"), (0xe9, "high byte")];
+
+/// `size` bytes of short text lines with `seam` written at the last byte of
+/// the first read() chunk, the first byte of the second, and the end of file.
+fn bufsize_fixture(size: usize, seam: u8) -> Vec<u8> {
+    let mut data: Vec<u8> = b"line of text\n".iter().copied().cycle().take(size).collect();
+    for at in [WCAT_BUFFER_SIZE - 1, WCAT_BUFFER_SIZE, size - 1] {
+        if at < size {
+            data[at] = seam;
+        }
+    }
+    data
+}
+
+/// `-nvET` on each boundary-sized fixture, from a file and from stdin, so
+/// multi-byte expansions (`^I`, `^M`, `M-i`, `$`, line numbers) straddle the
+/// seam between two read() chunks.
+fn add_bufsize_cases(cases: &mut Vec<TestCase>) {
+    for &size in BUFSIZE_BOUNDARIES {
+        for &(seam, what) in BUFSIZE_SEAM_BYTES {
+            let name: &'static str =
+                Box::leak(format!("bufsize {size} bytes, {what} on the seam, -nvET file").into_boxed_str());
+            cases.push((name, &["bufsize"], Box::new(move |h| {
+                let path = h.scratch().join(format!("bufsize_{size}_{seam:02x}.bin"));
+                fs::write(&path, bufsize_fixture(size, seam))?;
+                h.compare_with_cat(&["-nvET", path.to_str().unwrap()], None)
+            })));
+            let name: &'static str =
+                Box::leak(format!("bufsize {size} bytes, {what} on the seam, -nvET stdin").into_boxed_str());
+            cases.push((name, &["bufsize", "stdin"], Box::new(move |h| {
+                h.compare_with_cat(&["-nvET"], Some(&bufsize_fixture(size, seam)))
+            })));
+        }
+    }
+}
+
 fn build_args_with_file(h: &Harness, opts: &[String]) -> Vec<String> {
     let key = pick_fixture_key(opts);
     build_args_with_specific_file(h, opts, key)
@@ -1745,7 +2775,7 @@ fn build_args_with_stdin_file(h: &Harness, opts: &[String]) -> Vec<String> {
     args
 }
 
-fn fixture_path<'a>(h: &'a Harness, key: FixtureKey) -> &'a Path {
+fn fixture_path(h: &Harness, key: FixtureKey) -> &Path {
     match key {
         FixtureKey::SampleA => &h.fixtures.sample_a,
         FixtureKey::Blank => &h.fixtures.blank,
@@ -1803,9 +2833,162 @@ fn option_has_long(opts: &[String], flag: &str) -> bool {
     opts.iter().any(|opt| opt == flag)
 }
 
+// --------------------- Fuzzing ---------------------------------------------
+/// Options the fuzzer draws from; all are cat's, so cat is the oracle.
+const FUZZ_SHORT_FLAGS: &[u8] = b"AbeEnstTuv";
+const FUZZ_LONG_FLAGS: &[&str] = &[
+    "--number",
+    "--number-nonblank",
+    "--squeeze-blank",
+    "--show-ends",
+    "--show-tabs",
+    "--show-nonprinting",
+    "--show-all",
+];
+/// Input bytes, weighted towards the ones the decorations care about.
+const FUZZ_ALPHABET: &[u8] = b"aab  \t\n\n\n\r\x01\x1b\x7f\x80\x9b\xe9\xff\x00";
+/// Comparisons a minimization may spend before settling for what it has.
+const MINIMIZE_BUDGET: usize = 3000;
+
+fn run_fuzz(iterations: u64, seed: Option<u64>, wcat: Option<PathBuf>) -> Result<()> {
+    let h = Harness::new(wcat)?;
+    let seed = seed.unwrap_or_else(rand::random);
+    println!("fuzz: {iterations} cases, seed {seed}");
+    let mut rng = StdRng::seed_from_u64(seed);
+    for case in 0..iterations {
+        let (args, input) = fuzz_case(&mut rng);
+        if !fuzz_diverges(&h, &args, &input) {
+            continue;
+        }
+        println!("[FAIL] case {case} diverges: {args:?} with {} bytes of stdin; minimizing", input.len());
+        let (args, input) = minimize(&args, &input, |args, input| fuzz_diverges(&h, args, input));
+        let argv: Vec<&str> = args.iter().map(String::as_str).collect();
+        println!("minimal case: {argv:?} with stdin \"{}\"", input.escape_ascii());
+        println!("reproduce: printf '{}' | wcat {}", printf_escape(&input), argv.join(" "));
+        let wcat_out = run_cmd_with_arg0(&h.wcat, &argv, Some(&input), Some(&h.cat), &[])?;
+        let cat_out = run_cmd(&h.cat, &argv, Some(&input))?;
+        print!("{}", mismatch_report(&wcat_out, &cat_out));
+        bail!("fuzz case {case} of seed {seed} diverges from cat");
+    }
+    println!("fuzz: no divergence in {iterations} cases");
+    Ok(())
+}
+
+/// A random argv of cat options (clustered short flags and long spellings)
+/// and a random stdin, now and then longer than one read() chunk.
+fn fuzz_case(rng: &mut StdRng) -> (Vec<String>, Vec<u8>) {
+    let args = (0..rng.gen_range(0..=3))
+        .map(|_| {
+            if rng.gen_bool(0.2) {
+                FUZZ_LONG_FLAGS[rng.gen_range(0..FUZZ_LONG_FLAGS.len())].to_string()
+            } else {
+                let cluster = (0..rng.gen_range(1..=3))
+                    .map(|_| FUZZ_SHORT_FLAGS[rng.gen_range(0..FUZZ_SHORT_FLAGS.len())] as char);
+                format!("-{}", cluster.collect::<String>())
+            }
+        })
+        .collect();
+    let len = match rng.gen_range(0..10) {
+        0 => 0,
+        1 => rng.gen_range(WCAT_BUFFER_SIZE - 64..WCAT_BUFFER_SIZE + 64),
+        2..=4 => rng.gen_range(1..4096),
+        _ => rng.gen_range(1..64),
+    };
+    let input = (0..len).map(|_| FUZZ_ALPHABET[rng.gen_range(0..FUZZ_ALPHABET.len())]).collect();
+    (args, input)
+}
+
+/// Whether wcat and cat disagree on stdout, stderr or exit for ARGS reading
+/// INPUT on stdin. A run that cannot complete (a timeout) counts as one.
+fn fuzz_diverges(h: &Harness, args: &[String], input: &[u8]) -> bool {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let wcat_out = run_cmd_with_arg0(&h.wcat, &args, Some(input), Some(&h.cat), &[]);
+    let cat_out = run_cmd(&h.cat, &args, Some(input));
+    match (wcat_out, cat_out) {
+        (Ok(w), Ok(c)) => {
+            w.stdout != c.stdout || w.stderr != c.stderr || exit_of(&w.status) != exit_of(&c.status)
+        }
+        _ => true,
+    }
+}
+
+/// Shrink a failing case while `diverges` still holds: drop whole
+/// arguments, then single letters of clustered short options, then chunks of
+/// the input from half its length down to single bytes. Repeats until a
+/// whole round removes nothing or `MINIMIZE_BUDGET` comparisons are spent.
+fn minimize(
+    args: &[String],
+    input: &[u8],
+    mut diverges: impl FnMut(&[String], &[u8]) -> bool,
+) -> (Vec<String>, Vec<u8>) {
+    let mut args = args.to_vec();
+    let mut input = input.to_vec();
+    let mut budget = MINIMIZE_BUDGET;
+    let mut still_diverges = |args: &[String], input: &[u8]| {
+        budget = budget.saturating_sub(1);
+        budget > 0 && diverges(args, input)
+    };
+    loop {
+        let mut shrunk = false;
+        let mut i = 0;
+        while i < args.len() {
+            let mut fewer = args.clone();
+            fewer.remove(i);
+            if still_diverges(&fewer, &input) {
+                args = fewer;
+                shrunk = true;
+            } else {
+                i += 1;
+            }
+        }
+        for i in 0..args.len() {
+            let mut letter = 1;
+            while !args[i].starts_with("--") && args[i].len() > 2 && letter < args[i].len() {
+                let mut fewer = args.clone();
+                fewer[i].remove(letter);
+                if still_diverges(&fewer, &input) {
+                    args = fewer;
+                    shrunk = true;
+                } else {
+                    letter += 1;
+                }
+            }
+        }
+        let mut chunk = input.len().div_ceil(2);
+        while chunk > 0 {
+            let mut start = 0;
+            while start < input.len() {
+                let end = input.len().min(start + chunk);
+                let shorter = [&input[..start], &input[end..]].concat();
+                if still_diverges(&args, &shorter) {
+                    input = shorter;
+                    shrunk = true;
+                } else {
+                    start = end;
+                }
+            }
+            chunk /= 2;
+        }
+        if !shrunk {
+            return (args, input);
+        }
+    }
+}
+
+/// BYTES as a printf(1) format string that reproduces them exactly.
+fn printf_escape(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|&b| match b {
+            b' ' | b'!'..=b'~' if !matches!(b, b'\'' | b'\\' | b'%') => (b as char).to_string(),
+            _ => format!("\\x{b:02x}"),
+        })
+        .collect()
+}
+
 // --------------------- Individual tests -----------------------------------
 fn test_fifo_tabs(h: &Harness) -> Result<()> {
-    let fifo = h.fixtures.dir.path().join("tabs_fast.fifo");
+    let fifo = h.scratch().join("tabs_fast.fifo");
     mkfifo(&fifo, nix::sys::stat::Mode::from_bits_truncate(0o644))?;
     let tabs = fs::read(&h.fixtures.tabs)?;
     let fifo_path = fifo.to_str().unwrap();
@@ -1813,7 +2996,7 @@ fn test_fifo_tabs(h: &Harness) -> Result<()> {
 }
 
 fn test_fifo_plain(h: &Harness) -> Result<()> {
-    let fifo = h.fixtures.dir.path().join("plain_out.fifo");
+    let fifo = h.scratch().join("plain_out.fifo");
     mkfifo(&fifo, nix::sys::stat::Mode::from_bits_truncate(0o644))?;
     let data = fs::read(&h.fixtures.large)?;
     let fifo_path = fifo.to_str().unwrap();
@@ -1821,7 +3004,7 @@ fn test_fifo_plain(h: &Harness) -> Result<()> {
 }
 
 fn test_fifo_numbered(h: &Harness) -> Result<()> {
-    let fifo = h.fixtures.dir.path().join("num_fast.fifo");
+    let fifo = h.scratch().join("num_fast.fifo");
     mkfifo(&fifo, nix::sys::stat::Mode::from_bits_truncate(0o644))?;
     let data = fs::read(&h.fixtures.large)?;
     let fifo_path = fifo.to_str().unwrap();
@@ -1829,7 +3012,7 @@ fn test_fifo_numbered(h: &Harness) -> Result<()> {
 }
 
 fn test_fifo_visible(h: &Harness) -> Result<()> {
-    let fifo = h.fixtures.dir.path().join("vis_fast.fifo");
+    let fifo = h.scratch().join("vis_fast.fifo");
     mkfifo(&fifo, nix::sys::stat::Mode::from_bits_truncate(0o644))?;
     let data = fs::read(&h.fixtures.control)?;
     let fifo_path = fifo.to_str().unwrap();
@@ -1837,7 +3020,7 @@ fn test_fifo_visible(h: &Harness) -> Result<()> {
 }
 
 fn test_fifo_stream(h: &Harness) -> Result<()> {
-    let fifo = h.fixtures.dir.path().join("stream.fifo");
+    let fifo = h.scratch().join("stream.fifo");
     mkfifo(&fifo, nix::sys::stat::Mode::from_bits_truncate(0o644))?;
     let wcat = h.wcat.clone();
     let writer = std::thread::spawn({
@@ -1850,7 +3033,7 @@ fn test_fifo_stream(h: &Harness) -> Result<()> {
             Ok(())
         }
     });
-    let out = run_cmd_with_arg0(&wcat, &[fifo.to_str().unwrap()], None, Some(&h.cat))?;
+    let out = run_cmd_with_arg0(&wcat, &[fifo.to_str().unwrap()], None, Some(&h.cat), &[])?;
     writer.join().unwrap()?;
     if out.stdout != b"chunk1\nchunk2\n" {
         bail!("fifo stream mismatch");
@@ -1869,7 +3052,7 @@ fn test_fifo_stream(h: &Harness) -> Result<()> {
                 Ok(())
             }
         });
-        run_cmd_to_file(cmd, &[fifo_path], None, arg0_override, output)?;
+        run_cmd_to_file(cmd, &[fifo_path], None, arg0_override, &[], output)?;
         writer.join().unwrap()?;
         Ok(())
     };
@@ -1887,23 +3070,223 @@ fn test_fifo_stream(h: &Harness) -> Result<()> {
     Ok(())
 }
 
-fn test_help_output(h: &Harness) -> Result<()> {
-    let out = run_cmd_with_arg0(&h.wcat, &["--help"], None, Some(&h.cat))?;
-    let cat_out = run_cmd(&h.cat, &["--help"], None)?;
-    if out.stdout == cat_out.stdout {
-        bail!("help output should remain wcat-specific");
-    }
-    if !String::from_utf8_lossy(&out.stdout).contains("Usage: wcat") {
-        bail!("help missing usage");
+/// `-u` promises bytes as soon as they are read: with a fifo operand or a
+/// pipe on stdin, the first chunk must show up on wcat's stdout before the
+/// writer sends the second. test_fifo_stream only checks the final bytes.
+/// wcat accepts -u and relies on undecorated copies never being held back;
+/// decorated output is covered by test_buffer_line instead.
+fn test_fifo_unbuffered_incremental(h: &Harness) -> Result<()> {
+    let fifo = h.scratch().join("incremental.fifo");
+    mkfifo(&fifo, nix::sys::stat::Mode::from_bits_truncate(0o644))?;
+    for from_stdin in [false, true] {
+        let mut command = Command::new(&h.wcat);
+        clean_env(&mut command, &[]);
+        command.arg("-u").stdout(Stdio::piped());
+        if from_stdin {
+            command.stdin(Stdio::piped());
+        } else {
+            command.arg(&fifo).stdin(Stdio::null());
+        }
+        let mut child = command.spawn().context("spawning wcat")?;
+        let mut input: Box<dyn Write> = match child.stdin.take() {
+            Some(stdin) => Box::new(stdin),
+            None => Box::new(File::options().write(true).open(&fifo)?),
+        };
+        let mut stdout = child.stdout.take().unwrap();
+        let (tx, rx) = std::sync::mpsc::channel::<Vec<u8>>();
+        let reader = std::thread::spawn(move || {
+            let mut buf = [0u8; 4096];
+            while let Ok(n @ 1..) = stdout.read(&mut buf) {
+                if tx.send(buf[..n].to_vec()).is_err() {
+                    break;
+                }
+            }
+        });
+        let source = if from_stdin { "stdin pipe" } else { "fifo operand" };
+        let mut seen = Vec::new();
+        let mut expect_next = |expected: &[u8]| -> Result<()> {
+            while seen.len() < expected.len() {
+                match rx.recv_timeout(std::time::Duration::from_secs(5)) {
+                    Ok(chunk) => seen.extend(chunk),
+                    Err(_) => break,
+                }
+            }
+            if seen != expected {
+                let _ = child.kill();
+                bail!(
+                    "-u {source}: expected {:?} before more input, got {:?}",
+                    String::from_utf8_lossy(expected),
+                    String::from_utf8_lossy(&seen)
+                );
+            }
+            seen.clear();
+            Ok(())
+        };
+        input.write_all(b"chunk1\n")?;
+        expect_next(b"chunk1\n")?;
+        input.write_all(b"chunk2")?;
+        expect_next(b"chunk2")?;
+        drop(input);
+        reader.join().unwrap();
+        if !child.wait()?.success() {
+            bail!("-u {source}: wcat failed");
+        }
     }
     Ok(())
 }
 
-fn test_version_output(h: &Harness) -> Result<()> {
-    let out = run_cmd_with_arg0(&h.wcat, &["--version"], None, Some(&h.cat))?;
-    let cat_out = run_cmd(&h.cat, &["--version"], None)?;
-    if out.stdout == cat_out.stdout {
-        bail!("version output should remain wcat-specific");
+fn test_fifo_nonblocking_stdin(h: &Harness) -> Result<()> {
+    // stdin is a fifo opened O_NONBLOCK and the writer pauses mid-stream, so
+    // reads hit EAGAIN. GNU cat 9.x gives up with "Resource temporarily
+    // unavailable"; wcat waits for the data instead, so check the bytes directly.
+    let fifo = h.scratch().join("nonblock.fifo");
+    mkfifo(&fifo, nix::sys::stat::Mode::from_bits_truncate(0o644))?;
+    let output = NamedTempFile::new_in(h.fixtures.dir.path())?;
+    for (args, to_file) in [(vec![], false), (vec![], true), (vec!["-n"], false), (vec!["-n"], true)] {
+        let reader = nix::fcntl::open(
+            &fifo,
+            nix::fcntl::OFlag::O_RDONLY | nix::fcntl::OFlag::O_NONBLOCK | nix::fcntl::OFlag::O_CLOEXEC,
+            nix::sys::stat::Mode::empty(),
+        )?;
+        let mut writer_end = File::options().write(true).open(&fifo)?;
+        let writer = std::thread::spawn(move || -> std::io::Result<()> {
+            writer_end.write_all(b"chunk1\n")?;
+            std::thread::sleep(std::time::Duration::from_millis(100));
+            writer_end.write_all(b"chunk2\n")
+        });
+        let mut command = Command::new(&h.wcat);
+        command.args(&args).stdin(Stdio::from(reader)).stderr(Stdio::piped());
+        if to_file {
+            command.stdout(File::create(output.path())?);
+        } else {
+            command.stdout(Stdio::piped());
+        }
+        let child = command.spawn().context("spawning wcat")?;
+        let out = wait_with_deadline(child, &h.wcat, &args)?;
+        writer.join().unwrap()?;
+        let stdout = if to_file { fs::read(output.path())? } else { out.stdout };
+        let expected: &[u8] = if args.is_empty() {
+            b"chunk1\nchunk2\n"
+        } else {
+            b"     1\tchunk1\n     2\tchunk2\n"
+        };
+        if stdout != expected || !out.status.success() {
+            bail!(
+                "O_NONBLOCK fifo {:?} (to file: {}): got {:?}, status {:?}, stderr {}",
+                args,
+                to_file,
+                String::from_utf8_lossy(&stdout),
+                out.status.code(),
+                String::from_utf8_lossy(&out.stderr)
+            );
+        }
+    }
+    Ok(())
+}
+
+fn test_help_output(h: &Harness) -> Result<()> {
+    let out = run_cmd_with_arg0(&h.wcat, &["--help"], None, Some(&h.cat), &[])?;
+    let cat_out = run_cmd(&h.cat, &["--help"], None)?;
+    if out.stdout == cat_out.stdout {
+        bail!("help output should remain wcat-specific");
+    }
+    if !String::from_utf8_lossy(&out.stdout).contains("Usage: wcat") {
+        bail!("help missing usage");
+    }
+    Ok(())
+}
+
+/// The whole --help text, byte for byte: a usage synopsis, then "Options:"
+/// and "Extensions:" with one entry per flag, then "Examples:".
+fn test_help_snapshot(h: &Harness) -> Result<()> {
+    let expected = include_bytes!("../snapshots/wcat-help.txt");
+    let out = run_cmd(&h.wcat, &["--help"], None)?;
+    if out.stdout != expected || !out.status.success() || !out.stderr.is_empty() {
+        bail!(
+            "status {:?}, stderr {:?}; {}",
+            out.status.code(),
+            String::from_utf8_lossy(&out.stderr),
+            describe_diff("--help", ("wcat", &out.stdout), ("snapshot", expected))
+        );
+    }
+    Ok(())
+}
+
+/// help2man must turn --help into a man page with a synopsis and OPTIONS,
+/// EXTENSIONS and EXAMPLES sections; it runs --help and --version itself and
+/// fails if either exits non-zero.
+fn test_help2man(h: &Harness) -> Result<()> {
+    let Some(help2man) = &h.help2man else {
+        println!("[SKIP] help2man not available");
+        return Ok(());
+    };
+    let out = run_cmd(help2man, &["--no-info", h.wcat.to_str().unwrap()], None)?;
+    let page = String::from_utf8_lossy(&out.stdout);
+    if !out.status.success() {
+        bail!("help2man failed: {}", String::from_utf8_lossy(&out.stderr));
+    }
+    for needle in [".SH SYNOPSIS", ".SH OPTIONS", ".SH EXTENSIONS", ".SH EXAMPLES", "show\\-all", "byte\\-offset"] {
+        if !page.contains(needle) {
+            bail!("help2man page lacks {needle:?}:\n{page}");
+        }
+    }
+    Ok(())
+}
+
+/// --help and --version are fixed texts that always say "wcat", whatever
+/// argv[0] is, while diagnostics name the program as invoked, like cat: option
+/// errors, open errors and --dry-run lines alike. The texts must be
+/// byte-identical with and without the override.
+fn test_info_ignores_arg0(h: &Harness) -> Result<()> {
+    let renamed = h.scratch().join("renamed-tool");
+    let missing = h.scratch().join("arg0_missing.txt");
+    let missing = missing.to_str().unwrap();
+    let a = h.fixtures.sample_a.to_str().unwrap();
+    let wcat_name = h.wcat.to_str().unwrap();
+    let arg0s = [
+        (None, wcat_name),
+        (Some(h.cat.as_path()), h.cat.to_str().unwrap()),
+        (Some(renamed.as_path()), renamed.to_str().unwrap()),
+    ];
+    for arg in ["--help", "--version"] {
+        let plain = run_cmd(&h.wcat, &[arg], None)?.stdout;
+        if !plain.starts_with(b"Usage: wcat ") && !plain.starts_with(b"wcat ") {
+            bail!("{arg} does not open with the wcat name: {:?}", String::from_utf8_lossy(&plain));
+        }
+        for (arg0, name) in arg0s {
+            let out = run_cmd_with_arg0(&h.wcat, &[arg], None, arg0, &[])?;
+            if out.stdout != plain || !out.status.success() {
+                bail!("{arg} with argv[0] {name:?} differs from the plain run");
+            }
+            if arg0.is_some() && String::from_utf8_lossy(&out.stdout).contains(name) {
+                bail!("{arg} mentions argv[0] {name:?}");
+            }
+        }
+    }
+    for (arg0, name) in arg0s {
+        let cases: [(&[&str], String); 3] = [
+            (&["--bogus"], format!("{name}: unrecognized option '--bogus'\n")),
+            (&[missing], format!("{name}: {missing}: No such file or directory\n")),
+            (&["--dry-run", a], format!("{name}: {a}: ok\n")),
+        ];
+        for (args, expected) in cases {
+            let out = run_cmd_with_arg0(&h.wcat, args, None, arg0, &[])?;
+            if !String::from_utf8_lossy(&out.stderr).starts_with(&expected) {
+                bail!(
+                    "{args:?} diagnostic with argv[0] {name:?}: {:?}",
+                    String::from_utf8_lossy(&out.stderr)
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn test_version_output(h: &Harness) -> Result<()> {
+    let out = run_cmd_with_arg0(&h.wcat, &["--version"], None, Some(&h.cat), &[])?;
+    let cat_out = run_cmd(&h.cat, &["--version"], None)?;
+    if out.stdout == cat_out.stdout {
+        bail!("version output should remain wcat-specific");
     }
     if !String::from_utf8_lossy(&out.stdout).contains("wcat 0.1") {
         bail!("version string mismatch");
@@ -1922,23 +3305,342 @@ fn test_help_stdout_closed(h: &Harness) -> Result<()> {
         .stdout(Stdio::null())
         .stderr(Stdio::null())
         .status()?;
-    if wcat_status.code() != cat_status.code() {
-        bail!(
-            "--help exit mismatch {:?} vs {:?}",
-            wcat_status.code(),
-            cat_status.code()
-        );
+    if exit_of(&wcat_status) != exit_of(&cat_status) {
+        bail!("--help exit mismatch: {wcat_status} vs {cat_status}");
+    }
+    Ok(())
+}
+
+/// With fd 1 really closed (not /dev/null) both switches fail the write; the
+/// status and the "write error" diagnostic must match cat's.
+fn test_info_closed_stdout(h: &Harness) -> Result<()> {
+    use std::os::fd::{FromRawFd, OwnedFd};
+    let run_closed = |cmd: &Path, arg: &str| -> Result<std::process::Output> {
+        let mut command = Command::new(cmd);
+        command.arg0(&h.cat).arg(arg).stdin(Stdio::null()).stderr(Stdio::piped());
+        clean_env(&mut command, &[]);
+        // SAFETY: close is a single syscall, and fd 1 is not used again before exec.
+        unsafe {
+            command.pre_exec(|| {
+                nix::unistd::close(OwnedFd::from_raw_fd(1)).map_err(std::io::Error::from)
+            });
+        }
+        Ok(command.output()?)
+    };
+    for arg in ["--help", "--version"] {
+        let wcat_out = run_closed(&h.wcat, arg)?;
+        let cat_out = run_closed(&h.cat, arg)?;
+        if exit_of(&wcat_out.status) != exit_of(&cat_out.status) || wcat_out.stderr != cat_out.stderr {
+            bail!(
+                "{arg} with stdout closed: status {:?} vs {:?}, stderr {:?} vs {:?}",
+                wcat_out.status.code(),
+                cat_out.status.code(),
+                String::from_utf8_lossy(&wcat_out.stderr),
+                String::from_utf8_lossy(&cat_out.stderr)
+            );
+        }
+    }
+    Ok(())
+}
+
+/// With fd 2 closed, diagnostics have nowhere to go: cat drops them and
+/// carries on with the remaining operands, so stdout and the status match.
+fn test_closed_stderr(h: &Harness) -> Result<()> {
+    use std::os::fd::{FromRawFd, OwnedFd};
+    let a = h.fixtures.sample_a.to_str().unwrap();
+    let b = h.fixtures.sample_b.to_str().unwrap();
+    let missing = h.scratch().join("closed_stderr_missing.txt");
+    let missing = missing.to_str().unwrap();
+    let dir = h.fixtures.dir_path.to_str().unwrap();
+    let run_closed = |cmd: &Path, args: &[&str]| -> Result<std::process::Output> {
+        let mut command = Command::new(cmd);
+        command.args(args).stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::null());
+        clean_env(&mut command, &[]);
+        // SAFETY: close is a single syscall, and fd 2 is not used again before exec.
+        unsafe {
+            command.pre_exec(|| {
+                nix::unistd::close(OwnedFd::from_raw_fd(2)).map_err(std::io::Error::from)
+            });
+        }
+        Ok(command.output()?)
+    };
+    for args in [
+        &[a, missing, b][..],
+        &["-n", missing, dir, a],
+        &["-E", dir, missing],
+        &["-Z", a],
+    ] {
+        let wcat_out = run_closed(&h.wcat, args)?;
+        let cat_out = run_closed(&h.cat, args)?;
+        if wcat_out.stdout != cat_out.stdout
+            || exit_of(&wcat_out.status) != exit_of(&cat_out.status)
+        {
+            bail!(
+                "{args:?} with stderr closed: status {:?} vs {:?}\n{}",
+                wcat_out.status.code(),
+                cat_out.status.code(),
+                describe_diff("stdout", ("wcat", &wcat_out.stdout), ("cat", &cat_out.stdout))
+            );
+        }
+    }
+    Ok(())
+}
+
+/// describe_diff on synthetic buffers: line, column, caret and hex window.
+fn test_diff_report_text(_h: &Harness) -> Result<()> {
+    let report = describe_diff(
+        "stdout",
+        ("wcat", b"     1\tone\n     2\ttw^Io\n"),
+        ("cat", b"     1\tone\n     2\ttw\to\n"),
+    );
+    let expected = "stdout differs at byte 20 (line 2, column 10); wcat 24B, cat 23B\n\
+                    \x20 wcat line 2:      2\\ttw^Io\n\
+                    \x20 cat  line 2:      2\\ttw\\to\n\
+                    \x20                        ^\n\
+                    \x20 wcat @0xc: 20 20 20 20 32 09 74 77 [5e] 49 6f 0a\n\
+                    \x20 cat  @0xc: 20 20 20 20 32 09 74 77 [09] 6f 0a\n";
+    if report != expected {
+        bail!("report:\n{report}expected:\n{expected}");
+    }
+    let same = describe_diff("stderr", ("wcat", b"x"), ("cat", b"x"));
+    if same != "stderr: identical (1 bytes)\n" {
+        bail!("equal buffers: {same:?}");
+    }
+    Ok(())
+}
+
+/// A buffer that is a prefix of the other reports EOF at the first offset
+/// past its end, and a difference in byte 0 has no context before it.
+fn test_diff_report_eof(_h: &Harness) -> Result<()> {
+    let report = describe_diff("stdout", ("wcat", b"ab\n"), ("cat", b"ab\ncd"));
+    let expected = "stdout differs at byte 3 (line 2, column 1); wcat 3B, cat 5B\n\
+                    \x20 wcat line 2: \n\
+                    \x20 cat  line 2: cd\n\
+                    \x20              ^\n\
+                    \x20 wcat @0x0: 61 62 0a [EOF]\n\
+                    \x20 cat  @0x0: 61 62 0a [63] 64\n";
+    if report != expected {
+        bail!("report:\n{report}expected:\n{expected}");
+    }
+    let report = describe_diff("stdout", ("a", b"x"), ("b", b"y"));
+    if !report.starts_with("stdout differs at byte 0 (line 1, column 1); a 1B, b 1B\n")
+        || !report.ends_with("  a @0x0: [78]\n  b @0x0: [79]\n")
+    {
+        bail!("first-byte report: {report:?}");
+    }
+    Ok(())
+}
+
+/// NUL bytes suppress the line view, and long lines are cut around the
+/// difference with the caret still under it.
+fn test_diff_report_binary(_h: &Harness) -> Result<()> {
+    let report = describe_diff("stdout", ("wcat", b"\0\x01\x02\x03"), ("cat", b"\0\x01\xff\x03"));
+    if report.contains(" line 1: ")
+        || !report.contains("  wcat @0x0: 00 01 [02] 03\n  cat  @0x0: 00 01 [ff] 03\n")
+    {
+        bail!("binary report: {report:?}");
+    }
+    let mut left = vec![b'a'; 150];
+    let mut right = left.clone();
+    right[70] = b'b';
+    left.push(b'\n');
+    right.push(b'\n');
+    let report = describe_diff("stdout", ("wcat", &left), ("cat", &right));
+    let lines: Vec<&str> = report.lines().collect();
+    let shown = format!("  wcat line 1: ...{}...", "a".repeat(80));
+    if lines.len() != 6 || lines[1] != shown {
+        bail!("long-line report: {report}");
+    }
+    let caret = lines[3].len() - 1;
+    if lines[3].trim() != "^" || lines[2].as_bytes()[caret] != b'b' {
+        bail!("caret not under the difference:\n{report}");
+    }
+    Ok(())
+}
+
+/// A stub that "diverges" whenever -E is given (alone or clustered) and the
+/// input holds a CR must shrink to exactly `-E` and a lone CR.
+fn test_minimize_flag_and_byte(_h: &Harness) -> Result<()> {
+    let args: Vec<String> = ["-n", "-vEs", "--number", "-b"].map(String::from).to_vec();
+    let mut input = b"line one\n\tline two\n".repeat(200);
+    input.insert(1234, b'\r');
+    let mut calls = 0;
+    let (args, input) = minimize(&args, &input, |args, input| {
+        calls += 1;
+        let has_e = args.iter().any(|a| !a.starts_with("--") && a.contains('E'));
+        has_e && input.contains(&b'\r')
+    });
+    if args != ["-E"] || input != b"\r" {
+        bail!("minimized to {args:?} {:?} after {calls} calls", input.escape_ascii().to_string());
+    }
+    Ok(())
+}
+
+/// Shrinking never drops what the divergence needs: here two flags from
+/// different arguments and at least three newlines; and printf_escape
+/// round-trips the awkward bytes.
+fn test_minimize_keeps_needed(_h: &Harness) -> Result<()> {
+    let args: Vec<String> = ["-An", "-s", "--show-tabs"].map(String::from).to_vec();
+    let (args, input) = minimize(&args, b"a\nb\n\n\nc\nd\ne", |args, input| {
+        let has = |flag: &str| args.iter().any(|a| a == flag);
+        (has("-n") || has("-An")) && has("-s") && input.iter().filter(|&&b| b == b'\n').count() >= 3
+    });
+    if args != ["-n", "-s"] || input != b"\n\n\n" {
+        bail!("minimized to {args:?} {:?}", input.escape_ascii().to_string());
+    }
+    let escaped = printf_escape(b"it's 100%\\\t\xff");
+    if escaped != "it\\x27s 100\\x25\\x5c\\x09\\xff" {
+        bail!("printf_escape gave {escaped}");
+    }
+    Ok(())
+}
+
+/// parse_strace_summary on a captured `strace -c` table, including a row
+/// with an errors column and the total line.
+fn test_strace_summary_parsing(_h: &Harness) -> Result<()> {
+    let summary = "\
+% time     seconds  usecs/call     calls    errors syscall
+------ ----------- ----------- --------- --------- ----------------
+ 61.54    0.000016           4         4           write
+ 23.08    0.000006           6         1         1 openat
+ 15.38    0.000004           1         3           read
+------ ----------- ----------- --------- --------- ----------------
+100.00    0.000026           3         8         1 total
+";
+    let counts = parse_strace_summary(summary);
+    let expected = [("write", 4), ("openat", 1), ("read", 3), ("total", 8)];
+    if counts.len() != expected.len() || expected.iter().any(|(k, v)| counts.get(*k) != Some(v)) {
+        bail!("parsed {counts:?}");
+    }
+    Ok(())
+}
+
+/// Under `strace -f -c`, streaming a large input must take no more write()
+/// and writev() calls than one per output buffer, plus a little slack, so
+/// batching regressions show up even when wall time hides them.
+fn test_write_syscall_count(h: &Harness) -> Result<()> {
+    let Some(strace) = &h.strace else {
+        println!("[SKIP] strace not available");
+        return Ok(());
+    };
+    let large = h.fixtures.large.to_str().unwrap();
+    let large_bytes = fs::read(&h.fixtures.large)?;
+    for (args, input) in [
+        (&[large][..], None),
+        (&["-n", large], None),
+        (&["-A"], Some(&large_bytes[..])),
+        (&["-n", "-"], Some(&large_bytes[..])),
+    ] {
+        let summary = NamedTempFile::new_in(h.fixtures.dir.path())?;
+        let output = NamedTempFile::new_in(h.fixtures.dir.path())?;
+        let mut strace_args = vec!["-f", "-c", "-o", summary.path().to_str().unwrap(), "--"];
+        strace_args.push(h.wcat.to_str().unwrap());
+        strace_args.extend_from_slice(args);
+        let out = run_cmd_to_file(strace, &strace_args, input, None, &[], output.path())?;
+        if !out.status.success() {
+            bail!("strace wcat {args:?} failed: {}", String::from_utf8_lossy(&out.stderr));
+        }
+        let counts = parse_strace_summary(&fs::read_to_string(summary.path())?);
+        let writes = counts.get("write").unwrap_or(&0) + counts.get("writev").unwrap_or(&0);
+        let bytes = fs::metadata(output.path())?.len();
+        let limit = bytes.div_ceil(WCAT_BUFFER_SIZE as u64) + 2;
+        if writes > limit {
+            bail!("wcat {args:?} made {writes} write calls for {bytes} bytes (limit {limit})");
+        }
+    }
+    Ok(())
+}
+
+/// wcat makes no locale-dependent calls, so whatever locale the caller has
+/// set, --help, --version and -v output must be byte-identical to the C run.
+fn test_info_locale(h: &Harness) -> Result<()> {
+    let high = h.scratch().join("locale_high_bytes.txt");
+    fs::write(&high, b"caf\xc3\xa9 \xe2\x82\xac \x80\xff\n")?;
+    let high = high.to_str().unwrap();
+    let run = |args: &[&str], locale: Option<&str>| -> Result<std::process::Output> {
+        let mut command = Command::new(&h.wcat);
+        command.args(args);
+        clean_env(&mut command, &[]);
+        if let Some(locale) = locale {
+            command
+                .env_remove("LC_ALL")
+                .env("LANG", locale)
+                .env("LC_MESSAGES", locale)
+                .env("LC_CTYPE", locale)
+                .env("LC_TIME", locale)
+                .env("LANGUAGE", locale);
+        }
+        let child = command.stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+        wait_with_deadline(child, &h.wcat, args)
+    };
+    for args in [vec!["--help"], vec!["--version"], vec!["-A", high]] {
+        let baseline = run(&args, None)?;
+        for locale in ["POSIX", "C.UTF-8", "en_US.UTF-8", "de_DE.UTF-8", "tr_TR.UTF-8", "ja_JP.eucJP"] {
+            let out = run(&args, Some(locale))?;
+            if out.stdout != baseline.stdout || exit_of(&out.status) != exit_of(&baseline.status) {
+                bail!(
+                    "{args:?} under {locale}: {:?} (status {}) differs from the C locale's {:?}",
+                    String::from_utf8_lossy(&out.stdout),
+                    out.status,
+                    String::from_utf8_lossy(&baseline.stdout)
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The first of --help/--version wins, an earlier bad option beats both,
+/// and operands never matter. The texts differ, so compare which one ran.
+fn test_info_precedence(h: &Harness) -> Result<()> {
+    let a = h.fixtures.sample_a.to_str().unwrap();
+    let missing = h.scratch().join("missing_info.txt");
+    let missing = missing.to_str().unwrap();
+    let kind = |out: &CmdOutput| -> &'static str {
+        let stdout = String::from_utf8_lossy(&out.stdout);
+        if stdout.starts_with("Usage: ") {
+            "help"
+        } else if stdout.starts_with("wcat ") || stdout.starts_with("cat (") {
+            "version"
+        } else {
+            "other"
+        }
+    };
+    for args in [
+        vec!["--help", "--version"],
+        vec!["--version", "--help"],
+        vec!["-n", "--version", a],
+        vec![missing, "--help"],
+        vec!["--version", "-x"],
+        vec!["--help", "--bogus"],
+        vec!["--version=1", "--help"],
+    ] {
+        let wcat_out = run_cmd_with_arg0(&h.wcat, &args, None, Some(&h.cat), &[])?;
+        let cat_out = run_cmd(&h.cat, &args, None)?;
+        if kind(&wcat_out) != kind(&cat_out) || exit_of(&wcat_out.status) != exit_of(&cat_out.status) {
+            bail!(
+                "{args:?}: wcat gave {} (status {:?}), cat gave {} (status {:?})",
+                kind(&wcat_out),
+                wcat_out.status.code(),
+                kind(&cat_out),
+                cat_out.status.code()
+            );
+        }
+    }
+    // An invalid option before either switch is reported like cat does.
+    for args in [["-x", "--help"], ["--bogus", "--version"]] {
+        h.compare_with_cat(&args, None)?;
     }
     Ok(())
 }
 
 fn test_missing_file(h: &Harness) -> Result<()> {
-    let missing = h.fixtures.dir.path().join("missing.txt");
+    let missing = h.scratch().join("missing.txt");
     h.compare_with_cat(&[missing.to_str().unwrap()], None)
 }
 
 fn test_missing_among_files(h: &Harness) -> Result<()> {
-    let missing = h.fixtures.dir.path().join("missing.txt");
+    let missing = h.scratch().join("missing.txt");
     let args = [
         h.fixtures.sample_a.to_str().unwrap(),
         missing.to_str().unwrap(),
@@ -1947,12 +3649,54 @@ fn test_missing_among_files(h: &Harness) -> Result<()> {
     h.compare_with_cat(&args, None)
 }
 
+/// Good, missing, good, missing, good: with stdout and stderr on one pipe each
+/// diagnostic must land right after the previous operand's output, as with cat.
+/// The large fixture makes sure a buffered decorated write is still pending
+/// when the next operand fails.
+fn test_merged_error_order(h: &Harness) -> Result<()> {
+    let missing = h.scratch().join("missing_merged.txt");
+    let missing_again = h.scratch().join("missing_merged_again.txt");
+    let operands = [
+        h.fixtures.sample_a.to_str().unwrap(),
+        missing.to_str().unwrap(),
+        h.fixtures.large.to_str().unwrap(),
+        missing_again.to_str().unwrap(),
+        h.fixtures.sample_b.to_str().unwrap(),
+    ];
+    for opts in [&[][..], &["-n"], &["-v"], &["-A"], &["-s"], &["-b", "-E"]] {
+        let mut args = opts.to_vec();
+        args.extend(operands);
+        h.compare_merged_with_cat(&args)?;
+    }
+    Ok(())
+}
+
 fn test_bad_option(h: &Harness) -> Result<()> {
     h.compare_with_cat(&["-x"], None)
 }
 
+/// An unknown letter inside a cluster must be named in the diagnostic, in
+/// cat's exact wording, wherever it sits in the cluster.
 fn test_bad_option_bundle(h: &Harness) -> Result<()> {
-    h.compare_with_cat(&["-nZ"], None)
+    h.compare_with_cat(&["-nZ"], None)?;
+    let arg0 = Some(Path::new("cat"));
+    for cluster in ["-nZ", "-Zn", "-nEZv", "-sZZ"] {
+        let wcat = run_cmd_with_arg0(&h.wcat, &[cluster], None, arg0, &[])?;
+        let cat = run_cmd_with_arg0(&h.cat, &[cluster], None, arg0, &[])?;
+        let expected = "cat: invalid option -- 'Z'\nTry 'cat --help' for more information.\n";
+        if cat.stderr != expected.as_bytes() {
+            let got = String::from_utf8_lossy(&cat.stderr);
+            bail!("cat's own {cluster} diagnostic changed: {got:?}");
+        }
+        if wcat.stderr != cat.stderr || exit_of(&wcat.status) != exit_of(&cat.status) {
+            bail!(
+                "{cluster}: wcat wrote {:?} (status {:?}), cat wrote {expected:?}",
+                String::from_utf8_lossy(&wcat.stderr),
+                wcat.status.code()
+            );
+        }
+    }
+    Ok(())
 }
 
 fn test_mid_argv_double_dash(h: &Harness) -> Result<()> {
@@ -2042,8 +3786,8 @@ fn test_long_option_disallow_arg(h: &Harness) -> Result<()> {
 }
 
 fn test_line_state_across_files(h: &Harness) -> Result<()> {
-    let no_nl = h.fixtures.dir.path().join("no_newline_boundary.txt");
-    let with_nl = h.fixtures.dir.path().join("newline_boundary.txt");
+    let no_nl = h.scratch().join("no_newline_boundary.txt");
+    let with_nl = h.scratch().join("newline_boundary.txt");
     fs::write(&no_nl, b"first")?;
     fs::write(&with_nl, b"second\n")?;
     h.compare_with_cat(
@@ -2052,32 +3796,58 @@ fn test_line_state_across_files(h: &Harness) -> Result<()> {
     )
 }
 
+/// A file without a final newline runs into the next one, so -E must put
+/// exactly one $ at the seam, after the joined line, on every decoration
+/// path (plain -E, -T's, -v's) and with stdin on either side.
+fn test_show_ends_seam(h: &Harness) -> Result<()> {
+    let no_nl = h.fixtures.no_newline.to_str().unwrap();
+    let b = h.fixtures.sample_b.to_str().unwrap();
+    let no_nl_data = fs::read(&h.fixtures.no_newline)?;
+    let cases: [(&[&str], &[u8]); 6] = [
+        (&["-E", no_nl, b], b"no newlinebeta$\n"),
+        (&["-E", no_nl, no_nl, b], b"no newlineno newlinebeta$\n"),
+        (&["-E", b, no_nl], b"beta$\nno newline"),
+        (&["-nE", no_nl, b], b"     1\tno newlinebeta$\n"),
+        (&["-TE", no_nl, b], b"no newlinebeta$\n"),
+        (&["-vE", no_nl, b], b"no newlinebeta$\n"),
+    ];
+    for (args, expected) in cases {
+        h.expect_wcat(args, None, expected)?;
+        h.compare_with_cat(args, None)?;
+    }
+    for (args, input) in [(["-E", "-", b], &no_nl_data[..]), (["-E", no_nl, "-"], b"beta\n")] {
+        h.expect_wcat(&args, Some(input), b"no newlinebeta$\n")?;
+        h.compare_with_cat(&args, Some(input))?;
+    }
+    Ok(())
+}
+
 fn test_squeeze_across_files(h: &Harness) -> Result<()> {
-    let a = h.fixtures.dir.path().join("blank_a.txt");
-    let b = h.fixtures.dir.path().join("blank_b.txt");
+    let a = h.scratch().join("blank_a.txt");
+    let b = h.scratch().join("blank_b.txt");
     fs::write(&a, b"line1\n\n")?;
     fs::write(&b, b"\n\nline2\n")?;
     h.compare_with_cat(&["-s", a.to_str().unwrap(), b.to_str().unwrap()], None)
 }
 
 fn test_b_across_files(h: &Harness) -> Result<()> {
-    let a = h.fixtures.dir.path().join("b_across_a.txt");
-    let b = h.fixtures.dir.path().join("b_across_b.txt");
+    let a = h.scratch().join("b_across_a.txt");
+    let b = h.scratch().join("b_across_b.txt");
     fs::write(&a, b"line1\n\n")?;
     fs::write(&b, b"\nline2\n")?;
     h.compare_with_cat(&["-b", a.to_str().unwrap(), b.to_str().unwrap()], None)
 }
 
 fn test_squeeze_no_newline_boundary(h: &Harness) -> Result<()> {
-    let a = h.fixtures.dir.path().join("squeeze_no_nl_a.txt");
-    let b = h.fixtures.dir.path().join("squeeze_no_nl_b.txt");
+    let a = h.scratch().join("squeeze_no_nl_a.txt");
+    let b = h.scratch().join("squeeze_no_nl_b.txt");
     fs::write(&a, b"line1\n\n")?;
     fs::write(&b, b"\nline2")?;
     h.compare_with_cat(&["-s", a.to_str().unwrap(), b.to_str().unwrap()], None)
 }
 
 fn test_large_line_numbers(h: &Harness) -> Result<()> {
-    let path = h.fixtures.dir.path().join("million_lines.txt");
+    let path = h.scratch().join("million_lines.txt");
     let lines = 1_000_005usize;
     let mut data = Vec::with_capacity(lines * 2);
     for _ in 0..lines {
@@ -2087,6 +3857,43 @@ fn test_large_line_numbers(h: &Harness) -> Result<()> {
     h.compare_with_cat(&["-n", path.to_str().unwrap()], None)
 }
 
+/// Line numbers outgrow their padded field at 1,000,000 (7 digits) and keep
+/// growing with no padding; wcat has no --number-start, so the input simply
+/// has that many lines. Every decoration path is checked against literal
+/// bytes around the boundary, and -w pins the same carry at small widths.
+fn test_number_width_growth(h: &Harness) -> Result<()> {
+    let path = h.scratch().join("width_growth.txt");
+    fs::write(&path, b"x\n".repeat(1_000_001))?;
+    let path = path.to_str().unwrap();
+    let cases: [(&[&str], &[u8]); 5] = [
+        (&["-n"], b"999999\tx\n1000000\tx\n1000001\tx\n"),
+        (&["-b"], b"999999\tx\n1000000\tx\n1000001\tx\n"),
+        (&["-nE"], b"999999\tx$\n1000000\tx$\n1000001\tx$\n"),
+        (&["-nT"], b"999999\tx\n1000000\tx\n1000001\tx\n"),
+        (&["-nv"], b"999999\tx\n1000000\tx\n1000001\tx\n"),
+    ];
+    for (flags, tail) in cases {
+        let out = run_cmd(&h.wcat, &[flags, &[path]].concat(), None)?;
+        if !out.status.success() || !out.stdout.ends_with(tail) {
+            let start = out.stdout.len().saturating_sub(tail.len());
+            bail!(
+                "{flags:?}: output ends with {:?}, expected {:?}",
+                String::from_utf8_lossy(&out.stdout[start..]),
+                String::from_utf8_lossy(tail)
+            );
+        }
+    }
+    let twelve = b"x\n".repeat(12);
+    let expected = b"1\tx\n2\tx\n3\tx\n4\tx\n5\tx\n6\tx\n7\tx\n8\tx\n9\tx\n10\tx\n11\tx\n12\tx\n";
+    h.expect_wcat(&["-w", "1", "-n"], Some(&twelve), expected)?;
+    let hundred = b"x\n".repeat(101);
+    let out = run_cmd(&h.wcat, &["-w", "2", "-nE", "-"], Some(&hundred))?;
+    if !out.stdout.ends_with(b"98\tx$\n99\tx$\n100\tx$\n101\tx$\n") {
+        bail!("-w 2 past 99: {:?}", String::from_utf8_lossy(&out.stdout));
+    }
+    Ok(())
+}
+
 fn test_enoent_vs_eacces(h: &Harness) -> Result<()> {
     let missing = h.fixtures.dir_path.join("nope");
     let locked = h.fixtures.dir_path.join("locked.txt");
@@ -2101,6 +3908,21 @@ fn test_enoent_vs_eacces(h: &Harness) -> Result<()> {
     result
 }
 
+fn test_unreadable_directory(h: &Harness) -> Result<()> {
+    // Unprivileged, open() fails with EACCES; as root it succeeds and the read
+    // fails with EISDIR. Either way wcat must pick the same message as cat.
+    let locked = h.fixtures.dir_path.join("locked_dir");
+    fs::create_dir_all(&locked)?;
+    fs::set_permissions(&locked, fs::Permissions::from_mode(0o000))?;
+
+    let result = (|| {
+        h.compare_with_cat(&[locked.to_str().unwrap()], None)?;
+        h.compare_with_cat(&["-n", locked.to_str().unwrap()], None)
+    })();
+    fs::set_permissions(&locked, fs::Permissions::from_mode(0o700))?;
+    result
+}
+
 fn test_directory_operand(h: &Harness) -> Result<()> {
     h.compare_with_cat(&[h.fixtures.dir_path.to_str().unwrap()], None)
 }
@@ -2126,6 +3948,25 @@ fn test_binary_passthrough(h: &Harness) -> Result<()> {
     h.compare_with_cat(&["-"], Some(&buf))
 }
 
+/// -u may only change buffering, never bytes. wcat accepts and ignores it,
+/// so this pins that no -u combination on binary data picks a path that
+/// truncates or reorders: the binary fixture as a file and on stdin, control
+/// bytes under -v and -A, and random data spanning several read() chunks.
+fn test_unbuffered_binary(h: &Harness) -> Result<()> {
+    let binary = h.fixtures.binary.to_str().unwrap();
+    let control = h.fixtures.control.to_str().unwrap();
+    for flags in [&["-u"][..], &["-uv"], &["-u", "-A"], &["-un"]] {
+        h.compare_with_cat(&[flags, &[binary]].concat(), None)?;
+        h.compare_with_cat(&[flags, &["-"]].concat(), Some(&fs::read(binary)?))?;
+        h.compare_with_cat(&[flags, &[control, binary, control]].concat(), None)?;
+    }
+    h.compare_with_cat(&["-uv", control], None)?;
+    let mut buf = vec![0u8; 3 * WCAT_BUFFER_SIZE + 17];
+    rand::thread_rng().fill_bytes(&mut buf);
+    h.compare_with_cat(&["-u"], Some(&buf))?;
+    h.compare_with_cat(&["-u", "-v", "-"], Some(&buf))
+}
+
 fn test_mixed_stdin_file_numbering(h: &Harness) -> Result<()> {
     let mut stdin_payload = Vec::new();
     stdin_payload.extend_from_slice(b"stdin first\n");
@@ -2152,66 +3993,530 @@ fn test_broken_pipe_write_error(h: &Harness) -> Result<()> {
 
     let wcat_status = pipeline_exit(&h.wcat, data)?;
     let cat_status = pipeline_exit(&h.cat, data)?;
-    if wcat_status != cat_status {
-        bail!("broken pipe exit mismatch {:?} vs {:?}", wcat_status, cat_status);
+    if exit_of(&wcat_status) != exit_of(&cat_status) {
+        bail!("broken pipe exit mismatch: {wcat_status} vs {cat_status}");
     }
     Ok(())
 }
 
-fn pipeline_exit(cmd: &Path, data: &[u8]) -> Result<Option<i32>> {
-    let mut producer = Command::new(cmd)
-        .arg("-")
-        .stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .spawn()
-        .with_context(|| format!("spawn producer {cmd:?}"))?;
+/// Write into a pipe nobody reads: with the default disposition both binaries
+/// must die of SIGPIPE, and with SIGPIPE ignored both must exit 1 with the
+/// same "Broken pipe" write error. Covers the plain and decorated paths.
+fn test_sigpipe_parity(h: &Harness) -> Result<()> {
+    let large = h.fixtures.large.to_str().unwrap();
+    for ignore in [false, true] {
+        for flags in [vec![], vec!["-n"], vec!["-v"]] {
+            let mut args = flags.clone();
+            args.push(large);
+            let (wcat_exit, wcat_err) = run_into_closed_pipe(&h.wcat, &args, ignore)?;
+            let (cat_exit, cat_err) = run_into_closed_pipe(&h.cat, &args, ignore)?;
+            if wcat_exit != cat_exit || wcat_err != cat_err {
+                bail!(
+                    "SIGPIPE {} {flags:?}: wcat (code, signal) {wcat_exit:?} stderr {:?} vs cat {cat_exit:?} stderr {:?}",
+                    if ignore { "ignored" } else { "default" },
+                    String::from_utf8_lossy(&wcat_err),
+                    String::from_utf8_lossy(&cat_err)
+                );
+            }
+            let expected = if ignore { (Some(1), None) } else { (None, Some(Signal::SIGPIPE as i32)) };
+            if cat_exit != expected {
+                bail!("cat itself ended with {cat_exit:?}, expected {expected:?}");
+            }
+        }
+    }
+    Ok(())
+}
 
-    let mut consumer = Command::new("head")
-        .arg("-n0")
-        .stdin(producer.stdout.take().unwrap())
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .spawn()
-        .context("spawn head")?;
-    let head_status = consumer.wait()?;
-    producer.stdin.take().unwrap().write_all(data)?;
-    let prod_status = producer.wait()?;
-    let _ = head_status;
-    Ok(prod_status.code())
+/// Run `cmd` (as `cat`) with stdout on a pipe whose read end is already
+/// closed, optionally with SIGPIPE ignored; returns how it ended and stderr.
+fn run_into_closed_pipe(cmd: &Path, args: &[&str], ignore_sigpipe: bool) -> Result<(Exit, Vec<u8>)> {
+    use nix::sys::signal::{signal, SigHandler};
+    let (reader, writer) = std::io::pipe()?;
+    drop(reader);
+    let mut command = Command::new(cmd);
+    command
+        .arg0("cat")
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(writer)
+        .stderr(Stdio::piped());
+    if ignore_sigpipe {
+        // SAFETY: signal() is async-signal-safe; the disposition survives exec.
+        unsafe {
+            command.pre_exec(|| {
+                signal(Signal::SIGPIPE, SigHandler::SigIgn)
+                    .map(|_| ())
+                    .map_err(std::io::Error::from)
+            });
+        }
+    }
+    let child = command.spawn().with_context(|| format!("spawning {cmd:?}"))?;
+    drop(command);
+    let output = wait_with_deadline(child, cmd, args)?;
+    Ok((exit_of(&output.status), output.stderr))
 }
 
-fn test_dash_file_before_options(h: &Harness) -> Result<()> {
-    let args = ["--", h.fixtures.dash_name.to_str().unwrap(), "-n"];
-    h.compare_with_cat(&args, None)
+fn test_dev_full_write_error(h: &Harness) -> Result<()> {
+    // Every write to /dev/full fails with ENOSPC; the diagnostic must match cat's.
+    let dev_full = Path::new("/dev/full");
+    if !dev_full.exists() {
+        println!("[SKIP] /dev/full not available");
+        return Ok(());
+    }
+    let large = h.fixtures.large.to_str().unwrap();
+    for args in [vec![large], vec!["-n", large]] {
+        let out = run_cmd_to_file(&h.wcat, &args, None, Some(&h.cat), &[], dev_full)?;
+        let expected = run_cmd_to_file(&h.cat, &args, None, None, &[], dev_full)?;
+        if exit_of(&out.status) != exit_of(&expected.status) || out.stderr != expected.stderr {
+            bail!(
+                "/dev/full mismatch for {:?}: status {:?} vs {:?}\nwcat: {}cat:  {}",
+                args,
+                out.status.code(),
+                expected.status.code(),
+                String::from_utf8_lossy(&out.stderr),
+                String::from_utf8_lossy(&expected.stderr)
+            );
+        }
+    }
+    Ok(())
 }
 
-fn test_literal_dash_filename(h: &Harness) -> Result<()> {
-    let dash_path = h.fixtures.dir.path().join("-");
-    fs::write(&dash_path, b"dash literal\n")?;
-    let args = ["--", dash_path.to_str().unwrap()];
-    h.compare_with_cat(&args, None)
+fn test_many_operands(h: &Harness) -> Result<()> {
+    // One open/copy/close per shard; quadratic work or a leaked fd per operand
+    // shows up long before the last file.
+    let shards = h.scratch().join("shards");
+    fs::create_dir_all(&shards)?;
+    let mut paths = Vec::with_capacity(10_000);
+    for i in 0..10_000 {
+        let path = shards.join(format!("shard_{i:05}.txt"));
+        fs::write(&path, format!("shard {i}\n"))?;
+        paths.push(path.to_str().unwrap().to_string());
+    }
+    let mut args: Vec<&str> = vec!["-n"];
+    args.extend(paths.iter().map(String::as_str));
+    h.compare_with_cat(&args, None)?;
+    h.compare_with_cat(&args[1..], None)
 }
 
-fn test_visible_del(h: &Harness) -> Result<()> {
-    let path = h.fixtures.dir.path().join("del.txt");
-    fs::write(&path, b"del:\x7f!\n")?;
-    h.compare_with_cat(&["-v", path.to_str().unwrap()], None)
+fn test_operands_fd_limit(h: &Harness) -> Result<()> {
+    // With RLIMIT_NOFILE at 12 a descriptor leaked per operand runs into EMFILE
+    // within a handful of files, while cat gets through all of them.
+    let dir = h.scratch().join("fd_limit");
+    fs::create_dir_all(&dir)?;
+    let mut paths = Vec::new();
+    for i in 0..300 {
+        let path = dir.join(format!("part_{i:03}.txt"));
+        fs::write(&path, format!("part {i}\n"))?;
+        paths.push(path.to_str().unwrap().to_string());
+    }
+    for flags in [vec![], vec!["-n"]] {
+        let mut args = flags;
+        args.extend(paths.iter().map(String::as_str));
+        let wcat_out = run_cmd_with_fd_limit(&h.wcat, &args, 12)?;
+        let cat_out = run_cmd_with_fd_limit(&h.cat, &args, 12)?;
+        if wcat_out.stdout != cat_out.stdout || exit_of(&wcat_out.status) != exit_of(&cat_out.status) {
+            bail!(
+                "fd limit {:?}: wcat status {:?} ({}B, stderr {:?}) vs cat {:?} ({}B)",
+                &args[..args.len() - paths.len()],
+                wcat_out.status.code(),
+                wcat_out.stdout.len(),
+                String::from_utf8_lossy(&wcat_out.stderr).lines().next(),
+                cat_out.status.code(),
+                cat_out.stdout.len()
+            );
+        }
+    }
+    Ok(())
 }
 
-fn test_file_named_help(h: &Harness) -> Result<()> {
-    let path = h.fixtures.dir.path().join("--help");
-    fs::write(&path, b"help file\n")?;
-    h.compare_with_cat(&["--", path.to_str().unwrap()], None)
+/// Run `cmd` with both RLIMIT_NOFILE limits lowered to `limit` in the child.
+fn run_cmd_with_fd_limit(cmd: &Path, args: &[&str], limit: u64) -> Result<CmdOutput> {
+    use nix::sys::resource::{setrlimit, Resource};
+    let mut command = Command::new(cmd);
+    command.args(args).stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped());
+    clean_env(&mut command, &[]);
+    // SAFETY: setrlimit is a single syscall and allocates nothing.
+    unsafe {
+        command.pre_exec(move || {
+            setrlimit(Resource::RLIMIT_NOFILE, limit, limit).map_err(std::io::Error::from)
+        });
+    }
+    let child = command.spawn().with_context(|| format!("spawning {cmd:?}"))?;
+    let output = wait_with_deadline(child, cmd, args)?;
+    Ok(CmdOutput {
+        status: output.status,
+        stdout: output.stdout,
+        stderr: output.stderr,
+    })
 }
 
-fn test_file_named_version(h: &Harness) -> Result<()> {
-    let path = h.fixtures.dir.path().join("--version");
-    fs::write(&path, b"version file\n")?;
-    h.compare_with_cat(&["--", path.to_str().unwrap()], None)
+fn test_growing_file(h: &Harness) -> Result<()> {
+    // Start with far more than a pipe plus wcat's buffers hold and leave stdout
+    // unread, so the child is parked mid-file when the append lands. EOF is whatever read()
+    // says once it gets there, so the appended tail must come out too.
+    let large = fs::read(&h.fixtures.large)?;
+    let initial = large.repeat((4 << 20) / large.len() + 1);
+    let tail: Vec<u8> = (0..2000).flat_map(|i| format!("appended {i}\n").into_bytes()).collect();
+    for flags in [vec![], vec!["-n"]] {
+        let mut outputs = Vec::new();
+        for cmd in [&h.wcat, &h.cat] {
+            let path = h.scratch().join("growing.log");
+            fs::write(&path, &initial)?;
+            let mut args = flags.clone();
+            args.push(path.to_str().unwrap());
+            let child = Command::new(cmd)
+                .args(&args)
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()
+                .with_context(|| format!("spawning {cmd:?}"))?;
+            std::thread::sleep(std::time::Duration::from_millis(100));
+            File::options().append(true).open(&path)?.write_all(&tail)?;
+            let output = wait_with_deadline(child, cmd, &args)?;
+            outputs.push((output.stdout, exit_of(&output.status)));
+        }
+        let (wcat_out, cat_out) = (&outputs[0], &outputs[1]);
+        if wcat_out != cat_out {
+            bail!(
+                "growing file {flags:?}: wcat {}B (code, signal) {:?} vs cat {}B {:?}",
+                wcat_out.0.len(),
+                wcat_out.1,
+                cat_out.0.len(),
+                cat_out.1
+            );
+        }
+        if flags.is_empty() && wcat_out.0.len() != initial.len() + tail.len() {
+            bail!("growing file: {}B written, expected the appended tail too", wcat_out.0.len());
+        }
+    }
+    Ok(())
 }
 
-fn test_double_dash_then_dash(h: &Harness) -> Result<()> {
-    let args = ["--", "-"];
+fn test_slow_pipe_reader(h: &Harness) -> Result<()> {
+    // huge.txt fits in a pipe buffer, so repeat it until writes actually block.
+    let huge = fs::read(&h.fixtures.huge)?;
+    let path = h.scratch().join("backpressure.txt");
+    fs::write(&path, huge.repeat((1 << 20) / huge.len() + 1))?;
+    let path = path.to_str().unwrap();
+    for args in [vec![path], vec!["-n", path]] {
+        let (wcat_bytes, wcat_exit) = slow_reader_output(&h.wcat, &args)?;
+        let (cat_bytes, cat_exit) = slow_reader_output(&h.cat, &args)?;
+        if wcat_bytes != cat_bytes || wcat_exit != cat_exit {
+            bail!(
+                "slow reader mismatch for {:?}: {}B (code, signal) {:?} vs {}B {:?}",
+                args,
+                wcat_bytes.len(),
+                wcat_exit,
+                cat_bytes.len(),
+                cat_exit
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Drain `cmd`'s stdout a few bytes at a time with pauses, killing it if it
+/// outlives the --timeout deadline.
+fn slow_reader_output(cmd: &Path, args: &[&str]) -> Result<(Vec<u8>, Exit)> {
+    let mut child = Command::new(cmd)
+        .args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .with_context(|| format!("spawning {cmd:?}"))?;
+    let mut stdout = child.stdout.take().unwrap();
+    let child = std::sync::Arc::new(std::sync::Mutex::new(child));
+    let (done_tx, done_rx) = std::sync::mpsc::channel::<()>();
+    let watchdog = std::thread::spawn({
+        let child = child.clone();
+        move || {
+            if done_rx.recv_timeout(child_timeout()).is_err() {
+                let _ = child.lock().unwrap().kill();
+                return true;
+            }
+            false
+        }
+    });
+    let mut collected = Vec::new();
+    let mut chunk = [0u8; 512];
+    let mut reads = 0u32;
+    loop {
+        let n = stdout.read(&mut chunk)?;
+        if n == 0 {
+            break;
+        }
+        collected.extend_from_slice(&chunk[..n]);
+        reads += 1;
+        if reads.is_multiple_of(64) {
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+    }
+    let _ = done_tx.send(());
+    if watchdog.join().unwrap() {
+        bail!("{cmd:?} {args:?} did not finish under a slow reader");
+    }
+    let status = child.lock().unwrap().wait()?;
+    Ok((collected, exit_of(&status)))
+}
+
+/// Every `extension` case must end the same way with 16 workers as it does on
+/// one thread; a difference means two cases raced on a file or an fd. That tag
+/// is a few dozen cases with their own scratch files, so this fits the default
+/// run, and leaving out `tooling` keeps the case from running itself.
+fn test_parallel_matches_serial(h: &Harness) -> Result<()> {
+    let exe = std::env::current_exe()?;
+    let dir = &h.scratch();
+    let run = |threads: &str| -> Result<HashMap<String, String>> {
+        let trace = dir.join(format!("trace-{threads}.json"));
+        let mut command = Command::new(&exe);
+        command
+            .args(["tests", "--tag", "extension", "--threads", threads, "--wcat"])
+            .arg(&h.wcat)
+            .arg("--trace-json")
+            .arg(&trace)
+            .stdout(Stdio::null());
+        // Failing cases are compared below, not treated as an error here.
+        let _ = command.status().with_context(|| command_line(&command))?;
+        let cases: Vec<serde_json::Value> = serde_json::from_slice(&fs::read(&trace)?)?;
+        Ok(cases
+            .iter()
+            .map(|case| (case["name"].to_string(), case["status"].to_string()))
+            .collect())
+    };
+    let serial = run("1")?;
+    let parallel = run("16")?;
+    let mut differing: Vec<String> = serial
+        .iter()
+        .filter(|(name, status)| parallel.get(*name) != Some(status))
+        .map(|(name, status)| format!("{name}: {status} serially, {:?} with --threads 16", parallel.get(name)))
+        .collect();
+    if serial.len() != parallel.len() || !differing.is_empty() {
+        differing.sort();
+        bail!(
+            "{} serial vs {} parallel cases differ:\n{}",
+            serial.len(),
+            parallel.len(),
+            differing.join("\n")
+        );
+    }
+    Ok(())
+}
+
+/// A run narrowed by --tag or --filter must still fail when one of its cases
+/// does, or CI jobs that run one group at a time would never go red. `false`
+/// stands in for a wcat that gets every case wrong.
+fn test_selected_run_status(_h: &Harness) -> Result<()> {
+    let exe = std::env::current_exe()?;
+    let broken = which::which("false").context("`false` not found in PATH")?;
+    for selection in [["--tag", "error"], ["--filter", "missing file error"]] {
+        let mut command = Command::new(&exe);
+        command
+            .arg("tests")
+            .args(selection)
+            .arg("--wcat")
+            .arg(&broken)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        let status = command.status().with_context(|| command_line(&command))?;
+        if status.success() {
+            bail!("{selection:?} run against a failing wcat exited 0");
+        }
+    }
+    Ok(())
+}
+
+/// Under --filter the SUMMARY line describes the cases that ran: passed and
+/// failed add up to total, and total is the number of [PASS]/[FAIL] lines.
+fn test_summary_machine_filtered(h: &Harness) -> Result<()> {
+    let exe = std::env::current_exe()?;
+    let mut command = Command::new(&exe);
+    command
+        .args(["tests", "--filter", "missing file", "--summary-machine", "--wcat"])
+        .arg(&h.wcat)
+        .stderr(Stdio::null());
+    let out = command.output().with_context(|| command_line(&command))?;
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    let ran = stdout.lines().filter(|l| l.starts_with("[PASS] ") || l.starts_with("[FAIL] ")).count();
+    let summary = stdout
+        .lines()
+        .find_map(|l| l.strip_prefix("SUMMARY "))
+        .context("no SUMMARY line")?;
+    let field = |key: &str| -> Result<String> {
+        summary
+            .split(' ')
+            .find_map(|kv| kv.strip_prefix(key)?.strip_prefix('=').map(str::to_string))
+            .with_context(|| format!("SUMMARY has no {key}: {summary:?}"))
+    };
+    let [passed, failed, total]: [usize; 3] = [
+        field("passed")?.parse()?,
+        field("failed")?.parse()?,
+        field("total")?.parse()?,
+    ];
+    if passed + failed != total || total != ran || ran == 0 || field("filtered")? != "true" {
+        bail!("SUMMARY {summary:?} does not match the {ran} cases that ran");
+    }
+    Ok(())
+}
+
+fn test_child_timeout(h: &Harness) -> Result<()> {
+    // Opening a fifo with no writer blocks forever: the deadline must fire.
+    let fifo = h.scratch().join("never_written");
+    mkfifo(&fifo, nix::sys::stat::Mode::from_bits_truncate(0o644))?;
+    let saved = CHILD_TIMEOUT_SECS.swap(1, Ordering::Relaxed);
+    let result = run_cmd(&h.wcat, &[fifo.to_str().unwrap()], None);
+    CHILD_TIMEOUT_SECS.store(saved, Ordering::Relaxed);
+    match result {
+        Err(e) if format!("{e}").starts_with("timeout:") => Ok(()),
+        Err(e) => bail!("unexpected error from a hung wcat: {e:#}"),
+        Ok(out) => bail!("hung wcat was not killed (status {:?})", out.status.code()),
+    }
+}
+
+fn test_preallocate_parity(h: &Harness) -> Result<()> {
+    // --preallocate only reserves blocks: the file must end up byte-identical
+    // to plain cat's, including across several operands and a stdin operand.
+    let large = h.fixtures.large.to_str().unwrap();
+    let sample_a = h.fixtures.sample_a.to_str().unwrap();
+    let blank = h.fixtures.blank.to_str().unwrap();
+    let stdin_data = fs::read(&h.fixtures.huge)?;
+    for operands in [vec![large], vec![sample_a, large, blank], vec![sample_a, "-", large]] {
+        let mut args = vec!["--preallocate"];
+        args.extend(&operands);
+        // Only feed stdin when something reads it; otherwise the writer can hit EPIPE.
+        let input = operands.contains(&"-").then_some(stdin_data.as_slice());
+        let wcat_file = NamedTempFile::new_in(h.fixtures.dir.path())?;
+        let cat_file = NamedTempFile::new_in(h.fixtures.dir.path())?;
+        run_cmd_to_file(&h.wcat, &args, input, Some(&h.cat), &[], wcat_file.path())?;
+        run_cmd_to_file(&h.cat, &operands, input, None, &[], cat_file.path())?;
+        let wcat_bytes = fs::read(wcat_file.path())?;
+        let cat_bytes = fs::read(cat_file.path())?;
+        if wcat_bytes != cat_bytes {
+            bail!(
+                "--preallocate output differs for {:?} (wcat {}B vs cat {}B)",
+                operands,
+                wcat_bytes.len(),
+                cat_bytes.len()
+            );
+        }
+    }
+    Ok(())
+}
+
+fn pipeline_exit(cmd: &Path, data: &[u8]) -> Result<std::process::ExitStatus> {
+    let mut producer = Command::new(cmd)
+        .arg("-")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("spawn producer {cmd:?}"))?;
+
+    let mut consumer = Command::new("head")
+        .arg("-n0")
+        .stdin(producer.stdout.take().unwrap())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .context("spawn head")?;
+    let head_status = consumer.wait()?;
+    producer.stdin.take().unwrap().write_all(data)?;
+    let prod_status = producer.wait()?;
+    let _ = head_status;
+    Ok(prod_status)
+}
+
+fn test_dash_file_before_options(h: &Harness) -> Result<()> {
+    let args = ["--", h.fixtures.dash_name.to_str().unwrap(), "-n"];
+    h.compare_with_cat(&args, None)
+}
+
+fn test_literal_dash_filename(h: &Harness) -> Result<()> {
+    let dash_path = h.scratch().join("-");
+    fs::write(&dash_path, b"dash literal\n")?;
+    let args = ["--", dash_path.to_str().unwrap()];
+    h.compare_with_cat(&args, None)
+}
+
+fn test_visible_del(h: &Harness) -> Result<()> {
+    let path = h.scratch().join("del.txt");
+    fs::write(&path, b"del:\x7f!\n")?;
+    h.compare_with_cat(&["-v", path.to_str().unwrap()], None)
+}
+
+fn test_file_named_help(h: &Harness) -> Result<()> {
+    let path = h.scratch().join("--help");
+    fs::write(&path, b"help file\n")?;
+    h.compare_with_cat(&["--", path.to_str().unwrap()], None)
+}
+
+fn test_file_named_version(h: &Harness) -> Result<()> {
+    let path = h.scratch().join("--version");
+    fs::write(&path, b"version file\n")?;
+    h.compare_with_cat(&["--", path.to_str().unwrap()], None)
+}
+
+/// With files called `--number`, `--show-all`, `--bogus` and `-n` in the
+/// working directory, those words are still options (or bad options) unless
+/// `--` or a path prefix comes first. Both binaries run as `cat` there, with
+/// stdin from a file so that runs which never read it are fine.
+fn test_long_option_named_files(h: &Harness) -> Result<()> {
+    let dir = h.scratch().join("option_named_files");
+    fs::create_dir_all(&dir)?;
+    for name in ["--number", "--show-all", "--bogus", "-n"] {
+        fs::write(dir.join(name), format!("file {name}\n"))?;
+    }
+    let stdin_path = dir.join("stdin.txt");
+    fs::write(&stdin_path, b"from stdin\n\tx\n")?;
+    let run = |cmd: &Path, args: &[&str]| -> Result<std::process::Output> {
+        let mut command = Command::new(cmd);
+        command
+            .arg0("cat")
+            .args(args)
+            .current_dir(&dir)
+            .stdin(File::open(&stdin_path)?)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        clean_env(&mut command, &[]);
+        let child = command.spawn()?;
+        wait_with_deadline(child, cmd, args)
+    };
+    for args in [
+        vec!["--number"],
+        vec!["--show-all"],
+        vec!["--number", "--show-all"],
+        vec!["-n"],
+        vec!["--bogus"],
+        vec!["--number", "--bogus"],
+        vec!["./--number", "--show-all"],
+        vec!["--", "--number", "--show-all", "-n"],
+        vec!["--number", "--", "--bogus"],
+    ] {
+        let wcat_out = run(&h.wcat, &args)?;
+        let cat_out = run(&h.cat, &args)?;
+        if wcat_out.stdout != cat_out.stdout
+            || wcat_out.stderr != cat_out.stderr
+            || exit_of(&wcat_out.status) != exit_of(&cat_out.status)
+        {
+            bail!(
+                "{args:?} next to files of those names: stdout {:?} vs {:?}, stderr {:?} vs {:?}, status {} vs {}",
+                String::from_utf8_lossy(&wcat_out.stdout),
+                String::from_utf8_lossy(&cat_out.stdout),
+                String::from_utf8_lossy(&wcat_out.stderr),
+                String::from_utf8_lossy(&cat_out.stderr),
+                wcat_out.status,
+                cat_out.status
+            );
+        }
+    }
+    // Agreeing with cat is not enough if both read the file: pin the option.
+    let out = run(&h.wcat, &["--number"])?;
+    if out.stdout != b"     1\tfrom stdin\n     2\t\tx\n" {
+        bail!("--number was not taken as an option: {:?}", String::from_utf8_lossy(&out.stdout));
+    }
+    Ok(())
+}
+
+fn test_double_dash_then_dash(h: &Harness) -> Result<()> {
+    let args = ["--", "-"];
     h.compare_with_cat(&args, Some(&h.fixtures.stdin_data))
 }
 
@@ -2224,7 +4529,7 @@ fn test_option_between_dashes(h: &Harness) -> Result<()> {
 }
 
 fn test_space_in_filename(h: &Harness) -> Result<()> {
-    let path = h.fixtures.dir.path().join("space name.txt");
+    let path = h.scratch().join("space name.txt");
     fs::write(&path, b"space\n")?;
     h.compare_with_cat(&[path.to_str().unwrap()], None)
 }
@@ -2238,42 +4543,60 @@ fn test_multiple_stdin_operands_with_options(h: &Harness) -> Result<()> {
 }
 
 fn test_enotdir_path(h: &Harness) -> Result<()> {
-    let path = h.fixtures.dir.path().join("notdir");
+    let path = h.scratch().join("notdir");
     fs::write(&path, b"data")?;
     let child = path.join("child");
     h.compare_with_cat(&[child.to_str().unwrap()], None)
 }
 
 fn test_eloop_symlink(h: &Harness) -> Result<()> {
-    let a = h.fixtures.dir.path().join("loop_a");
-    let b = h.fixtures.dir.path().join("loop_b");
+    let a = h.scratch().join("loop_a");
+    let b = h.scratch().join("loop_b");
     symlink(&b, &a)?;
     symlink(&a, &b)?;
     h.compare_with_cat(&[a.to_str().unwrap()], None)
 }
 
+/// open() of a Unix socket fails with ENXIO. ETXTBSY and EBUSY need a write
+/// open or an exclusive device, which cat never does, so this is the busy-ish
+/// errno a reader can actually hit. Both run as `cat` so only the text counts.
+fn test_socket_operand(h: &Harness) -> Result<()> {
+    let path = h.scratch().join("operand.sock");
+    let _listener = match std::os::unix::net::UnixListener::bind(&path) {
+        Ok(listener) => listener,
+        Err(e) => {
+            println!("[SKIP] cannot bind a unix socket: {e}");
+            return Ok(());
+        }
+    };
+    let sock = path.to_str().unwrap();
+    let a = h.fixtures.sample_a.to_str().unwrap();
+    h.compare_merged_with_cat(&[sock])?;
+    h.compare_merged_with_cat(&["-n", a, sock, a])
+}
+
 fn test_symlink_to_file(h: &Harness) -> Result<()> {
-    let link = h.fixtures.dir.path().join("link_to_a.txt");
+    let link = h.scratch().join("link_to_a.txt");
     symlink(&h.fixtures.sample_a, &link)?;
     h.compare_with_cat(&[link.to_str().unwrap()], None)
 }
 
 fn test_symlink_to_dir(h: &Harness) -> Result<()> {
-    let link = h.fixtures.dir.path().join("link_to_dir");
+    let link = h.scratch().join("link_to_dir");
     symlink(&h.fixtures.dir_path, &link)?;
     h.compare_with_cat(&[link.to_str().unwrap()], None)
 }
 
 fn test_hardlink_to_file(h: &Harness) -> Result<()> {
-    let link = h.fixtures.dir.path().join("hardlink_b.txt");
+    let link = h.scratch().join("hardlink_b.txt");
     fs::hard_link(&h.fixtures.sample_b, &link)?;
     h.compare_with_cat(&[link.to_str().unwrap()], None)
 }
 
 fn test_symlink_chain(h: &Harness) -> Result<()> {
-    let target = h.fixtures.dir.path().join("chain_target.txt");
-    let link1 = h.fixtures.dir.path().join("chain_link1");
-    let link2 = h.fixtures.dir.path().join("chain_link2");
+    let target = h.scratch().join("chain_target.txt");
+    let link1 = h.scratch().join("chain_link1");
+    let link2 = h.scratch().join("chain_link2");
     fs::write(&target, b"chain target\n")?;
     symlink(&target, &link1)?;
     symlink(&link1, &link2)?;
@@ -2281,7 +4604,7 @@ fn test_symlink_chain(h: &Harness) -> Result<()> {
 }
 
 fn test_relative_symlink(h: &Harness) -> Result<()> {
-    let base = h.fixtures.dir.path();
+    let base = &h.scratch();
     let rel_dir = base.join("rel_dir");
     fs::create_dir(&rel_dir)?;
     let target = rel_dir.join("rel_target.txt");
@@ -2300,12 +4623,12 @@ fn test_directory_operand_visible(h: &Harness) -> Result<()> {
 }
 
 fn test_missing_file_numbered(h: &Harness) -> Result<()> {
-    let missing = h.fixtures.dir.path().join("missing_numbered.txt");
+    let missing = h.scratch().join("missing_numbered.txt");
     h.compare_with_cat(&["-n", missing.to_str().unwrap()], None)
 }
 
 fn test_missing_file_visible_among_files(h: &Harness) -> Result<()> {
-    let missing = h.fixtures.dir.path().join("missing_visible.txt");
+    let missing = h.scratch().join("missing_visible.txt");
     let args = [
         "-v",
         h.fixtures.sample_a.to_str().unwrap(),
@@ -2316,164 +4639,1993 @@ fn test_missing_file_visible_among_files(h: &Harness) -> Result<()> {
 }
 
 fn test_visible_cr(h: &Harness) -> Result<()> {
-    let path = h.fixtures.dir.path().join("cr.txt");
+    let path = h.scratch().join("cr.txt");
     fs::write(&path, b"carriage\rreturn\n")?;
     h.compare_with_cat(&["-v", path.to_str().unwrap()], None)
 }
 
 fn test_visible_nul(h: &Harness) -> Result<()> {
-    let path = h.fixtures.dir.path().join("nul.txt");
+    let path = h.scratch().join("nul.txt");
     fs::write(&path, b"nul:\0x\n")?;
     h.compare_with_cat(&["-v", path.to_str().unwrap()], None)
 }
 
 fn test_visible_ff(h: &Harness) -> Result<()> {
-    let path = h.fixtures.dir.path().join("ff.txt");
+    let path = h.scratch().join("ff.txt");
     fs::write(&path, b"ff:\xff!\n")?;
     h.compare_with_cat(&["-v", path.to_str().unwrap()], None)
 }
 
 fn test_tabs_no_newline_t(h: &Harness) -> Result<()> {
-    let path = h.fixtures.dir.path().join("tabs_no_nl.txt");
+    let path = h.scratch().join("tabs_no_nl.txt");
     fs::write(&path, b"a\tb")?;
     h.compare_with_cat(&["-T", path.to_str().unwrap()], None)
 }
 
+/// The tab after a line number is a separator, not input, so -T must leave
+/// it alone while turning a tab at column 0 of the body into ^I. The blank
+/// line matters too: numbering it on the -T path once rewound the buffer.
+fn test_number_then_show_tabs(h: &Harness) -> Result<()> {
+    let data: &[u8] = b"\tfirst\n\t\tsecond\n\n\t\n\tlast\tno newline";
+    let path = h.scratch().join("leading_tab.txt");
+    fs::write(&path, data)?;
+    let file = path.to_str().unwrap();
+    let out = run_cmd(&h.wcat, &["-n", "-T", file], None)?;
+    if !out.stdout.starts_with(b"     1\t^Ifirst\n     2\t^I^Isecond\n") {
+        bail!("-n -T head: {:?}", String::from_utf8_lossy(&out.stdout));
+    }
+    for args in [["-n", "-T"], ["-T", "-n"], ["-b", "-T"], ["-n", "-A"]] {
+        h.compare_with_cat(&[args[0], args[1], file], None)?;
+        h.compare_with_cat(&[args[0], args[1], "-"], Some(data))?;
+    }
+    Ok(())
+}
+
 fn test_tabs_no_newline_a(h: &Harness) -> Result<()> {
-    let path = h.fixtures.dir.path().join("tabs_no_nl_a.txt");
+    let path = h.scratch().join("tabs_no_nl_a.txt");
     fs::write(&path, b"a\tb")?;
     h.compare_with_cat(&["-A", path.to_str().unwrap()], None)
 }
 
-fn test_long_line_no_newline_number(h: &Harness) -> Result<()> {
-    let path = h.fixtures.dir.path().join("long_line.txt");
-    let data = vec![b'x'; 600_000];
-    fs::write(&path, &data)?;
-    h.compare_with_cat(&["-n", path.to_str().unwrap()], None)
+fn test_long_line_no_newline_number(h: &Harness) -> Result<()> {
+    let path = h.scratch().join("long_line.txt");
+    let data = vec![b'x'; 600_000];
+    fs::write(&path, &data)?;
+    h.compare_with_cat(&["-n", path.to_str().unwrap()], None)
+}
+
+/// One logical line several times the read buffer, so every flag's state
+/// (at line start, last byte seen) has to survive many chunk boundaries:
+/// -n must not renumber mid-line and -E must mark only the real line end.
+fn test_line_spanning_reads(h: &Harness) -> Result<()> {
+    let long: Vec<u8> =
+        b"ab\tc\x01\xe9 ".iter().copied().cycle().take(4 * WCAT_BUFFER_SIZE + 123).collect();
+    let path = h.scratch().join("spanning_line.txt");
+    let shapes = [
+        long.clone(),
+        [&long[..], b"\n"].concat(),
+        [b"short\n", &long[..], b"\nnext"].concat(),
+    ];
+    for data in shapes {
+        fs::write(&path, &data)?;
+        for flags in ["-n", "-E", "-v", "-nE", "-bA"] {
+            h.compare_with_cat(&[flags, path.to_str().unwrap()], None)?;
+            h.compare_with_cat(&[flags, "-"], Some(&data))?;
+        }
+    }
+    Ok(())
+}
+
+/// /proc and sysfs files stat as 0 bytes yet read() returns data, so any
+/// path that trusts st_size would print nothing. /proc/self/status names and
+/// sizes the reading process, so only its field names can be compared.
+fn test_proc_zero_size(h: &Harness) -> Result<()> {
+    for path in [
+        "/proc/version",
+        "/proc/filesystems",
+        "/proc/sys/kernel/ostype",
+        "/sys/kernel/mm/transparent_hugepage/enabled",
+    ] {
+        if !Path::new(path).exists() {
+            continue;
+        }
+        for flags in [&[][..], &["-n"], &["-A"]] {
+            h.compare_with_cat(&[flags, &[path]].concat(), None)?;
+        }
+    }
+    let fields = |out: &[u8]| -> Vec<String> {
+        String::from_utf8_lossy(out)
+            .lines()
+            .map(|line| line.split(':').next().unwrap_or("").to_string())
+            .collect()
+    };
+    let wcat = run_cmd(&h.wcat, &["/proc/self/status"], None)?;
+    let cat = run_cmd(&h.cat, &["/proc/self/status"], None)?;
+    if wcat.stdout.is_empty() || fields(&wcat.stdout) != fields(&cat.stdout) {
+        bail!(
+            "/proc/self/status fields differ\n=== wcat ===\n{}\n=== cat ===\n{}",
+            String::from_utf8_lossy(&wcat.stdout),
+            String::from_utf8_lossy(&cat.stdout)
+        );
+    }
+    Ok(())
+}
+
+/// An output cap must end an endless input: `--max-bytes=1024 /dev/zero`
+/// writes exactly 1024 NULs and exits 0. Skipped while wcat has no
+/// --max-bytes. A wcat that ignores the cap is cut off by closing its pipe
+/// after 1 MiB, and the 10 s guard catches one that stalls instead.
+fn test_max_bytes_dev_zero(h: &Harness) -> Result<()> {
+    if !run_cmd(&h.wcat, &["--max-bytes=1", "/dev/null"], None)?.status.success() {
+        println!("[SKIP] wcat does not support --max-bytes");
+        return Ok(());
+    }
+    let mut child = Command::new(&h.wcat)
+        .args(["--max-bytes=1024", "/dev/zero"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .spawn()?;
+    let stdout = child.stdout.take().unwrap();
+    let reader = std::thread::spawn(move || -> std::io::Result<Vec<u8>> {
+        let mut out = Vec::new();
+        stdout.take(1 << 20).read_to_end(&mut out)?;
+        Ok(out)
+    });
+    let deadline = Instant::now() + std::time::Duration::from_secs(10);
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() > deadline {
+            child.kill()?;
+            child.wait()?;
+            bail!("--max-bytes=1024 /dev/zero still running after 10 s");
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    };
+    let out = reader.join().unwrap()?;
+    if out.len() != 1024 || out.iter().any(|&b| b != 0) || exit_of(&status) != (Some(0), None) {
+        bail!(
+            "--max-bytes=1024 /dev/zero wrote {}B, (code, signal) {:?}",
+            out.len(),
+            exit_of(&status)
+        );
+    }
+    Ok(())
+}
+
+/// Character devices have no usable size: /dev/null stats as 0 bytes and ends
+/// at once, /dev/zero never ends. Both must take the read() loop rather than
+/// sendfile or mmap, which key off st_size. The first 256 KiB of /dev/zero
+/// under each path (plain, -v, -n) must match cat's, and so must /dev/null
+/// among regular files. Bounded --max-bytes reads are covered above.
+fn test_char_device_operand(h: &Harness) -> Result<()> {
+    let a = h.fixtures.sample_a.to_str().unwrap();
+    for flags in [&[][..], &["-n"], &["-A"], &["-bs"]] {
+        h.compare_with_cat(&[flags, &["/dev/null"]].concat(), None)?;
+        h.compare_with_cat(&[flags, &[a, "/dev/null", a]].concat(), None)?;
+    }
+    if !Path::new("/dev/zero").exists() {
+        println!("[SKIP] no /dev/zero");
+        return Ok(());
+    }
+    for flags in [&[][..], &["-v"], &["-n"], &["-ET"]] {
+        let args = [flags, &["/dev/zero"]].concat();
+        let wcat = endless_head(&h.wcat, &args, WCAT_BUFFER_SIZE)?;
+        let cat = endless_head(&h.cat, &args, WCAT_BUFFER_SIZE)?;
+        if wcat != cat {
+            bail!("{args:?}\n{}", describe_diff("stdout", ("wcat", &wcat), ("cat", &cat)));
+        }
+    }
+    Ok(())
+}
+
+/// Read the first `len` bytes `cmd` writes for an input that never ends, then
+/// kill it. Fails if it stops short or stalls past the --timeout deadline.
+fn endless_head(cmd: &Path, args: &[&str], len: usize) -> Result<Vec<u8>> {
+    let mut command = Command::new(cmd);
+    clean_env(&mut command, &[]);
+    let mut child = command
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .with_context(|| format!("spawning {cmd:?}"))?;
+    let stdout = child.stdout.take().unwrap();
+    let pid = Pid::from_raw(child.id() as i32);
+    let (done_tx, done_rx) = std::sync::mpsc::channel::<()>();
+    let watcher = std::thread::spawn(move || {
+        if done_rx.recv_timeout(child_timeout()).is_err() {
+            let _ = kill(pid, Signal::SIGKILL);
+            return true;
+        }
+        false
+    });
+    let mut out = Vec::with_capacity(len);
+    let read = stdout.take(len as u64).read_to_end(&mut out);
+    let _ = done_tx.send(());
+    let _ = child.kill();
+    child.wait()?;
+    if watcher.join().unwrap() {
+        bail!("timeout: {cmd:?} {args:?} wrote {}B of {len}B", out.len());
+    }
+    read?;
+    if out.len() != len {
+        bail!("{cmd:?} {args:?} stopped after {}B of an endless input", out.len());
+    }
+    Ok(out)
+}
+
+/// -s and -b share one notion of blank: a line with no bytes before its
+/// newline. Space- and tab-only lines are text, so they end a blank run and
+/// are never squeezed, even when runs of empty lines surround them.
+fn test_whitespace_squeeze(h: &Harness) -> Result<()> {
+    let file = h.fixtures.whitespace.to_str().unwrap();
+    let interleaved = b"\n\n \n\n\n\t\n\n \n \n\n\n\n";
+    for flags in ["-s", "-sE", "-sn", "-bs", "-sA"] {
+        h.compare_with_cat(&[flags, file], None)?;
+        h.compare_with_cat(&[flags, "-"], Some(interleaved))?;
+    }
+    Ok(())
+}
+
+fn test_only_newlines_file_s(h: &Harness) -> Result<()> {
+    let path = h.scratch().join("only_newlines.txt");
+    fs::write(&path, b"\n\n\n\n")?;
+    h.compare_with_cat(&["-s", path.to_str().unwrap()], None)
+}
+
+fn test_stdin_only_newlines_s(h: &Harness) -> Result<()> {
+    h.compare_with_cat(&["-s", "-"], Some(b"\n\n\n\n"))
+}
+
+fn test_stdin_empty_numbered(h: &Harness) -> Result<()> {
+    h.compare_with_cat(&["-n", "-"], Some(&[]))
+}
+
+/// No input means no output under every decoration: no number, $ or other
+/// prefix may be written before the first byte arrives. Both an implicit
+/// and an explicit `-` stdin.
+fn test_stdin_empty_decorated(h: &Harness) -> Result<()> {
+    for flags in [
+        &["-E"][..],
+        &["-A"],
+        &["-s"],
+        &["-b"],
+        &["-v"],
+        &["-T"],
+        &["-e"],
+        &["-t"],
+        &["-nE"],
+        &["-bs"],
+        &["-nsvET"],
+    ] {
+        for operands in [&[][..], &["-"]] {
+            let args = [flags, operands].concat();
+            h.expect_wcat(&args, Some(b""), b"")?;
+            h.compare_with_cat(&args, Some(b""))?;
+        }
+    }
+    Ok(())
+}
+
+fn test_binary_show_tabs(h: &Harness) -> Result<()> {
+    h.compare_with_cat(&["-T", h.fixtures.binary.to_str().unwrap()], None)
+}
+
+fn test_binary_show_ends(h: &Harness) -> Result<()> {
+    h.compare_with_cat(&["-E", h.fixtures.binary.to_str().unwrap()], None)
+}
+
+fn test_fifo_squeeze_blank(h: &Harness) -> Result<()> {
+    let fifo = h.scratch().join("squeeze.fifo");
+    mkfifo(&fifo, nix::sys::stat::Mode::from_bits_truncate(0o644))?;
+    let data = b"one\n\n\n\nthree\n";
+    let fifo_path = fifo.to_str().unwrap();
+    compare_fifo_outputs(h, &fifo, &["-s", fifo_path], data, "fifo squeeze")
+}
+
+fn test_fifo_show_ends(h: &Harness) -> Result<()> {
+    let fifo = h.scratch().join("show_ends.fifo");
+    mkfifo(&fifo, nix::sys::stat::Mode::from_bits_truncate(0o644))?;
+    let data = b"one\n\n";
+    let fifo_path = fifo.to_str().unwrap();
+    compare_fifo_outputs(h, &fifo, &["-E", fifo_path], data, "fifo show ends")
+}
+
+fn test_fifo_number_show_ends(h: &Harness) -> Result<()> {
+    let fifo = h.scratch().join("num_ends.fifo");
+    mkfifo(&fifo, nix::sys::stat::Mode::from_bits_truncate(0o644))?;
+    let data = b"one\n\n";
+    let fifo_path = fifo.to_str().unwrap();
+    compare_fifo_outputs(h, &fifo, &["-nE", fifo_path], data, "fifo numbered ends")
+}
+
+fn test_fifo_show_all(h: &Harness) -> Result<()> {
+    let fifo = h.scratch().join("show_all.fifo");
+    mkfifo(&fifo, nix::sys::stat::Mode::from_bits_truncate(0o644))?;
+    let data = b"tab\t\x01\n";
+    let fifo_path = fifo.to_str().unwrap();
+    compare_fifo_outputs(h, &fifo, &["-A", fifo_path], data, "fifo show all")
+}
+
+fn test_fifo_show_tabs_ends(h: &Harness) -> Result<()> {
+    let fifo = h.scratch().join("show_tabs_ends.fifo");
+    mkfifo(&fifo, nix::sys::stat::Mode::from_bits_truncate(0o644))?;
+    let data = b"one\tend\n\n";
+    let fifo_path = fifo.to_str().unwrap();
+    compare_fifo_outputs(
+        h,
+        &fifo,
+        &["-ET", fifo_path],
+        data,
+        "fifo show tabs and ends",
+    )
+}
+
+fn test_fifo_number_nonblank(h: &Harness) -> Result<()> {
+    let fifo = h.scratch().join("num_nonblank.fifo");
+    mkfifo(&fifo, nix::sys::stat::Mode::from_bits_truncate(0o644))?;
+    let data = b"one\n\nthree\n";
+    let fifo_path = fifo.to_str().unwrap();
+    compare_fifo_outputs(h, &fifo, &["-b", fifo_path], data, "fifo number nonblank")
+}
+
+fn test_squeeze_limit_one(h: &Harness) -> Result<()> {
+    let inputs: [&[u8]; 3] = [b"one\n\n\nthree\n\n\n", b"\n\n\n", b"a\n\n\tb\n\n\n\nc"];
+    for input in inputs {
+        for extra in [&[][..], &["-n"][..], &["-E"][..], &["-A"][..]] {
+            let mut limit_args = extra.to_vec();
+            limit_args.extend(["--squeeze-limit=1", "-"]);
+            let mut cat_args = extra.to_vec();
+            cat_args.extend(["-s", "-"]);
+            let wcat_out = run_cmd(&h.wcat, &limit_args, Some(input))?;
+            let cat_out = run_cmd(&h.cat, &cat_args, Some(input))?;
+            compare_outputs(wcat_out, cat_out, &format!("squeeze-limit=1 {extra:?}"))?;
+        }
+    }
+    Ok(())
+}
+
+fn test_squeeze_limit_across_files(h: &Harness) -> Result<()> {
+    let a = h.scratch().join("limit_a.txt");
+    let b = h.scratch().join("limit_b.txt");
+    fs::write(&a, b"line1\n\n\n")?;
+    fs::write(&b, b"\n\nline2")?;
+    let (a, b) = (a.to_str().unwrap(), b.to_str().unwrap());
+    h.expect_wcat(&["--squeeze-limit=2", a, b], None, b"line1\n\n\nline2")?;
+    h.expect_wcat(&["--squeeze-limit=3", a, b], None, b"line1\n\n\n\nline2")
+}
+
+fn test_stats_stdout_unchanged(h: &Harness) -> Result<()> {
+    let files = [&h.fixtures.large, &h.fixtures.binary, &h.fixtures.no_newline];
+    for file in files {
+        let path = file.to_str().unwrap();
+        for extra in [&[][..], &["-n"][..], &["-v"][..], &["-A"][..]] {
+            let mut cat_args = extra.to_vec();
+            cat_args.push(path);
+            let mut args = extra.to_vec();
+            args.extend(["--stats", path]);
+            let wcat_out = run_cmd(&h.wcat, &args, None)?;
+            let cat_out = run_cmd(&h.cat, &cat_args, None)?;
+            if wcat_out.stdout != cat_out.stdout || !wcat_out.status.success() {
+                bail!("--stats changed stdout for {args:?}");
+            }
+            let stderr = String::from_utf8_lossy(&wcat_out.stderr);
+            let expected = format!(": {} bytes, ", cat_out.stdout.len());
+            if !stderr.contains(&expected) || !stderr.ends_with(" bytes\n") {
+                bail!("unexpected --stats summary for {args:?}: {stderr:?}");
+            }
+        }
+    }
+    Ok(())
+}
+
+fn test_stats_summary(h: &Harness) -> Result<()> {
+    let cases: [(&[&str], &[u8], &str); 4] = [
+        (&["--stats"], b"ab\ncdefg\nxyz", ": 12 bytes, 3 lines, longest line 5 bytes\n"),
+        (&["--stats"], b"", ": 0 bytes, 0 lines, longest line 0 bytes\n"),
+        (&["--stats", "-E"], b"a\n\nbcd\n", ": 10 bytes, 3 lines, longest line 4 bytes\n"),
+        (&["-n", "--stats"], b"x\n", ": 9 bytes, 1 lines, longest line 8 bytes\n"),
+    ];
+    for (args, input, tail) in cases {
+        let mut full = args.to_vec();
+        full.push("-");
+        let out = run_cmd(&h.wcat, &full, Some(input))?;
+        let stderr = String::from_utf8_lossy(&out.stderr);
+        let expected = format!("{}{tail}", h.wcat.display());
+        if stderr != expected || !out.status.success() {
+            bail!("--stats summary for {full:?}: expected {expected:?}, got {stderr:?}");
+        }
+    }
+    Ok(())
+}
+
+fn test_files_from_golden(h: &Harness) -> Result<()> {
+    // Each --files-from run must match cat given the same names as operands.
+    let dir = &h.scratch();
+    let a = h.fixtures.sample_a.to_str().unwrap();
+    let b = h.fixtures.sample_b.to_str().unwrap();
+    let no_nl = h.fixtures.no_newline.to_str().unwrap();
+    let blank = h.fixtures.blank.to_str().unwrap();
+    let missing = dir.join("files_from_missing");
+    let missing = missing.to_str().unwrap();
+    let list = dir.join("files_from.list");
+    let list_arg = format!("--files-from={}", list.display());
+    // Empty lines are skipped and the last name has no trailing newline.
+    fs::write(&list, format!("{no_nl}\n\n{a}\n{blank}\n\n{b}"))?;
+    let stdin_list = format!("{blank}\n{a}\n");
+    let stdin_list_missing = format!("{a}\n{missing}\n{b}\n");
+    let cases = [
+        (vec![list_arg.as_str()], vec![no_nl, a, blank, b], None),
+        (vec!["-n", b, &list_arg, a], vec!["-n", b, no_nl, a, blank, b, a], None),
+        (vec!["-sE", "--files-from=-", b], vec!["-sE", blank, a, b], Some(stdin_list.as_bytes())),
+        (vec!["--files-from=-"], vec![a, missing, b], Some(stdin_list_missing.as_bytes())),
+    ];
+    for (wcat_args, cat_args, list_on_stdin) in cases {
+        let out = run_cmd_with_arg0(&h.wcat, &wcat_args, list_on_stdin, Some(&h.cat), &[])?;
+        let expected = run_cmd(&h.cat, &cat_args, None)?;
+        // Compare diagnostics after the program-name prefix: that prefix
+        // belongs to the shared open-error path, not to this option.
+        let tail = |stderr: &[u8]| {
+            String::from_utf8_lossy(stderr)
+                .lines()
+                .map(|l| l.split_once(": ").map_or(l, |(_, rest)| rest).to_string())
+                .collect::<Vec<_>>()
+        };
+        if out.stdout != expected.stdout
+            || exit_of(&out.status) != exit_of(&expected.status)
+            || tail(&out.stderr) != tail(&expected.stderr)
+        {
+            bail!(
+                "--files-from mismatch: wcat {:?} vs cat {:?}\nwcat stderr: {}cat stderr: {}",
+                wcat_args,
+                cat_args,
+                String::from_utf8_lossy(&out.stderr),
+                String::from_utf8_lossy(&expected.stderr)
+            );
+        }
+    }
+    Ok(())
+}
+
+fn test_binary_safe_tty(h: &Harness) -> Result<()> {
+    let text = h.fixtures.sample_a.to_str().unwrap();
+    // binary.bin is random and may lack a NUL; this one always has one.
+    let binary_path = h.scratch().join("binary_safe.bin");
+    fs::write(&binary_path, b"ELF\x00\x01\x02\n")?;
+    let binary = binary_path.to_str().unwrap();
+    // The tty turns "\n" into "\r\n" on the way to the master side.
+    let text_on_tty = String::from_utf8(fs::read(text)?)?.replace('\n', "\r\n").into_bytes();
+
+    let passed = run_cmd_on_pty(&h.wcat, &["--binary-safe", text], &[])?;
+    if passed.stdout != text_on_tty || !passed.status.success() {
+        bail!("--binary-safe blocked a text file: {:?}", String::from_utf8_lossy(&passed.stderr));
+    }
+    // The text operand is written, then the binary one stops the run.
+    let refused = run_cmd_on_pty(&h.wcat, &["--binary-safe", text, binary, text], &[])?;
+    let stderr = String::from_utf8_lossy(&refused.stderr);
+    if refused.status.code() != Some(1)
+        || refused.stdout != text_on_tty
+        || !stderr.contains(&format!("{binary}: binary file not written to a terminal"))
+    {
+        bail!(
+            "--binary-safe on a tty: status {:?}, {}B on the tty, stderr {stderr:?}",
+            refused.status.code(),
+            refused.stdout.len()
+        );
+    }
+    Ok(())
+}
+
+fn test_quiet_errors(h: &Harness) -> Result<()> {
+    let a = h.fixtures.sample_a.to_str().unwrap();
+    let missing = h.scratch().join("quiet_missing.txt");
+    let args = ["--quiet-errors", missing.to_str().unwrap(), a, h.fixtures.dir_path.to_str().unwrap()];
+    let out = run_cmd(&h.wcat, &args, None)?;
+    let expected = fs::read(&h.fixtures.sample_a)?;
+    if out.stdout != expected || !out.stderr.is_empty() || out.status.code() != Some(1) {
+        bail!(
+            "--quiet-errors: status {:?}, stdout {}B (want {}B), stderr {:?}",
+            out.status.code(),
+            out.stdout.len(),
+            expected.len(),
+            String::from_utf8_lossy(&out.stderr)
+        );
+    }
+    Ok(())
+}
+
+fn test_ignore_missing(h: &Harness) -> Result<()> {
+    let a = h.fixtures.sample_a.to_str().unwrap();
+    let b = h.fixtures.sample_b.to_str().unwrap();
+    let missing = h.scratch().join("ignored_missing.txt");
+    let mut expected = fs::read(a)?;
+    expected.extend(fs::read(b)?);
+    h.expect_wcat(&["--ignore-missing", a, missing.to_str().unwrap(), b], None, &expected)?;
+
+    // Anything other than ENOENT still counts.
+    let blocker = h.scratch().join("ignore_blocker");
+    fs::write(&blocker, b"file, not a directory")?;
+    let below = blocker.join("child");
+    h.expect_wcat_error(&["--ignore-missing", a, below.to_str().unwrap()], None, "Not a directory")?;
+    let locked = h.scratch().join("ignore_locked.txt");
+    fs::write(&locked, b"locked")?;
+    fs::set_permissions(&locked, fs::Permissions::from_mode(0o000))?;
+    // Root opens it anyway, in which case there is no EACCES to check.
+    let result = match File::open(&locked) {
+        Ok(_) => Ok(()),
+        Err(_) => h.expect_wcat_error(
+            &["--ignore-missing", locked.to_str().unwrap()],
+            None,
+            "Permission denied",
+        ),
+    };
+    fs::set_permissions(&locked, fs::Permissions::from_mode(0o600))?;
+    result
+}
+
+fn test_reverse_like_tac(h: &Harness) -> Result<()> {
+    let a = h.fixtures.sample_a.to_str().unwrap();
+    let no_newline = h.fixtures.no_newline.to_str().unwrap();
+    h.compare_with_tac(&[a, no_newline], None)?;
+    h.compare_with_tac(&["-n"], Some(&h.fixtures.stdin_data))
+}
+
+fn test_dedupe_like_uniq(h: &Harness) -> Result<()> {
+    // Repeats spanning a file boundary, one line longer than a read() chunk,
+    // and unterminated last lines both equal to and unlike the line before.
+    let long = "x".repeat(WCAT_BUFFER_SIZE + 100);
+    let first = h.scratch().join("dedupe_1.txt");
+    let second = h.scratch().join("dedupe_2.txt");
+    fs::write(&first, format!("a\na\n\n\nb\nb\n{long}\n{long}"))?;
+    fs::write(&second, format!("\n{long}x\nc\nc"))?;
+    let (first, second) = (first.to_str().unwrap(), second.to_str().unwrap());
+    for flags in [&[][..], &["-n"], &["-s"], &["-E", "-n"]] {
+        let mut args = flags.to_vec();
+        args.extend([first, second]);
+        h.compare_with_uniq(&args, None)?;
+    }
+    for input in [&b"a\na"[..], b"a\nb", b"same\nsame\n", b""] {
+        h.compare_with_uniq(&["-"], Some(input))?;
+    }
+    h.compare_with_uniq(&[h.fixtures.blank.to_str().unwrap()], None)
+}
+
+/// --normalize drops the whitespace ending each line, so whitespace-only
+/// lines become blank, then squeezes blank runs like -s. A run is only
+/// dropped once its line ends: one that spans reads or operands and then
+/// meets more text is kept whole.
+fn test_normalize(h: &Harness) -> Result<()> {
+    let input = b"a  \n \t\n\n  b\t x \r\n\n\x0b\x0c\nend \t";
+    let cases: [(&[&str], &[u8]); 5] = [
+        (&[], b"a\n\n  b\t x\n\nend"),
+        (&["-n"], b"     1\ta\n     2\t\n     3\t  b\t x\n     4\t\n     5\tend"),
+        (&["-E"], b"a$\n$\n  b\t x$\n$\nend"),
+        (&["-A"], b"a$\n$\n  b^I x$\n$\nend"),
+        (&["-b", "--dedupe"], b"     1\ta\n\n     2\t  b\t x\n\n     3\tend"),
+    ];
+    let path = h.scratch().join("normalize.txt");
+    fs::write(&path, input)?;
+    let path = path.to_str().unwrap();
+    for (flags, expected) in cases {
+        h.expect_wcat(&[&["--normalize"], flags, &[path]].concat(), None, expected)?;
+        h.expect_wcat(&[&["--normalize"], flags].concat(), Some(input), expected)?;
+    }
+    let head = h.scratch().join("normalize_head.txt");
+    fs::write(&head, b"x  ")?;
+    let head = head.to_str().unwrap();
+    h.expect_wcat(&["--normalize", head, "-"], Some(b"\t y\n"), b"x  \t y\n")?;
+    h.expect_wcat(&["--normalize", head, "-"], Some(b" \n"), b"x\n")?;
+    h.expect_wcat(&["--normalize", "--dedupe"], Some(b"a \na\n\t\na"), b"a\n\na")?;
+    let spaces = " ".repeat(WCAT_BUFFER_SIZE + 100);
+    let kept = format!("a{spaces}z\n");
+    h.expect_wcat(&["--normalize"], Some(format!("a{spaces}\nb").as_bytes()), b"a\nb")?;
+    h.expect_wcat(&["--normalize"], Some(kept.as_bytes()), kept.as_bytes())?;
+    h.expect_wcat_error(&["--normalize=yes"], None, "doesn't allow an argument")
+}
+
+/// `--reverse` alone and under -n/-E against tac on one fixture.
+fn reverse_fixture_with_tac(h: &Harness, path: &Path) -> Result<()> {
+    let path = path.to_str().unwrap();
+    for flags in [&[][..], &["-n"], &["-E"], &["-n", "-E"]] {
+        let mut args = flags.to_vec();
+        args.push(path);
+        h.compare_with_tac(&args, None)?;
+    }
+    Ok(())
+}
+
+fn test_color_always(h: &Harness) -> Result<()> {
+    // Only the number is colored: padding inside, tab and text outside.
+    let input = b"first\n\nthird\n";
+    let expected = b"\x1b[32m     1\x1b[0m\tfirst\n\x1b[32m     2\x1b[0m\t\n\x1b[32m     3\x1b[0m\tthird\n";
+    h.expect_wcat(&["--color=always", "-n"], Some(input), expected)?;
+    let expected = b"\x1b[32m     1\x1b[0m\tfirst$\n$\n\x1b[32m     2\x1b[0m\tthird$\n";
+    h.expect_wcat(&["--color=always", "-bE"], Some(input), expected)?;
+    // Without numbering there is nothing to color.
+    h.expect_wcat(&["--color=always"], Some(input), input)
+}
+
+/// With POSIXLY_CORRECT set (even to ""), cat stops parsing options at the
+/// first operand, "-" included, so later "-n" or "--" are file names.
+fn test_posixly_correct(h: &Harness) -> Result<()> {
+    let a = h.fixtures.sample_a.to_str().unwrap();
+    let b = h.fixtures.sample_b.to_str().unwrap();
+    let arg0 = Some(Path::new("cat"));
+    let run = |cmd: &Path, value: Option<&str>, args: &[&str]| {
+        let envs: Vec<_> = value.map(|v| ("POSIXLY_CORRECT", v)).into_iter().collect();
+        run_cmd_with_arg0(cmd, args, Some(b""), arg0, &envs)
+    };
+    for args in [
+        &[a, "-n"][..],
+        &["-n", a, "-E", b],
+        &["-n", a, "--", b],
+        &["-", "-n", a],
+        &["-E", "--", "-n", a],
+        &["-s", "-n", a, "--number"],
+    ] {
+        for value in [None, Some("1"), Some("")] {
+            let wcat = run(&h.wcat, value, args)?;
+            let cat = run(&h.cat, value, args)?;
+            if wcat.stdout != cat.stdout
+                || wcat.stderr != cat.stderr
+                || exit_of(&wcat.status) != exit_of(&cat.status)
+            {
+                bail!(
+                    "POSIXLY_CORRECT={value:?} {args:?}\n{}\n{}",
+                    describe_diff("stdout", ("wcat", &wcat.stdout), ("cat", &cat.stdout)),
+                    describe_diff("stderr", ("wcat", &wcat.stderr), ("cat", &cat.stderr))
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// --color=always wraps each file diagnostic in red; auto (stderr is a pipe
+/// here), lines and never leave stderr byte-identical to cat's.
+fn test_color_diagnostics(h: &Harness) -> Result<()> {
+    let a = h.fixtures.sample_a.to_str().unwrap();
+    let missing = h.scratch().join("color_missing.txt");
+    let missing = missing.to_str().unwrap();
+    let dir = h.fixtures.dir_path.to_str().unwrap();
+    let arg0 = Some(Path::new("cat"));
+    let cat = run_cmd_with_arg0(&h.cat, &[a, missing, dir], None, arg0, &[])?;
+    let run =
+        |color: &str| run_cmd_with_arg0(&h.wcat, &[color, a, missing, dir], None, arg0, &[]);
+    let mut red = Vec::new();
+    for line in cat.stderr.split_inclusive(|&b| b == b'\n') {
+        red.extend_from_slice(b"\x1b[31m");
+        red.extend_from_slice(&line[..line.len() - 1]);
+        red.extend_from_slice(b"\x1b[0m\n");
+    }
+    for (color, expected) in [
+        ("--color=always", &red),
+        ("--color", &cat.stderr),
+        ("--color=auto", &cat.stderr),
+        ("--color=lines", &cat.stderr),
+        ("--color=never", &cat.stderr),
+    ] {
+        let out = run(color)?;
+        if out.stderr != *expected || out.stdout != cat.stdout || out.status.code() != Some(1) {
+            bail!(
+                "{color}: stderr {:?}, expected {:?}",
+                String::from_utf8_lossy(&out.stderr),
+                String::from_utf8_lossy(expected)
+            );
+        }
+    }
+    Ok(())
+}
+
+fn test_nonprinting_hex(h: &Harness) -> Result<()> {
+    let control = h.fixtures.control.to_str().unwrap();
+    h.expect_wcat(
+        &["--nonprinting-style=hex", "-v", control],
+        None,
+        b"plain\ncontrol:\\x01here\nesc:\\x1bX\nmeta:\\x80Y\n",
+    )?;
+    // -v leaves tab and newline alone; -T's ^I is not -v notation.
+    let binary = h.fixtures.binary.to_str().unwrap();
+    let expected: Vec<u8> = fs::read(binary)?
+        .into_iter()
+        .flat_map(|b| match b {
+            b'\t' | b'\n' | 0x20..=0x7e => vec![b],
+            _ => format!("\\x{b:02x}").into_bytes(),
+        })
+        .collect();
+    h.expect_wcat(&["--nonprinting-style=hex", "-v", binary], None, &expected)?;
+    // The default style, spelled out, stays exactly cat -v.
+    for path in [control, binary] {
+        let expected = run_cmd(&h.cat, &["-A", path], None)?.stdout;
+        h.expect_wcat(&["--nonprinting-style=caret", "-A", path], None, &expected)?;
+    }
+    Ok(())
+}
+
+/// `WCAT_OPTIONS=ENV wcat ARGS` must behave like `cat ENV ARGS`, with argv
+/// winning where a later option overrides an earlier one.
+fn test_wcat_options_env(h: &Harness) -> Result<()> {
+    let blank = h.fixtures.blank.to_str().unwrap();
+    let run_env = |env: &str, args: &[&str]| {
+        run_cmd_with_arg0(&h.wcat, args, Some(b""), Some(&h.cat), &[("WCAT_OPTIONS", env)])
+    };
+    for (env, args, cat_args) in [
+        ("-n", vec![blank], vec!["-n", blank]),
+        ("  -s\t--show-ends ", vec!["-n", blank], vec!["-s", "-E", "-n", blank]),
+        ("-n", vec!["-b", blank], vec!["-n", "-b", blank]),
+        ("--color=always -n", vec!["--color=never", blank], vec!["-n", blank]),
+        ("", vec![blank], vec![blank]),
+        ("-x", vec![blank], vec!["-x", blank]),
+        ("--bogus", vec![blank], vec!["--bogus", blank]),
+    ] {
+        let out = run_env(env, &args)?;
+        let expected = run_cmd(&h.cat, &cat_args, None)?;
+        if out.stdout != expected.stdout
+            || out.stderr != expected.stderr
+            || exit_of(&out.status) != exit_of(&expected.status)
+        {
+            bail!(
+                "WCAT_OPTIONS={env:?} {args:?} differs from cat {cat_args:?}: stdout {:?} vs {:?}, stderr {:?} vs {:?}",
+                String::from_utf8_lossy(&out.stdout),
+                String::from_utf8_lossy(&expected.stdout),
+                String::from_utf8_lossy(&out.stderr),
+                String::from_utf8_lossy(&expected.stderr)
+            );
+        }
+    }
+    // Operands do not belong in the variable.
+    let out = run_env("-n stray.txt", &[blank])?;
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    if out.status.code() != Some(1)
+        || !stderr.contains("WCAT_OPTIONS may only hold options, not 'stray.txt'")
+    {
+        bail!("operand in WCAT_OPTIONS: status {:?} stderr {stderr:?}", out.status.code());
+    }
+    // --files-from is positional, and pass 2 only walks argv: it must not
+    // quietly fall back to stdin, in either spelling.
+    let list = h.scratch().join("env_files_from.txt");
+    fs::write(&list, format!("{blank}\n"))?;
+    let list = list.to_str().unwrap();
+    for env in [format!("--files-from={list}"), format!("-n --files-from {list}")] {
+        let out = run_env(&env, &[])?;
+        let stderr = String::from_utf8_lossy(&out.stderr);
+        if out.status.code() != Some(1)
+            || !out.stdout.is_empty()
+            || !stderr.contains("WCAT_OPTIONS may not hold '--files-from'")
+        {
+            bail!("WCAT_OPTIONS={env:?}: status {:?} stderr {stderr:?}", out.status.code());
+        }
+    }
+    Ok(())
+}
+
+/// --dry-run writes one stderr line per operand, either "ok" or the error a
+/// real run would report, copies nothing, and exits 1 if anything would fail.
+fn test_dry_run(h: &Harness) -> Result<()> {
+    let a = h.fixtures.sample_a.to_str().unwrap();
+    let b = h.fixtures.sample_b.to_str().unwrap();
+    let dir = h.fixtures.dir_path.to_str().unwrap();
+    let missing = h.scratch().join("missing_dry_run.txt");
+    let missing = missing.to_str().unwrap();
+    // stdin is a regular file so that its shared offset shows whether it was read.
+    let stdin_path = h.scratch().join("dry_run_stdin.txt");
+    fs::write(&stdin_path, b"never read\n")?;
+    let dry_run = |args: &[&str]| -> Result<std::process::Output> {
+        let stdin = File::open(&stdin_path)?;
+        let child = Command::new(&h.wcat)
+            .arg0("cat")
+            .args(args)
+            .stdin(stdin.try_clone()?)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let out = wait_with_deadline(child, &h.wcat, args)?;
+        let offset = (&stdin).stream_position()?;
+        if offset != 0 {
+            bail!("dry-run {args:?} read {offset} bytes of stdin");
+        }
+        Ok(out)
+    };
+    let out = dry_run(&["--dry-run", "-n", a, missing, dir, "-", b])?;
+    let expected = format!(
+        "cat: {a}: ok\ncat: {missing}: No such file or directory\ncat: {dir}: Is a directory\ncat: -: ok\ncat: {b}: ok\n"
+    );
+    if !out.stdout.is_empty()
+        || String::from_utf8_lossy(&out.stderr) != expected
+        || out.status.code() != Some(1)
+    {
+        bail!(
+            "dry-run mismatch: status {:?}, stdout {}B, stderr {:?}",
+            out.status.code(),
+            out.stdout.len(),
+            String::from_utf8_lossy(&out.stderr)
+        );
+    }
+    // The failure lines are exactly what the real run prints.
+    let real = run_cmd(&h.cat, &[a, missing, dir, b], None)?;
+    let real = String::from_utf8_lossy(&real.stderr).replace(&*h.cat.to_string_lossy(), "cat");
+    let failures: String = expected
+        .lines()
+        .filter(|l| !l.ends_with(": ok"))
+        .map(|l| format!("{l}\n"))
+        .collect();
+    if real != failures {
+        bail!("dry-run errors {failures:?} differ from a real run's {real:?}");
+    }
+    // All good: status 0, and no operands means stdin.
+    let out = dry_run(&["--dry-run"])?;
+    if !out.stdout.is_empty() || out.stderr != b"cat: -: ok\n" || out.status.code() != Some(0) {
+        bail!(
+            "dry-run of stdin: status {:?} stderr {:?}",
+            out.status.code(),
+            String::from_utf8_lossy(&out.stderr)
+        );
+    }
+    Ok(())
+}
+
+/// How many lines `cat -n` (or `-b`, with or without `-s`) would number in
+/// the concatenated input.
+fn numbered_line_count(data: &[u8], nonblank: bool, squeeze: bool) -> usize {
+    let mut count = 0;
+    let mut previous_blank = false;
+    for line in data.split_inclusive(|&b| b == b'\n') {
+        let blank = line == b"\n";
+        if squeeze && blank && previous_blank {
+            continue;
+        }
+        previous_blank = blank;
+        if !(nonblank && blank) {
+            count += 1;
+        }
+    }
+    count
+}
+
+fn test_count_only(h: &Harness) -> Result<()> {
+    let operand_sets = [
+        vec![&h.fixtures.sample_a, &h.fixtures.no_newline, &h.fixtures.blank],
+        vec![&h.fixtures.no_newline, &h.fixtures.no_newline],
+        vec![&h.fixtures.large],
+        vec![&h.fixtures.empty],
+    ];
+    for operands in operand_sets {
+        let mut data = Vec::new();
+        for path in &operands {
+            data.extend(fs::read(path)?);
+        }
+        let names: Vec<&str> = operands.iter().map(|p| p.to_str().unwrap()).collect();
+        for flags in [vec![], vec!["-n"], vec!["-b"], vec!["-s"], vec!["-bs"], vec!["-nE"]] {
+            let nonblank = flags.iter().any(|f| f.contains('b'));
+            let squeeze = flags.iter().any(|f| f.contains('s'));
+            let lines = numbered_line_count(&data, nonblank, squeeze);
+            let mut args = vec!["--count-only"];
+            args.extend(&flags);
+            args.extend(&names);
+            h.expect_wcat(&args, None, format!("{lines}\n").as_bytes())?;
+            args[0] = "--count-only=lines,bytes";
+            h.expect_wcat(&args, None, format!("{lines} {}\n", data.len()).as_bytes())?;
+        }
+    }
+    // stdin counts too, and the last spelling decides whether bytes are shown.
+    let input = &h.fixtures.stdin_mix;
+    let lines = numbered_line_count(input, false, false);
+    h.expect_wcat(
+        &["--count-only=lines,bytes", "--count-only"],
+        Some(input),
+        format!("{lines}\n").as_bytes(),
+    )?;
+    h.expect_wcat_error(&["--count-only=words"], None, "invalid argument 'words' for '--count-only'")
+}
+
+/// A broken pipe under --epipe-exit must end wcat with the chosen exit code
+/// and no diagnostic, on the plain and decorated paths; without the flag the
+/// SIGPIPE comparison against cat covers the default.
+fn test_epipe_exit(h: &Harness) -> Result<()> {
+    let large = h.fixtures.large.to_str().unwrap();
+    for (flag, code) in [("--epipe-exit", 0), ("--epipe-exit=3", 3), ("--epipe-exit=255", 255)] {
+        for extra in [None, Some("-n"), Some("-v")] {
+            let mut args = vec![flag];
+            args.extend(extra);
+            args.push(large);
+            let (exit, stderr) = run_into_closed_pipe(&h.wcat, &args, false)?;
+            if exit != (Some(code), None) || !stderr.is_empty() {
+                bail!(
+                    "{args:?}: expected exit {code} without a signal, got (code, signal) {exit:?} stderr {:?}",
+                    String::from_utf8_lossy(&stderr)
+                );
+            }
+        }
+    }
+    let (exit, _) = run_into_closed_pipe(&h.wcat, &["-n", large], false)?;
+    if exit != (None, Some(Signal::SIGPIPE as i32)) {
+        bail!("without --epipe-exit wcat should die of SIGPIPE, got (code, signal) {exit:?}");
+    }
+    h.expect_wcat_error(&["--epipe-exit=256"], None, "invalid argument '256' for '--epipe-exit'")
+}
+
+/// --strip-trailing-cr drops the CR of each CRLF before the other options
+/// see the line, so "\r\n" lines are blank to -b and -s, while lone CRs,
+/// including one that ends the input, are kept (and shown as ^M by -v).
+fn test_strip_trailing_cr(h: &Harness) -> Result<()> {
+    let path = h.scratch().join("crlf_strip.txt");
+    fs::write(&path, b"one\r\ntwo\r\n\r\n\r\nlone\rcr\r\nend\r")?;
+    let file = path.to_str().unwrap();
+    let cases: [(&[&str], &[u8]); 6] = [
+        (&[], b"one\ntwo\n\n\nlone\rcr\nend\r"),
+        (&["-E"], b"one$\ntwo$\n$\n$\nlone\rcr$\nend\r"),
+        (&["-n"], b"     1\tone\n     2\ttwo\n     3\t\n     4\t\n     5\tlone\rcr\n     6\tend\r"),
+        (&["-bs"], b"     1\tone\n     2\ttwo\n\n     3\tlone\rcr\n     4\tend\r"),
+        (&["-nE"], b"     1\tone$\n     2\ttwo$\n     3\t$\n     4\t$\n     5\tlone\rcr$\n     6\tend\r"),
+        (&["-A"], b"one$\ntwo$\n$\n$\nlone^Mcr$\nend^M"),
+    ];
+    for (flags, expected) in cases {
+        let mut args = vec!["--strip-trailing-cr"];
+        args.extend_from_slice(flags);
+        args.push(file);
+        h.expect_wcat(&args, None, expected)?;
+        args.pop();
+        h.expect_wcat(&args, Some(&fs::read(&path)?), expected)?;
+    }
+    // A CR that ends one read is only dropped once the next read shows a LF,
+    // and a file's trailing CR pairs with the next file's leading LF.
+    let boundary = h.scratch().join("crlf_boundary.txt");
+    for next in [b'\n', b'x'] {
+        let mut data = vec![b'a'; WCAT_BUFFER_SIZE - 1];
+        data.extend_from_slice(&[b'\r', next, b'\n']);
+        fs::write(&boundary, &data)?;
+        let mut expected = vec![b'a'; WCAT_BUFFER_SIZE - 1];
+        expected.extend_from_slice(if next == b'\n' { b"\n\n" } else { b"\rx\n" });
+        h.expect_wcat(&["--strip-trailing-cr", boundary.to_str().unwrap()], None, &expected)?;
+    }
+    let first = h.scratch().join("cr_end.txt");
+    let second = h.scratch().join("lf_start.txt");
+    fs::write(&first, b"a\r")?;
+    fs::write(&second, b"\nb\r\n")?;
+    h.expect_wcat(
+        &["--strip-trailing-cr", "-n", first.to_str().unwrap(), second.to_str().unwrap()],
+        None,
+        b"     1\ta\n     2\tb\n",
+    )?;
+    h.expect_wcat_error(&["--strip-trailing-cr=yes"], None, "doesn't allow an argument")
+}
+
+/// -w WIDTH takes its value attached, from the next word, or at the end of
+/// a cluster; every spelling must give the same bytes, and width 6 is cat's.
+fn test_number_width(h: &Harness) -> Result<()> {
+    let path = h.scratch().join("width_lines.txt");
+    let lines: String =
+        (1..=11).map(|i| if i == 3 { "\n".into() } else { format!("l{i}\n") }).collect();
+    fs::write(&path, &lines)?;
+    let file = path.to_str().unwrap();
+    let golden = run_cmd(&h.cat, &["-n", file], None)?.stdout;
+    for args in [
+        &["-w6", "-n", file][..],
+        &["-nw6", file],
+        &["-n", "-w", "6", file],
+        &["-w", "6", "-n", file],
+        &["-n", file],
+    ] {
+        h.expect_wcat(args, None, &golden)?;
+    }
+    let numbered = |width: usize, nonblank: bool| -> Vec<u8> {
+        let mut out = String::new();
+        let mut n = 0;
+        for line in lines.lines() {
+            if nonblank && line.is_empty() {
+                out.push('\n');
+                continue;
+            }
+            n += 1;
+            out.push_str(&format!("{n:>width$}\t{line}\n"));
+        }
+        out.into_bytes()
+    };
+    for (args, expected) in [
+        (&["-nw3", file][..], numbered(3, false)),
+        (&["-w", "3", "-n", file], numbered(3, false)),
+        (&["-bw1", file], numbered(1, true)),
+        (&["-w", "12", "-b", file], numbered(12, true)),
+        (&["-w3", file], lines.clone().into_bytes()),
+    ] {
+        h.expect_wcat(args, None, &expected)?;
+    }
+    let out = run_cmd_with_arg0(&h.wcat, &[file], None, None, &[("WCAT_OPTIONS", "-w 3 -n")])?;
+    if out.stdout != numbered(3, false) {
+        bail!("WCAT_OPTIONS='-w 3 -n' gave {:?}", String::from_utf8_lossy(&out.stdout));
+    }
+    h.expect_wcat_error(&["-n", "-w"], None, "option requires an argument -- 'w'")?;
+    for bad in ["-w0", "-wx", "-w33", "-w-1"] {
+        let needle = format!("invalid line number field width: '{}'", &bad[2..]);
+        h.expect_wcat_error(&[bad, file], None, &needle)?;
+    }
+    Ok(())
+}
+
+/// --repeat=N copies each operand N times in place, as if it were named N
+/// times, with -n numbering running on. Pipes, FIFOs and stdin cannot be
+/// reread, so they are read once and replayed, unlike `cat - -`.
+fn test_repeat(h: &Harness) -> Result<()> {
+    let a = h.fixtures.sample_a.to_str().unwrap();
+    let b = h.fixtures.sample_b.to_str().unwrap();
+    let unterminated = h.scratch().join("repeat_tail.txt");
+    fs::write(&unterminated, b"x\n\n\ny")?;
+    let tail = unterminated.to_str().unwrap();
+    for (flags, operands, explicit) in [
+        (&[][..], &[a][..], &[a, a, a][..]),
+        (&["-n"], &[a, b], &[a, a, a, b, b, b]),
+        (&["-bs"], &[tail, b], &[tail, tail, tail, b, b, b]),
+        (&["-A"], &[tail], &[tail, tail, tail]),
+    ] {
+        let golden = run_cmd(&h.cat, &[flags, explicit].concat(), None)?.stdout;
+        h.expect_wcat(&[&["--repeat=3"], flags, operands].concat(), None, &golden)?;
+    }
+    // Unseekable input: stdin from a pipe and a FIFO each give N copies.
+    let data = b"one\n\ntwo";
+    let twice = [&data[..], data].concat();
+    let golden = run_cmd(&h.cat, &["-n", "-", b, b], Some(&twice))?.stdout;
+    h.expect_wcat(&["--repeat=2", "-n", "-", b], Some(data), &golden)?;
+    let fifo = h.scratch().join("repeat.fifo");
+    mkfifo(&fifo, nix::sys::stat::Mode::from_bits_truncate(0o644))?;
+    let args = ["--repeat=2", "-n", fifo.to_str().unwrap()];
+    let out = run_fifo_cmd(&h.wcat, &args, &fifo, data, None)?;
+    let golden = run_cmd(&h.cat, &["-n"], Some(&twice))?.stdout;
+    if out.stdout != golden {
+        bail!(
+            "--repeat=2 on a FIFO\n{}",
+            describe_diff("stdout", ("wcat", &out.stdout), ("cat", &golden))
+        );
+    }
+    // A directory fails every pass alike: its error is reported once, and the
+    // operands after it still get all their copies.
+    let dir = h.fixtures.dir_path.to_str().unwrap();
+    let out = run_cmd(&h.wcat, &["--repeat=3", "-n", dir, a], None)?;
+    let golden = run_cmd(&h.cat, &["-n", a, a, a], None)?.stdout;
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    if out.stdout != golden || stderr.matches("Is a directory").count() != 1 || out.status.code() != Some(1) {
+        bail!(
+            "--repeat=3 on a directory: status {:?}, stderr {stderr:?}\n{}",
+            out.status.code(),
+            describe_diff("stdout", ("wcat", &out.stdout), ("cat", &golden))
+        );
+    }
+    h.expect_wcat_error(&["--repeat=0", a], None, "invalid argument '0' for '--repeat'")?;
+    h.expect_wcat_error(&[a, "--repeat"], None, "option '--repeat' requires an argument")
+}
+
+/// --headers puts tail -v's "==> NAME <==" line before each operand, with an
+/// empty line before all but the first. Headers are not numbered, close an
+/// unterminated line, and only a `-` operand names standard input.
+fn test_headers(h: &Harness) -> Result<()> {
+    let dir = &h.scratch();
+    let first = dir.join("head_one.txt");
+    let second = dir.join("head_two.txt");
+    fs::write(&first, b"a\nb")?;
+    fs::write(&second, b"\n\nc\n")?;
+    let (one, two) = (first.to_str().unwrap(), second.to_str().unwrap());
+    let header = |name: &str, first: bool| {
+        format!("{}==> {name} <==\n", if first { "" } else { "\n" })
+    };
+    let plain = format!("{}a\nb{}\n\nc\n", header(one, true), header(two, false));
+    h.expect_wcat(&["--headers", one, two], None, plain.as_bytes())?;
+    let numbered = format!(
+        "{}     1\ta\n     2\tb{}     3\t\n     4\t\n     5\tc\n",
+        header(one, true),
+        header(two, false)
+    );
+    h.expect_wcat(&["--headers", "-n", one, two], None, numbered.as_bytes())?;
+    let squeezed = format!("{}\nc\n{}a\nb", header(two, true), header(one, false));
+    h.expect_wcat(&["--headers", "-s", two, one], None, squeezed.as_bytes())?;
+    let stdin = format!("{}in\n{}a\nb", header("standard input", true), header(one, false));
+    h.expect_wcat(&["--headers", "-", one], Some(b"in\n"), stdin.as_bytes())?;
+    h.expect_wcat(&["--headers"], Some(b"in\n"), b"in\n")?;
+    let missing = dir.join("head_missing.txt");
+    let out = run_cmd(&h.wcat, &["--headers", missing.to_str().unwrap(), two], None)?;
+    if out.stdout != format!("{}\n\nc\n", header(two, true)).as_bytes() {
+        bail!("--headers with a missing operand: {:?}", String::from_utf8_lossy(&out.stdout));
+    }
+    h.expect_wcat_error(&["--headers=yes"], None, "doesn't allow an argument")
+}
+
+/// --tab-marker=STR changes only what -T writes for a tab; the default stays
+/// cat's ^I and the marker alone leaves tabs alone.
+fn test_tab_marker(h: &Harness) -> Result<()> {
+    let tabs = h.fixtures.tabs.to_str().unwrap();
+    let tabs_data = fs::read(&h.fixtures.tabs)?;
+    let cases: [(&[&str], &[u8]); 5] = [
+        (&["-T", "--tab-marker=->"], b"col1->col2\nline->2\n"),
+        (&["--tab-marker=[tab]", "-nT"], b"     1\tcol1[tab]col2\n     2\tline[tab]2\n"),
+        (&["-A", "--tab-marker= "], b"col1 col2$\nline 2$\n"),
+        (&["--tab-marker=^I", "-t"], b"col1^Icol2\nline^I2\n"),
+        (&["--tab-marker=->"], b"col1\tcol2\nline\t2\n"),
+    ];
+    for (flags, expected) in cases {
+        h.expect_wcat(&[flags, &[tabs]].concat(), None, expected)?;
+        h.expect_wcat(flags, Some(&tabs_data), expected)?;
+    }
+    h.compare_with_cat(&["-T", tabs], None)?;
+    h.compare_with_cat(&["-T", "-"], Some(&tabs_data))?;
+    h.expect_wcat_error(&["--tab-marker="], None, "invalid argument '' for '--tab-marker'")?;
+    h.expect_wcat_error(&["--tab-marker=\x1b[7m"], None, "for '--tab-marker'")?;
+    h.expect_wcat_error(&["--tab-marker"], None, "requires an argument")
+}
+
+/// --end-marker=STR changes only what -E writes before a line end; a last
+/// line without one gets no marker, as with cat's $, and the default stays
+/// cat's.
+fn test_end_marker(h: &Harness) -> Result<()> {
+    let path = h.scratch().join("end_marker.txt");
+    fs::write(&path, b"a\tb\n\nc")?;
+    let data = fs::read(&path)?;
+    let path = path.to_str().unwrap();
+    let cases: [(&[&str], &[u8]); 6] = [
+        (&["-E", "--end-marker=<"], b"a\tb<\n<\nc"),
+        (&["--end-marker= |", "-nE"], b"     1\ta\tb |\n     2\t |\n     3\tc"),
+        (&["-A", "--end-marker=[eol]"], b"a^Ib[eol]\n[eol]\nc"),
+        (&["-bsE", "--end-marker=#"], b"     1\ta\tb#\n#\n     2\tc"),
+        (&["--end-marker=$", "-E"], b"a\tb$\n$\nc"),
+        (&["--end-marker=<"], b"a\tb\n\nc"),
+    ];
+    for (flags, expected) in cases {
+        h.expect_wcat(&[flags, &[path]].concat(), None, expected)?;
+        h.expect_wcat(flags, Some(&data), expected)?;
+    }
+    h.compare_with_cat(&["-E", path], None)?;
+    h.compare_with_cat(&["-E", "-"], Some(&data))?;
+    h.expect_wcat_error(&["--end-marker="], None, "invalid argument '' for '--end-marker'")?;
+    h.expect_wcat_error(&["--end-marker=\r"], None, "for '--end-marker'")?;
+    h.expect_wcat_error(&["--end-marker"], None, "requires an argument")
+}
+
+/// --ensure-newline adds one line end after the last operand when the
+/// output would otherwise stop mid-line, and nothing when it already ends a
+/// line or is empty. Unterminated operands in the middle still join.
+fn test_ensure_newline(h: &Harness) -> Result<()> {
+    let dir = &h.scratch();
+    let first = dir.join("ensure_first.txt");
+    let last = dir.join("ensure_last.txt");
+    fs::write(&first, b"a\nb")?;
+    fs::write(&last, b"c")?;
+    let (first, last) = (first.to_str().unwrap(), last.to_str().unwrap());
+    let no_newline = h.fixtures.no_newline.to_str().unwrap();
+    let cases: [(&[&str], &[u8]); 6] = [
+        (&[first, last], b"a\nbc\n"),
+        (&["-E", first, last], b"a$\nbc$\n"),
+        (&["-n", first, last], b"     1\ta\n     2\tbc\n"),
+        (&["--null", first], b"a\0b\0"),
+        (&["-A", first], b"a$\nb$\n"),
+        (&["-E", first, "/dev/null"], b"a$\nb$\n"),
+    ];
+    for (args, expected) in cases {
+        h.expect_wcat(&[&["--ensure-newline"], args].concat(), None, expected)?;
+    }
+    let mut terminated = fs::read(&h.fixtures.no_newline)?;
+    terminated.push(b'\n');
+    h.expect_wcat(&["--ensure-newline", no_newline], None, &terminated)?;
+    h.expect_wcat(&["--ensure-newline", "-"], Some(b"c"), b"c\n")?;
+    let blank = h.fixtures.blank.to_str().unwrap();
+    h.expect_wcat(&["--ensure-newline", blank], None, &fs::read(&h.fixtures.blank)?)?;
+    h.expect_wcat(&["--ensure-newline", "-E"], Some(b""), b"")?;
+    h.expect_wcat_error(&["--ensure-newline=yes"], None, "doesn't allow an argument")
+}
+
+/// --exit-ok turns the status of a run with unreadable operands into 0 but
+/// changes nothing else: stdout and the diagnostics are cat's. Usage errors
+/// still fail.
+fn test_exit_ok(h: &Harness) -> Result<()> {
+    let a = h.fixtures.sample_a.to_str().unwrap();
+    let missing = h.scratch().join("exit_ok_missing.txt");
+    let missing = missing.to_str().unwrap();
+    let dir = h.fixtures.dir_path.to_str().unwrap();
+    for operands in [&[missing][..], &[a, missing, a], &["-n", dir, a]] {
+        let cat_out = run_cmd(&h.cat, operands, None)?;
+        let args = [&["--exit-ok"], operands].concat();
+        let out = run_cmd_with_arg0(&h.wcat, &args, None, Some(&h.cat), &[])?;
+        if cat_out.status.success() {
+            bail!("cat {operands:?} succeeded; the case needs a failing operand");
+        }
+        if out.stdout != cat_out.stdout || out.stderr != cat_out.stderr || !out.status.success() {
+            bail!(
+                "--exit-ok {operands:?}: status {:?}, stderr {:?} vs cat's {:?}",
+                out.status.code(),
+                String::from_utf8_lossy(&out.stderr),
+                String::from_utf8_lossy(&cat_out.stderr)
+            );
+        }
+    }
+    let quiet = run_cmd(&h.wcat, &["--exit-ok", "--quiet-errors", missing, a], None)?;
+    if !quiet.status.success() || !quiet.stderr.is_empty() || quiet.stdout != fs::read(&h.fixtures.sample_a)? {
+        bail!("--exit-ok --quiet-errors: status {:?}", quiet.status.code());
+    }
+    h.expect_wcat_error(&["--exit-ok", "--bogus"], None, "unrecognized option '--bogus'")?;
+    h.expect_wcat_error(&["--exit-ok=1"], None, "doesn't allow an argument")
+}
+
+/// A mandatory long option value may follow '=' or be the next word, as
+/// with getopt_long; options without a value still reject "=VALUE".
+fn test_long_option_values(h: &Harness) -> Result<()> {
+    let a = h.fixtures.sample_a.to_str().unwrap();
+    let golden: Vec<u8> = String::from_utf8(fs::read(&h.fixtures.sample_a)?)?
+        .lines()
+        .enumerate()
+        .flat_map(|(i, line)| format!("{:>8}\t{line}\n", i + 1).into_bytes())
+        .collect();
+    for args in [
+        &["-n", "--number-width=8", a][..],
+        &["-n", "--number-width", "8", a],
+        &["--number-width", "8", "-n", a],
+        &["-n", "-w", "8", a],
+    ] {
+        h.expect_wcat(args, None, &golden)?;
+    }
+    let twice = run_cmd(&h.cat, &["-n", a, a], None)?.stdout;
+    h.expect_wcat(&["--repeat", "2", "-n", a], None, &twice)?;
+    let list = h.scratch().join("long_values.list");
+    fs::write(&list, format!("{a}\n"))?;
+    let list = list.to_str().unwrap();
+    h.expect_wcat(&["--files-from", list, a], None, &run_cmd(&h.cat, &[a, a], None)?.stdout)?;
+    let envs = [("WCAT_OPTIONS", "--number-width 8 -n")];
+    let out = run_cmd_with_arg0(&h.wcat, &[a], None, None, &envs)?;
+    if out.stdout != golden {
+        let got = String::from_utf8_lossy(&out.stdout);
+        bail!("WCAT_OPTIONS='--number-width 8 -n' gave {got:?}");
+    }
+    for (args, needle) in [
+        (&["--show-ends=1", a][..], "option '--show-ends' doesn't allow an argument"),
+        (&[a, "--number-width"], "option '--number-width' requires an argument"),
+        (&["--number-width", "0", a], "invalid argument '0' for '--number-width'"),
+        (&["--number-width=33", a], "invalid argument '33' for '--number-width'"),
+    ] {
+        h.expect_wcat_error(args, None, needle)?;
+    }
+    Ok(())
+}
+
+/// Feed stdin one line at a time and require each numbered line on stdout
+/// before the next is written; a partial line must wait for its newline.
+/// Without --buffer=line all of it would arrive at EOF.
+fn test_buffer_line(h: &Harness) -> Result<()> {
+    for (args, lines) in [
+        (vec!["--buffer=line", "-n"], ["     1\tone\n", "     2\ttwo\n", "     3\tthree\n"]),
+        (vec!["--buffer=line", "-E"], ["one$\n", "two$\n", "three$\n"]),
+    ] {
+        let mut child = Command::new(&h.wcat)
+            .args(&args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let mut stdin = child.stdin.take().unwrap();
+        let mut stdout = child.stdout.take().unwrap();
+        let (tx, rx) = std::sync::mpsc::channel::<Vec<u8>>();
+        let reader = std::thread::spawn(move || {
+            let mut buf = [0u8; 4096];
+            while let Ok(n @ 1..) = stdout.read(&mut buf) {
+                if tx.send(buf[..n].to_vec()).is_err() {
+                    break;
+                }
+            }
+        });
+        let mut seen = Vec::new();
+        let mut expect_next = |expected: &str| -> Result<()> {
+            while seen.len() < expected.len() {
+                match rx.recv_timeout(std::time::Duration::from_secs(5)) {
+                    Ok(chunk) => seen.extend(chunk),
+                    Err(_) => break,
+                }
+            }
+            if seen != expected.as_bytes() {
+                let _ = child.kill();
+                bail!(
+                    "{args:?}: expected {expected:?} before more input, got {:?}",
+                    String::from_utf8_lossy(&seen)
+                );
+            }
+            seen.clear();
+            Ok(())
+        };
+        stdin.write_all(b"one\n")?;
+        expect_next(lines[0])?;
+        stdin.write_all(b"two\nthr")?;
+        expect_next(lines[1])?;
+        stdin.write_all(b"ee\n")?;
+        expect_next(lines[2])?;
+        drop(stdin);
+        reader.join().unwrap();
+        if !child.wait()?.success() {
+            bail!("{args:?}: wcat failed");
+        }
+    }
+    Ok(())
+}
+
+fn test_merge_stdin_replay(h: &Harness) -> Result<()> {
+    // Golden output: cat reading a file copy of stdin at every "-" position.
+    let a = h.fixtures.sample_a.to_str().unwrap();
+    let large = fs::read(&h.fixtures.large)?;
+    for (input, flags) in [(&h.fixtures.stdin_data, vec!["-n"]), (&large, vec![])] {
+        let copy = h.scratch().join("merge_stdin_copy.txt");
+        fs::write(&copy, input)?;
+        let copy = copy.to_str().unwrap();
+        let mut cat_args = flags.clone();
+        cat_args.extend([copy, a, copy]);
+        let expected = run_cmd(&h.cat, &cat_args, None)?.stdout;
+        let mut args = vec!["--merge-stdin"];
+        args.extend(flags);
+        args.extend(["-", a, "-"]);
+        h.expect_wcat(&args, Some(input), &expected)?;
+    }
+    Ok(())
+}
+
+fn test_since_mtime(h: &Harness) -> Result<()> {
+    let old = h.scratch().join("rotated.log.1");
+    let new = h.scratch().join("rotated.log");
+    fs::write(&old, "old entry\nolder entry\n")?;
+    fs::write(&new, "new entry\n")?;
+    let epoch = |secs| std::time::UNIX_EPOCH + std::time::Duration::from_secs(secs);
+    File::options().write(true).open(&old)?.set_modified(epoch(1_000_000))?;
+    File::options().write(true).open(&new)?.set_modified(epoch(2_000_000))?;
+    let (old, new) = (old.to_str().unwrap(), new.to_str().unwrap());
+
+    // The old file drops out entirely, "-" always stays, numbering runs on.
+    let expected = run_cmd(&h.cat, &["-n", new, "-"], Some(b"piped\n"))?.stdout;
+    let args = ["--since-mtime=1500000", "-n", old, new, "-", old];
+    h.expect_wcat(&args, Some(b"piped\n"), &expected)?;
+    // The cutoff itself is inclusive.
+    h.expect_wcat(&["--since-mtime=2000000", old, new], None, b"new entry\n")?;
+    h.expect_wcat_error(&["--since-mtime=-1", new], None, "invalid argument '-1'")
+}
+
+/// Run `cmd` with stdout on a fresh pseudo-terminal and `envs` on top of the
+/// clean environment; `stdout` holds what the master side read.
+fn run_cmd_on_pty(cmd: &Path, args: &[&str], envs: &[(&str, &str)]) -> Result<CmdOutput> {
+    let pty = nix::pty::openpty(None, None)?;
+    // openpty() leaves both ends inheritable; under --threads another case's
+    // child would hold the slave open and the master would never see EIO.
+    for fd in [&pty.master, &pty.slave] {
+        nix::fcntl::fcntl(fd, nix::fcntl::FcntlArg::F_SETFD(nix::fcntl::FdFlag::FD_CLOEXEC))?;
+    }
+    let mut command = Command::new(cmd);
+    clean_env(&mut command, envs);
+    let child = command
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::from(pty.slave))
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("spawning {cmd:?}"))?;
+    // The Command owns our copy of the slave: the master only sees EIO once
+    // it is gone too.
+    drop(command);
+    let mut master = File::from(pty.master);
+    let reader = std::thread::spawn(move || {
+        // Once the slave side is gone the master reports EIO: that is EOF.
+        let mut seen = Vec::new();
+        let _ = master.read_to_end(&mut seen);
+        seen
+    });
+    let output = wait_with_deadline(child, cmd, args)?;
+    Ok(CmdOutput {
+        status: output.status,
+        stdout: reader.join().unwrap(),
+        stderr: output.stderr,
+    })
+}
+
+/// --pager only acts on a terminal: into a pipe the bytes are exactly cat's.
+/// On a pty, a stub $PAGER saves what it is fed; it gets the decorated
+/// output, nothing reaches the tty directly, and wcat waits for the pager
+/// before exiting.
+fn test_pager(h: &Harness) -> Result<()> {
+    let a = h.fixtures.sample_a.to_str().unwrap();
+    let control = h.fixtures.control.to_str().unwrap();
+    for flags in [&[][..], &["-n"], &["-A"]] {
+        let expected = run_cmd(&h.cat, &[flags, &[a, control]].concat(), None)?.stdout;
+        h.expect_wcat(&[&["--pager"], flags, &[a, control]].concat(), None, &expected)?;
+    }
+    let paged = h.scratch().join("paged.out");
+    // The pause proves wcat waits: the file is only complete after it.
+    let pager = format!("sleep 0.2; cat > '{}'", paged.display());
+    let out = run_cmd_on_pty(&h.wcat, &["--pager", "-n", a, control], &[("PAGER", &pager)])?;
+    let expected = run_cmd(&h.cat, &["-n", a, control], None)?.stdout;
+    let got = fs::read(&paged).unwrap_or_default();
+    if got != expected || !out.stdout.is_empty() || !out.status.success() {
+        bail!(
+            "--pager on a tty: pager got {}B of {}B, {}B went to the tty, status {:?}",
+            got.len(),
+            expected.len(),
+            out.stdout.len(),
+            out.status.code()
+        );
+    }
+    Ok(())
+}
+
+/// --byte-offset starts each line with where it begins in the input: eight
+/// hex digits and a tab, before the -n/-b number. Lines dropped by -s and
+/// stripped CRs still take up input bytes, and the offset runs on across
+/// operands. The line end --ensure-newline adds is not input and moves
+/// nothing; options that hold lines back or reorder them are refused.
+fn test_byte_offset(h: &Harness) -> Result<()> {
+    let dir = &h.scratch();
+    let first = dir.join("offset_first.txt");
+    let second = dir.join("offset_second.txt");
+    let blanks = dir.join("offset_blanks.txt");
+    let crlf = dir.join("offset_crlf.txt");
+    fs::write(&first, b"ab\n\ncd\n")?;
+    fs::write(&second, b"x\ny")?;
+    fs::write(&blanks, b"\n\n\nz\n")?;
+    fs::write(&crlf, b"a\r\nb\r\n")?;
+    let [first, second, blanks, crlf] =
+        [&first, &second, &blanks, &crlf].map(|path| path.to_str().unwrap());
+    let numbered: &[u8] = b"00000000\t     1\tab\n00000003\t     2\t\n00000004\t     3\tcd\n";
+    let cases: [(&[&str], &[u8]); 7] = [
+        (&[first], b"00000000\tab\n00000003\t\n00000004\tcd\n"),
+        (&["-n", first], numbered),
+        (&["-b", first], b"00000000\t     1\tab\n00000003\t\n00000004\t     2\tcd\n"),
+        (&["-E", first, second], b"00000000\tab$\n00000003\t$\n00000004\tcd$\n00000007\tx$\n00000009\ty"),
+        (&["-s", blanks], b"00000000\t\n00000003\tz\n"),
+        (&["--strip-trailing-cr", crlf], b"00000000\ta\n00000003\tb\n"),
+        (&["-A", crlf], b"00000000\ta^M$\n00000003\tb^M$\n"),
+    ];
+    for (args, expected) in cases {
+        h.expect_wcat(&[&["--byte-offset"], args].concat(), None, expected)?;
+    }
+    h.expect_wcat(&["--byte-offset", "-n"], Some(b"ab\n\ncd\n"), numbered)?;
+    h.expect_wcat(&["--byte-offset"], Some(b""), b"")?;
+    h.expect_wcat(
+        &["--byte-offset", "--ensure-newline", "-E", first, second],
+        None,
+        b"00000000\tab$\n00000003\t$\n00000004\tcd$\n00000007\tx$\n00000009\ty$\n",
+    )?;
+    h.expect_wcat(
+        &["--byte-offset", "--ensure-newline", second, second],
+        None,
+        b"00000000\tx\n00000002\tyx\n00000005\ty\n",
+    )?;
+    for other in ["--reverse", "--dedupe", "--normalize"] {
+        let needle = format!("option '--byte-offset' cannot be combined with '{other}'");
+        h.expect_wcat_error(&["--byte-offset", other, first], None, &needle)?;
+        h.expect_wcat_error(&[other, "--byte-offset", "-"], None, &needle)?;
+    }
+    h.expect_wcat_error(&["--byte-offset=8"], None, "doesn't allow an argument")
+}
+
+fn test_progress_markers(h: &Harness) -> Result<()> {
+    // stderr is a pipe here, so a bare --progress stays quiet and =always
+    // prints the final total; stdout is untouched either way.
+    let huge = h.fixtures.huge.to_str().unwrap();
+    let expected_stdout = fs::read(&h.fixtures.huge)?;
+    let quiet = run_cmd(&h.wcat, &["--progress", huge], None)?;
+    if quiet.stdout != expected_stdout || !quiet.stderr.is_empty() {
+        bail!("--progress spoke to a non-tty stderr: {:?}", String::from_utf8_lossy(&quiet.stderr));
+    }
+    let forced = run_cmd(&h.wcat, &["--progress=always", huge], None)?;
+    let expected = format!("{}: progress {} bytes\n", h.wcat.display(), expected_stdout.len());
+    if forced.stdout != expected_stdout || forced.stderr != expected.as_bytes() || !forced.status.success() {
+        bail!(
+            "--progress=always: expected {expected:?}, got {:?}",
+            String::from_utf8_lossy(&forced.stderr)
+        );
+    }
+    Ok(())
+}
+
+fn test_progress_interval(h: &Harness) -> Result<()> {
+    // 130 MiB crosses the 64 MiB step twice, then the final total follows.
+    let path = h.scratch().join("progress.bin");
+    File::create(&path)?.set_len(130 << 20)?;
+    let out = run_cmd_to_file(
+        &h.wcat,
+        &["--progress=always", path.to_str().unwrap()],
+        None,
+        None,
+        &[],
+        Path::new("/dev/null"),
+    )?;
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    let lines: Vec<&str> = stderr.lines().collect();
+    let total = format!("progress {} bytes", 130 << 20);
+    if lines.len() != 3 || !lines.iter().all(|l| l.contains(": progress ")) || !lines[2].ends_with(&total) {
+        bail!("unexpected --progress lines: {stderr:?}");
+    }
+    Ok(())
+}
+
+fn test_checksum_matches_output(h: &Harness) -> Result<()> {
+    let crc = crc::Crc::<u32>::new(&crc::CRC_32_ISO_HDLC);
+    let files = [&h.fixtures.large, &h.fixtures.binary, &h.fixtures.empty, &h.fixtures.control];
+    for file in files {
+        let path = file.to_str().unwrap();
+        for extra in [&[][..], &["-n"][..], &["-A"][..], &["-s", "-E"][..]] {
+            let mut cat_args = extra.to_vec();
+            cat_args.push(path);
+            let mut args = extra.to_vec();
+            args.extend(["--checksum", path]);
+            let wcat_out = run_cmd(&h.wcat, &args, None)?;
+            let cat_out = run_cmd(&h.cat, &cat_args, None)?;
+            if wcat_out.stdout != cat_out.stdout || !wcat_out.status.success() {
+                bail!("--checksum changed stdout for {args:?}");
+            }
+            let expected = format!(
+                "{}: crc32 {:08x}\n",
+                h.wcat.display(),
+                crc.checksum(&cat_out.stdout)
+            );
+            let stderr = String::from_utf8_lossy(&wcat_out.stderr);
+            if stderr != expected {
+                bail!("--checksum for {args:?}: expected {expected:?}, got {stderr:?}");
+            }
+        }
+    }
+    Ok(())
+}
+
+fn test_tabs_run_at_chunk_boundary(h: &Harness) -> Result<()> {
+    // One full 256 KiB read of line text, then the rest of the line in the
+    // next read: the buffered run must survive the flush in between.
+    let path = h.scratch().join("chunk_boundary.txt");
+    let mut data = vec![b'a'; WCAT_BUFFER_SIZE];
+    data.extend_from_slice(b"x\tz\n");
+    fs::write(&path, &data)?;
+    for flag in ["-T", "-A", "-vT"] {
+        h.compare_with_cat(&[flag, path.to_str().unwrap()], None)?;
+    }
+    Ok(())
+}
+
+fn test_comment_preservation(_h: &Harness) -> Result<()> {
+    let tmp = TempDir::new()?;
+    let asm = tmp.path().join("sample.asm");
+    fs::write(
+        &asm,
+        b";only comment\nmov rax, rbx ; trailing\n  ; indented comment\nlabel: nop\n",
+    )?;
+    let out_dir = tmp.path().join("out");
+    process_one_asm(&asm, &out_dir.join("sample.asm"), false, ';')?;
+    let content = fs::read_to_string(out_dir.join("sample.asm"))?;
+    let lines: Vec<&str> = content.lines().collect();
+    if lines[0].trim_start() != ";only comment" {
+        bail!("comment-only line removed");
+    }
+    if !lines[1].contains("mov rax, rbx") || lines[1].contains(";") {
+        bail!("trailing comment not stripped correctly");
+    }
+    if lines[2].trim_start() != "; indented comment" {
+        bail!("indented comment line lost");
+    }
+    Ok(())
+}
+
+fn test_process_asm_check(_h: &Harness) -> Result<()> {
+    if which::which("nasm").is_err() {
+        println!("[SKIP] nasm not available");
+        return Ok(());
+    }
+    let tmp = TempDir::new()?;
+    let good = tmp.path().join("good.asm");
+    fs::write(&good, b"section .text ; code\nglobal _start\n_start: ; entry\n    ret\n")?;
+    let processed = tmp.path().join("out/good.asm");
+    process_one_asm(&good, &processed, false, ';')?;
+    check_processed_asm(&good, &processed)?;
+
+    let broken = tmp.path().join("broken.asm");
+    fs::write(&broken, b"section .text\n    mov rax,\n")?;
+    if check_processed_asm(&good, &broken).is_ok() {
+        bail!("--check accepted a processed file that does not assemble");
+    }
+    check_processed_asm(&broken, &broken).context("sources that never assembled should be skipped")
+}
+
+fn test_process_asm_in_place(_h: &Harness) -> Result<()> {
+    let tmp = TempDir::new()?;
+    let asm = tmp.path().join("inplace.asm");
+    fs::write(&asm, b"; header\nmov rax, rbx ; trailing\n")?;
+    fs::set_permissions(&asm, fs::Permissions::from_mode(0o640))?;
+    process_asm_in_place(&asm, false, false, ';')?;
+    let content = fs::read_to_string(&asm)?;
+    if content != "; header\nmov rax, rbx\n" {
+        bail!("in-place rewrite produced {content:?}");
+    }
+    let mode = fs::metadata(&asm)?.permissions().mode() & 0o777;
+    if mode != 0o640 {
+        bail!("in-place rewrite changed permissions to {mode:o}");
+    }
+    let leftovers = fs::read_dir(tmp.path())?.count();
+    if leftovers != 1 {
+        bail!("in-place rewrite left {leftovers} entries behind");
+    }
+    Ok(())
+}
+
+fn test_comment_line_continuation(_h: &Harness) -> Result<()> {
+    let tmp = TempDir::new()?;
+    let asm = tmp.path().join("continued.asm");
+    fs::write(
+        &asm,
+        b"%define X a \\\nb ; note\nmov rax, 1 ; old \\\nmov rbx, 2\nmov rcx, 3\n; wrapped \\\ncomment\n",
+    )?;
+    let dest = tmp.path().join("out/continued.asm");
+    process_one_asm(&asm, &dest, false, ';')?;
+    let content = fs::read_to_string(dest)?;
+    let expected = [
+        "%define X a \\",
+        "b",
+        "mov rax, 1",
+        "",
+        "mov rcx, 3",
+        "; wrapped \\",
+        "comment",
+    ];
+    let lines: Vec<&str> = content.lines().collect();
+    if lines != expected {
+        bail!("line continuation mishandled: {lines:?}");
+    }
+    Ok(())
+}
+
+fn test_drop_empty_lines(_h: &Harness) -> Result<()> {
+    let source = "; header\nmov rax, rbx ; trailing\n\n    ; indented\n  \nlabel: nop ; x \\\nstill comment\nret\n";
+    let (kept, _) = strip_asm_source(source, false, ';');
+    let kept: Vec<&str> = kept.lines().collect();
+    let expected_kept = [
+        "; header",
+        "mov rax, rbx",
+        "",
+        "    ; indented",
+        "",
+        "label: nop",
+        "",
+        "ret",
+    ];
+    if kept != expected_kept {
+        bail!("default run changed shape: {kept:?}");
+    }
+    let (dropped, _) = strip_asm_source(source, true, ';');
+    let dropped: Vec<&str> = dropped.lines().collect();
+    let expected_dropped = ["mov rax, rbx", "", "", "label: nop", "ret"];
+    if dropped != expected_dropped {
+        bail!("--drop-empty output: {dropped:?}");
+    }
+    Ok(())
+}
+
+fn test_process_asm_diff(_h: &Harness) -> Result<()> {
+    let clean = "; header\nmov rax, rbx\n";
+    let (processed, _) = strip_asm_source(clean, false, ';');
+    if let Some(diff) = asm_unified_diff("clean.asm", clean, &processed) {
+        bail!("clean source reported as changed:\n{diff}");
+    }
+    let dirty = "; header\nmov rax, rbx ; trailing\nret\n";
+    let (processed, _) = strip_asm_source(dirty, false, ';');
+    let diff = asm_unified_diff("wcat/dirty.asm", dirty, &processed).context("dirty source not reported")?;
+    let expected = "--- a/wcat/dirty.asm\n\
+                    +++ b/wcat/dirty.asm\n\
+                    @@ -1,3 +1,3 @@\n\
+                    \x20; header\n\
+                    -mov rax, rbx ; trailing\n\
+                    +mov rax, rbx\n\
+                    \x20ret\n";
+    if diff != expected {
+        bail!("diff:\n{diff}expected:\n{expected}");
+    }
+    let (dropped, _) = strip_asm_source(clean, true, ';');
+    if asm_unified_diff("clean.asm", clean, &dropped).is_none() {
+        bail!("--diff --drop-empty missed a comment-only line");
+    }
+    Ok(())
+}
+
+fn test_custom_comment_char(_h: &Harness) -> Result<()> {
+    let source = "# header\nmovq %rax, %rbx # trailing\nmsg: .ascii \"a#b\" # note\nlabel: nop ; not a comment here\n";
+    let (out, stats) = strip_asm_source(source, false, '#');
+    let lines: Vec<&str> = out.lines().collect();
+    let expected = [
+        "# header",
+        "movq %rax, %rbx",
+        "msg: .ascii \"a#b\"",
+        "label: nop ; not a comment here",
+    ];
+    if lines != expected {
+        bail!("--comment-char='#' output: {lines:?}");
+    }
+    if (stats.comment_only, stats.trailing) != (1, 2) {
+        bail!("--comment-char='#' stats: {stats:?}");
+    }
+    let (dropped, _) = strip_asm_source("@ arm header\nmov r0, r1 @ copy\n", true, '@');
+    if dropped != "mov r0, r1\n" {
+        bail!("--comment-char='@' --drop-empty output: {dropped:?}");
+    }
+    for bad in ["", "##", " ", "\"", "\\"] {
+        if parse_comment_char(bad).is_ok() {
+            bail!("--comment-char accepted {bad:?}");
+        }
+    }
+    Ok(())
+}
+
+fn test_toolchain_selection(_h: &Harness) -> Result<()> {
+    let tmp = TempDir::new()?;
+    let dir = tmp.path();
+    if pick_toolchain(dir, true, true).is_ok() {
+        bail!("picked a toolchain without any source");
+    }
+    fs::write(dir.join("wcat.asm"), b"")?;
+    if pick_toolchain(dir, true, true)? != Toolchain::Nasm {
+        bail!("nasm should win when wcat.asm is present");
+    }
+    let err = pick_toolchain(dir, false, true).err().context("missing nasm without wcat.s accepted")?;
+    let msg = format!("{err}");
+    if !msg.contains("install nasm") || !msg.contains("--wcat") {
+        bail!("unhelpful toolchain error: {err}");
+    }
+    fs::write(dir.join("wcat.s"), b"")?;
+    if pick_toolchain(dir, false, true)? != Toolchain::Gas {
+        bail!("GNU as should be used when nasm is absent");
+    }
+    if pick_toolchain(dir, false, false).is_ok() {
+        bail!("picked a toolchain with no assembler installed");
+    }
+    Ok(())
 }
 
-fn test_only_newlines_file_s(h: &Harness) -> Result<()> {
-    let path = h.fixtures.dir.path().join("only_newlines.txt");
-    fs::write(&path, b"\n\n\n\n")?;
-    h.compare_with_cat(&["-s", path.to_str().unwrap()], None)
+fn test_linker_selection(_h: &Harness) -> Result<()> {
+    let dir = Path::new(".");
+    let ld = link_command(None, dir)?;
+    if ld.get_args().collect::<Vec<_>>() != ["-o", "wcat", "wcat.o"] {
+        bail!("default link step changed: {ld:?}");
+    }
+    if which::which("cc").is_ok() {
+        let cc = link_command(Some("cc"), dir)?;
+        if !cc.get_args().any(|arg| arg == "-nostdlib") {
+            bail!("cc link step must skip the C runtime: {cc:?}");
+        }
+    }
+    let err = link_command(Some("gold-plated"), dir).err().context("unknown linker accepted")?;
+    if !format!("{err}").contains("WCAT_LINKER") {
+        bail!("unhelpful linker error: {err}");
+    }
+    Ok(())
 }
 
-fn test_stdin_only_newlines_s(h: &Harness) -> Result<()> {
-    h.compare_with_cat(&["-s", "-"], Some(b"\n\n\n\n"))
+fn test_rebuild_uses_content_hash(_h: &Harness) -> Result<()> {
+    let linker = std::env::var("WCAT_LINKER").unwrap_or_else(|_| "ld".to_string());
+    if which::which("nasm").is_err() || which::which(&linker).is_err() {
+        println!("[SKIP] nasm or {linker} not available");
+        return Ok(());
+    }
+    let tmp = TempDir::new()?;
+    let wcat_dir = tmp.path().join("wcat");
+    fs::create_dir(&wcat_dir)?;
+    let asm = wcat_dir.join("wcat.asm");
+    let source = repo_root()?.join("wcat/wcat.asm");
+    fs::copy(&source, &asm)?;
+    let binary = wcat_dir.join("wcat");
+    if !ensure_wcat_built(tmp.path(), &binary)? {
+        bail!("fresh tree was not built");
+    }
+    if ensure_wcat_built(tmp.path(), &binary)? {
+        bail!("rebuilt without any change");
+    }
+    let later = std::time::SystemTime::now() + std::time::Duration::from_secs(3600);
+    File::options().write(true).open(&asm)?.set_modified(later)?;
+    if ensure_wcat_built(tmp.path(), &binary)? {
+        bail!("touching wcat.asm without editing it triggered a rebuild");
+    }
+    fs::OpenOptions::new().append(true).open(&asm)?.write_all(b"; edited\n")?;
+    if !ensure_wcat_built(tmp.path(), &binary)? {
+        bail!("content change did not trigger a rebuild");
+    }
+    Ok(())
 }
 
-fn test_stdin_empty_numbered(h: &Harness) -> Result<()> {
-    h.compare_with_cat(&["-n", "-"], Some(&[]))
+/// A failing build step reports its command line, status and stderr, not
+/// just that something failed.
+fn test_build_step_error(_h: &Harness) -> Result<()> {
+    let mut failing = Command::new("sh");
+    failing.args(["-c", "echo 'wcat.asm:12: error: bad operand' >&2; exit 3"]);
+    let err = format!("{:#}", run_status(failing).err().context("failing step succeeded")?);
+    for needle in ["sh -c echo", "exit status: 3", "wcat.asm:12: error: bad operand"] {
+        if !err.contains(needle) {
+            bail!("build error lacks {needle:?}: {err}");
+        }
+    }
+    let mut passing = Command::new("sh");
+    passing.args(["-c", "exit 0"]);
+    run_status(passing)
 }
 
-fn test_binary_show_tabs(h: &Harness) -> Result<()> {
-    h.compare_with_cat(&["-T", h.fixtures.binary.to_str().unwrap()], None)
+fn test_asm_comment_stats(_h: &Harness) -> Result<()> {
+    let source = "; header\nmov rax, rbx ; trailing\nlabel: nop\nmsg db \"a;b\" ; note\n";
+    let (_, stats) = strip_asm_source(source, false, ';');
+    let expected = AsmCommentStats {
+        lines: 4,
+        comment_only: 1,
+        trailing: 2,
+        saved_bytes: " ; trailing".len() + " ; note".len(),
+    };
+    if stats != expected {
+        bail!("comment stats {stats:?}, expected {expected:?}");
+    }
+    let table = render_asm_stats(&[("a.asm".to_string(), stats), ("b.asm".to_string(), stats)]);
+    let rows: Vec<&str> = table.lines().collect();
+    if rows.len() != 4 || !rows[3].starts_with("TOTAL") || !rows[3].ends_with(&format!(" {}", 2 * expected.saved_bytes)) {
+        bail!("unexpected stats table:\n{table}");
+    }
+    if rows.iter().any(|row| row.len() != rows[0].len()) {
+        bail!("stats table columns are not aligned:\n{table}");
+    }
+    Ok(())
 }
 
-fn test_binary_show_ends(h: &Harness) -> Result<()> {
-    h.compare_with_cat(&["-E", h.fixtures.binary.to_str().unwrap()], None)
+fn test_comment_in_string_literal(_h: &Harness) -> Result<()> {
+    let tmp = TempDir::new()?;
+    let asm = tmp.path().join("strings.asm");
+    fs::write(
+        &asm,
+        b"msg db \"hello; world\", 0 ; greeting\nsemi db ';' ; lone semicolon\nesc db `it\\`s; fine`, 0 ; escaped backquote\nmixed db \"it's; ok\" ; quote inside other quote\n",
+    )?;
+    let dest = tmp.path().join("out/strings.asm");
+    process_one_asm(&asm, &dest, false, ';')?;
+    let content = fs::read_to_string(dest)?;
+    let expected = [
+        "msg db \"hello; world\", 0",
+        "semi db ';'",
+        "esc db `it\\`s; fine`, 0",
+        "mixed db \"it's; ok\"",
+    ];
+    let lines: Vec<&str> = content.lines().collect();
+    if lines != expected {
+        bail!("string literals mangled: {lines:?}");
+    }
+    Ok(())
 }
 
-fn test_fifo_squeeze_blank(h: &Harness) -> Result<()> {
-    let fifo = h.fixtures.dir.path().join("squeeze.fifo");
-    mkfifo(&fifo, nix::sys::stat::Mode::from_bits_truncate(0o644))?;
-    let data = b"one\n\n\n\nthree\n";
-    let fifo_path = fifo.to_str().unwrap();
-    compare_fifo_outputs(h, &fifo, &["-s", fifo_path], data, "fifo squeeze")
+// --------------------- Helpers --------------------------------------------
+/// Calls per syscall from a `strace -c` summary table (the `total` row too).
+/// Rows are `% time, seconds, usecs/call, calls, [errors], syscall`.
+fn parse_strace_summary(summary: &str) -> HashMap<String, u64> {
+    summary
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let name = fields.last()?;
+            let calls = fields.get(3)?.parse().ok()?;
+            fields[0].parse::<f64>().ok()?;
+            Some((name.to_string(), calls))
+        })
+        .collect()
 }
 
-fn test_fifo_show_ends(h: &Harness) -> Result<()> {
-    let fifo = h.fixtures.dir.path().join("show_ends.fifo");
-    mkfifo(&fifo, nix::sys::stat::Mode::from_bits_truncate(0o644))?;
-    let data = b"one\n\n";
-    let fifo_path = fifo.to_str().unwrap();
-    compare_fifo_outputs(h, &fifo, &["-E", fifo_path], data, "fifo show ends")
+/// Which assembler/source pair builds wcat in this environment.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Toolchain {
+    /// `nasm -f elf64 wcat.asm` (the reference source)
+    Nasm,
+    /// `as --64 wcat.s` for hosts without nasm but with a GAS-syntax port
+    Gas,
 }
 
-fn test_fifo_number_show_ends(h: &Harness) -> Result<()> {
-    let fifo = h.fixtures.dir.path().join("num_ends.fifo");
-    mkfifo(&fifo, nix::sys::stat::Mode::from_bits_truncate(0o644))?;
-    let data = b"one\n\n";
-    let fifo_path = fifo.to_str().unwrap();
-    compare_fifo_outputs(h, &fifo, &["-nE", fifo_path], data, "fifo numbered ends")
-}
+impl Toolchain {
+    fn source(self) -> &'static str {
+        match self {
+            Toolchain::Nasm => "wcat.asm",
+            Toolchain::Gas => "wcat.s",
+        }
+    }
 
-fn test_fifo_show_all(h: &Harness) -> Result<()> {
-    let fifo = h.fixtures.dir.path().join("show_all.fifo");
-    mkfifo(&fifo, nix::sys::stat::Mode::from_bits_truncate(0o644))?;
-    let data = b"tab\t\x01\n";
-    let fifo_path = fifo.to_str().unwrap();
-    compare_fifo_outputs(h, &fifo, &["-A", fifo_path], data, "fifo show all")
+    fn assemble_command(self, wcat_dir: &Path) -> Command {
+        let mut cmd = match self {
+            Toolchain::Nasm => {
+                let mut cmd = Command::new("nasm");
+                cmd.args(["-f", "elf64", "wcat.asm", "-o", "wcat.o"]);
+                cmd
+            }
+            Toolchain::Gas => {
+                let mut cmd = Command::new("as");
+                cmd.args(["--64", "wcat.s", "-o", "wcat.o"]);
+                cmd
+            }
+        };
+        cmd.current_dir(wcat_dir);
+        cmd
+    }
 }
 
-fn test_fifo_show_tabs_ends(h: &Harness) -> Result<()> {
-    let fifo = h.fixtures.dir.path().join("show_tabs_ends.fifo");
-    mkfifo(&fifo, nix::sys::stat::Mode::from_bits_truncate(0o644))?;
-    let data = b"one\tend\n\n";
-    let fifo_path = fifo.to_str().unwrap();
-    compare_fifo_outputs(
-        h,
-        &fifo,
-        &["-ET", fifo_path],
-        data,
-        "fifo show tabs and ends",
+/// Prefer nasm on wcat.asm; fall back to GNU as when nasm is missing and a
+/// GAS-syntax wcat.s sits next to it.
+fn pick_toolchain(wcat_dir: &Path, have_nasm: bool, have_as: bool) -> Result<Toolchain> {
+    let has_asm = wcat_dir.join(Toolchain::Nasm.source()).exists();
+    let has_s = wcat_dir.join(Toolchain::Gas.source()).exists();
+    if have_nasm && has_asm {
+        return Ok(Toolchain::Nasm);
+    }
+    if have_as && has_s {
+        return Ok(Toolchain::Gas);
+    }
+    if !have_nasm && has_asm {
+        bail!(
+            "cannot build wcat: `nasm` was not found in PATH. Install it (e.g. `apt install nasm`, \
+             `dnf install nasm` or `pacman -S nasm`), or run the suite against a prebuilt binary \
+             with `cargo run -- tests --wcat <path>`"
+        );
+    }
+    bail!(
+        "cannot build wcat: need nasm with {} ({}), or GNU as with {} ({}); nasm {}, as {}. \
+         Use `cargo run -- tests --wcat <path>` to test a prebuilt binary",
+        Toolchain::Nasm.source(),
+        if has_asm { "present" } else { "missing" },
+        Toolchain::Gas.source(),
+        if has_s { "present" } else { "missing" },
+        if have_nasm { "found" } else { "not found" },
+        if have_as { "found" } else { "not found" }
     )
 }
 
-fn test_fifo_number_nonblank(h: &Harness) -> Result<()> {
-    let fifo = h.fixtures.dir.path().join("num_nonblank.fifo");
-    mkfifo(&fifo, nix::sys::stat::Mode::from_bits_truncate(0o644))?;
-    let data = b"one\n\nthree\n";
-    let fifo_path = fifo.to_str().unwrap();
-    compare_fifo_outputs(h, &fifo, &["-b", fifo_path], data, "fifo number nonblank")
+/// Build the link step selected by `WCAT_LINKER` (`ld` by default, or `cc`).
+/// wcat is a freestanding pure-syscall binary, so `cc` is told to skip the C
+/// runtime and link statically.
+fn link_command(linker: Option<&str>, wcat_dir: &Path) -> Result<Command> {
+    let linker = linker.unwrap_or("ld");
+    let args: &[&str] = match linker {
+        "ld" => &["-o", "wcat", "wcat.o"],
+        "cc" => &["-nostdlib", "-static", "-no-pie", "-o", "wcat", "wcat.o"],
+        other => bail!("unsupported WCAT_LINKER {other:?}: expected \"ld\" or \"cc\""),
+    };
+    let program = which::which(linker).with_context(|| {
+        format!(
+            "linker `{linker}` not found in PATH. Install binutils (for `ld`) or a C compiler \
+             (for `cc`), pick another one with WCAT_LINKER, or test a prebuilt binary with \
+             `cargo run -- tests --wcat <path>`"
+        )
+    })?;
+    let mut cmd = Command::new(program);
+    cmd.current_dir(wcat_dir).args(args);
+    Ok(cmd)
 }
 
-fn test_comment_preservation(_h: &Harness) -> Result<()> {
-    let tmp = TempDir::new()?;
-    let asm = tmp.path().join("sample.asm");
-    fs::write(
-        &asm,
-        b";only comment\nmov rax, rbx ; trailing\n  ; indented comment\nlabel: nop\n",
-    )?;
-    let out_dir = tmp.path().join("out");
-    process_one_asm(&asm, &out_dir.join("sample.asm"))?;
-    let content = fs::read_to_string(out_dir.join("sample.asm"))?;
-    let lines: Vec<&str> = content.lines().collect();
-    if lines[0].trim_start() != ";only comment" {
-        bail!("comment-only line removed");
-    }
-    if !lines[1].contains("mov rax, rbx") || lines[1].contains(";") {
-        bail!("trailing comment not stripped correctly");
-    }
-    if lines[2].trim_start() != "; indented comment" {
-        bail!("indented comment line lost");
-    }
-    Ok(())
+/// Hex SHA-256 of `path`, used to decide whether wcat needs a rebuild.
+fn source_hash(path: &Path) -> Result<String> {
+    let digest = Sha256::digest(fs::read(path)?);
+    Ok(digest.iter().map(|b| format!("{b:02x}")).collect())
 }
 
-// --------------------- Helpers --------------------------------------------
-fn ensure_wcat_built(root: &Path, binary: &Path) -> Result<()> {
-    let asm = root.join("wcat/wcat.asm");
-    let obj = root.join("wcat/wcat.o");
+/// Rebuild wcat when the binary/object is missing or the source content no
+/// longer matches `wcat/.build-hash`; mtimes are ignored so checkouts that only
+/// touch files don't force a rebuild.  Returns whether a build ran.
+fn ensure_wcat_built(root: &Path, binary: &Path) -> Result<bool> {
+    let wcat_dir = root.join("wcat");
+    let toolchain = pick_toolchain(
+        &wcat_dir,
+        which::which("nasm").is_ok(),
+        which::which("as").is_ok(),
+    )?;
+    let source = wcat_dir.join(toolchain.source());
+    let obj = wcat_dir.join("wcat.o");
+    let hash_file = wcat_dir.join(".build-hash");
+    let hash = source_hash(&source)?;
     let rebuild = !binary.exists()
         || !obj.exists()
-        || fs::metadata(&asm)?.modified()?
-            > fs::metadata(binary)
-                .ok()
-                .and_then(|m| m.modified().ok())
-                .unwrap_or(std::time::SystemTime::UNIX_EPOCH);
+        || fs::read_to_string(&hash_file).ok().as_deref() != Some(hash.as_str());
     if rebuild {
-        println!("[build] assembling wcat");
-        let mut nasm = Command::new("nasm");
-        nasm.current_dir(root.join("wcat"))
-            .args(["-f", "elf64", "wcat.asm", "-o", "wcat.o"]);
-        run_status(nasm)?;
-        let mut ld = Command::new("ld");
-        ld.current_dir(root.join("wcat"))
-            .args(["-o", "wcat", "wcat.o"]);
-        run_status(ld)?;
+        println!("[build] assembling wcat from {}", toolchain.source());
+        run_status(toolchain.assemble_command(&wcat_dir))?;
+        let linker = std::env::var("WCAT_LINKER").ok();
+        run_status(link_command(linker.as_deref(), &wcat_dir)?)?;
+        fs::write(&hash_file, &hash)?;
     }
-    Ok(())
+    Ok(rebuild)
+}
+
+/// How a child ended, as (exit code, terminating signal). `code()` alone is
+/// `None` for every signal, so a SIGPIPE death would equal a SIGSEGV one.
+type Exit = (Option<i32>, Option<i32>);
+
+fn exit_of(status: &std::process::ExitStatus) -> Exit {
+    (status.code(), status.signal())
 }
 
 #[derive(Clone)]
@@ -2484,7 +6636,21 @@ struct CmdOutput {
 }
 
 fn run_cmd(cmd: &Path, args: &[&str], stdin_data: Option<&[u8]>) -> Result<CmdOutput> {
-    run_cmd_with_arg0(cmd, args, stdin_data, None)
+    run_cmd_with_arg0(cmd, args, stdin_data, None, &[])
+}
+
+/// Start every run from the same environment: only PATH survives from the
+/// host, so a stray WCAT_OPTIONS, POSIXLY_CORRECT or locale cannot make wcat
+/// and cat disagree. LC_ALL=C is set because a localized cat translates its
+/// diagnostics and --help. `envs` is then applied identically to whichever
+/// binary runs.
+fn clean_env(command: &mut Command, envs: &[(&str, &str)]) {
+    command.env_clear();
+    if let Some(path) = std::env::var_os("PATH") {
+        command.env("PATH", path);
+    }
+    command.env("LC_ALL", "C");
+    command.envs(envs.iter().copied());
 }
 
 fn run_cmd_with_arg0(
@@ -2492,12 +6658,14 @@ fn run_cmd_with_arg0(
     args: &[&str],
     stdin_data: Option<&[u8]>,
     arg0_override: Option<&Path>,
+    envs: &[(&str, &str)],
 ) -> Result<CmdOutput> {
     let mut command = Command::new(cmd);
     if let Some(arg0) = arg0_override {
         command.arg0(arg0);
     }
     command.args(args);
+    clean_env(&mut command, envs);
     if stdin_data.is_some() {
         command.stdin(Stdio::piped());
     }
@@ -2514,7 +6682,7 @@ fn run_cmd_with_arg0(
     } else {
         None
     };
-    let output = child.wait_with_output()?;
+    let output = wait_with_deadline(child, cmd, args)?;
     if let Some(writer) = stdin_writer {
         writer.join().unwrap()?;
     }
@@ -2535,19 +6703,92 @@ fn run_cmd_with_arg0(
     })
 }
 
+fn child_timeout() -> std::time::Duration {
+    std::time::Duration::from_secs(CHILD_TIMEOUT_SECS.load(Ordering::Relaxed))
+}
+
+/// `wait_with_output`, but SIGKILL the child and fail if it outlives the
+/// --timeout deadline, so a hung wcat fails its case instead of the suite.
+fn wait_with_deadline(
+    child: std::process::Child,
+    cmd: &Path,
+    args: &[&str],
+) -> Result<std::process::Output> {
+    let pid = Pid::from_raw(child.id() as i32);
+    let (done_tx, done_rx) = std::sync::mpsc::channel::<()>();
+    let watcher = std::thread::spawn(move || {
+        if done_rx.recv_timeout(child_timeout()).is_err() {
+            let _ = kill(pid, Signal::SIGKILL);
+            return true;
+        }
+        false
+    });
+    let output = child.wait_with_output();
+    let _ = done_tx.send(());
+    if watcher.join().unwrap() {
+        bail!(
+            "timeout: {cmd:?} {args:?} still running after {}s",
+            CHILD_TIMEOUT_SECS.load(Ordering::Relaxed)
+        );
+    }
+    Ok(output?)
+}
+
+/// Run `cmd` with stdout and stderr dup'd onto the same pipe and return the
+/// interleaved bytes in the order they were written.
+fn run_cmd_merged(
+    cmd: &Path,
+    args: &[&str],
+    arg0: &Path,
+) -> Result<(Vec<u8>, std::process::ExitStatus)> {
+    let (mut reader, writer) = std::io::pipe()?;
+    let mut command = Command::new(cmd);
+    command
+        .arg0(arg0)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(writer.try_clone()?)
+        .stderr(writer);
+    clean_env(&mut command, &[]);
+    let child = command
+        .spawn()
+        .with_context(|| format!("spawning {cmd:?}"))?;
+    // The Command still holds the write ends; drop them so the reader sees EOF.
+    drop(command);
+    let drain = std::thread::spawn(move || -> std::io::Result<Vec<u8>> {
+        let mut merged = Vec::new();
+        reader.read_to_end(&mut merged)?;
+        Ok(merged)
+    });
+    let output = wait_with_deadline(child, cmd, args)?;
+    let merged = drain.join().unwrap()?;
+    if VERBOSE.load(Ordering::Relaxed) {
+        println!(
+            "[CMD ] {:?} {:?} -> status {:?}, merged stdout+stderr {}B",
+            cmd,
+            args,
+            output.status.code(),
+            merged.len()
+        );
+    }
+    Ok((merged, output.status))
+}
+
 fn run_cmd_to_file(
     cmd: &Path,
     args: &[&str],
     stdin_data: Option<&[u8]>,
     arg0_override: Option<&Path>,
+    envs: &[(&str, &str)],
     output_path: &Path,
-) -> Result<std::process::ExitStatus> {
+) -> Result<CmdOutput> {
     let stdout_file = File::create(output_path)?;
     let mut command = Command::new(cmd);
     if let Some(arg0) = arg0_override {
         command.arg0(arg0);
     }
     command.args(args);
+    clean_env(&mut command, envs);
     if stdin_data.is_some() {
         command.stdin(Stdio::piped());
     }
@@ -2564,7 +6805,7 @@ fn run_cmd_to_file(
     } else {
         None
     };
-    let output = child.wait_with_output()?;
+    let output = wait_with_deadline(child, cmd, args)?;
     if let Some(writer) = stdin_writer {
         writer.join().unwrap()?;
     }
@@ -2578,25 +6819,114 @@ fn run_cmd_to_file(
             output.stderr.len()
         );
     }
-    Ok(output.status)
+    Ok(CmdOutput {
+        status: output.status,
+        stdout: Vec::new(),
+        stderr: output.stderr,
+    })
 }
 
 fn compare_outputs(actual: CmdOutput, expected: CmdOutput, label: &str) -> Result<()> {
     if actual.stdout != expected.stdout
         || actual.stderr != expected.stderr
-        || actual.status.code() != expected.status.code()
+        || exit_of(&actual.status) != exit_of(&expected.status)
     {
-        bail!(
-            "{label} mismatch\nstdout diff? {}\nstderr diff? {}\nstatus {:?} vs {:?}",
-            actual.stdout != expected.stdout,
-            actual.stderr != expected.stderr,
-            actual.status.code(),
-            expected.status.code()
-        );
+        bail!("{label} mismatch\n{}", mismatch_report(&actual, &expected));
     }
     Ok(())
 }
 
+/// Describe how a wcat run differs from cat's: a located diff for each of
+/// stdout and stderr that differs, then the statuses if they disagree.
+fn mismatch_report(wcat: &CmdOutput, cat: &CmdOutput) -> String {
+    let mut report = String::new();
+    if wcat.stdout != cat.stdout {
+        report += &describe_diff("stdout", ("wcat", &wcat.stdout), ("cat", &cat.stdout));
+    }
+    if wcat.stderr != cat.stderr {
+        report += &describe_diff("stderr", ("wcat", &wcat.stderr), ("cat", &cat.stderr));
+    }
+    if exit_of(&wcat.status) != exit_of(&cat.status) {
+        report += &format!("status: wcat {} vs cat {}\n", wcat.status, cat.status);
+    }
+    report
+}
+
+/// Bytes of context shown on each side of the first difference.
+const DIFF_WINDOW: usize = 8;
+/// Longest stretch of a line shown on either side of the first difference.
+const DIFF_LINE_CONTEXT: usize = 40;
+
+/// Locate the first byte where two named buffers differ and describe it
+/// without dumping either one: the offset with its line and column, the
+/// diverging line from each side with a caret under the difference (unless
+/// the line holds NUL bytes), and a hex window around it. A buffer that ends
+/// first shows `EOF` at that offset.
+fn describe_diff(what: &str, left: (&str, &[u8]), right: (&str, &[u8])) -> String {
+    let ((left_name, left), (right_name, right)) = (left, right);
+    let at = left.iter().zip(right).take_while(|(a, b)| a == b).count();
+    if at == left.len() && at == right.len() {
+        return format!("{what}: identical ({} bytes)\n", left.len());
+    }
+    // The prefix is shared, so both sides agree on where the line starts.
+    let line_start = left[..at].iter().rposition(|&b| b == b'\n').map_or(0, |nl| nl + 1);
+    let line = left[..at].iter().filter(|&&b| b == b'\n').count() + 1;
+    let mut report = format!(
+        "{what} differs at byte {at} (line {line}, column {}); {left_name} {}B, {right_name} {}B\n",
+        at - line_start + 1,
+        left.len(),
+        right.len()
+    );
+    let width = left_name.len().max(right_name.len());
+    let line_end = |buf: &[u8]| {
+        buf[line_start..].iter().position(|&b| b == b'\n').map_or(buf.len(), |nl| line_start + nl)
+    };
+    let (left_end, right_end) = (line_end(left), line_end(right));
+    if !left[line_start..left_end].contains(&0) && !right[line_start..right_end].contains(&0) {
+        let mut caret = 0;
+        for (name, buf, end) in [(left_name, left, left_end), (right_name, right, right_end)] {
+            let head = format!("  {name:<width$} line {line}: ");
+            let (text, column) = render_line(&buf[line_start..end], at - line_start);
+            report += &format!("{head}{text}\n");
+            caret = head.len() + column;
+        }
+        report += &format!("{}^\n", " ".repeat(caret));
+    }
+    let from = at.saturating_sub(DIFF_WINDOW);
+    for (name, buf) in [(left_name, left), (right_name, right)] {
+        report += &format!("  {name:<width$} @{from:#x}:");
+        for offset in from..=at + DIFF_WINDOW {
+            match buf.get(offset) {
+                Some(byte) if offset == at => report += &format!(" [{byte:02x}]"),
+                Some(byte) => report += &format!(" {byte:02x}"),
+                None if offset == at => report += " [EOF]",
+                None => break,
+            }
+        }
+        report.push('\n');
+    }
+    report
+}
+
+/// Escape one line for display, keeping at most `DIFF_LINE_CONTEXT` bytes on
+/// each side of byte `at`; returns the text and the column `at` starts in.
+fn render_line(line: &[u8], at: usize) -> (String, usize) {
+    let escape = |bytes: &[u8]| bytes.escape_ascii().to_string();
+    let mut text = String::new();
+    let head_from = at.saturating_sub(DIFF_LINE_CONTEXT);
+    if head_from > 0 {
+        text += "...";
+    }
+    text += &escape(&line[head_from..at]);
+    let column = text.len();
+    let tail_to = line.len().min(at + DIFF_LINE_CONTEXT);
+    text += &escape(&line[at..tail_to]);
+    if tail_to < line.len() {
+        text += "...";
+    }
+    (text, column)
+}
+
 fn run_fifo_cmd(
     cmd: &Path,
     args: &[&str],
@@ -2610,7 +6940,7 @@ fn run_fifo_cmd(
         fs::write(&fifo_writer, &data)?;
         Ok(())
     });
-    let out = run_cmd_with_arg0(cmd, args, None, arg0_override)?;
+    let out = run_cmd_with_arg0(cmd, args, None, arg0_override, &[])?;
     writer.join().unwrap()?;
     Ok(out)
 }
@@ -2622,16 +6952,16 @@ fn run_fifo_cmd_to_file(
     data: &[u8],
     arg0_override: Option<&Path>,
     output_path: &Path,
-) -> Result<std::process::ExitStatus> {
+) -> Result<CmdOutput> {
     let fifo_writer = fifo.to_path_buf();
     let data = data.to_vec();
     let writer = std::thread::spawn(move || -> Result<()> {
         fs::write(&fifo_writer, &data)?;
         Ok(())
     });
-    let status = run_cmd_to_file(cmd, args, None, arg0_override, output_path)?;
+    let out = run_cmd_to_file(cmd, args, None, arg0_override, &[], output_path)?;
     writer.join().unwrap()?;
-    Ok(status)
+    Ok(out)
 }
 
 fn compare_fifo_outputs(
@@ -2669,70 +6999,327 @@ fn compare_fifo_output_files(
     let cat_bytes = fs::read(cat_file.path())?;
     if wcat_bytes != cat_bytes {
         bail!(
-            "{label} file output mismatch (wcat {}B vs cat {}B)",
-            wcat_bytes.len(),
-            cat_bytes.len()
+            "{label} file output mismatch\n{}",
+            describe_diff("file", ("wcat", &wcat_bytes), ("cat", &cat_bytes))
         );
     }
     Ok(())
 }
 
+/// Run a build step to completion. Its output is captured: warnings are
+/// passed on to stderr, and on failure the error carries the command line,
+/// exit status and everything the tool printed. `--verbose` also logs each
+/// command line and how long it took.
 fn run_status(mut cmd: Command) -> Result<()> {
-    let status = cmd.status()?;
-    if !status.success() {
-        bail!("command failed: {:?}", cmd);
+    let line = command_line(&cmd);
+    let started = Instant::now();
+    let output = cmd.output().with_context(|| format!("spawning `{line}`"))?;
+    if VERBOSE.load(Ordering::Relaxed) {
+        println!("[CMD ] {line} ({:.1} ms)", started.elapsed().as_secs_f64() * 1000.0);
+    }
+    if !output.status.success() {
+        bail!(
+            "`{line}` failed ({}):\n{}{}",
+            output.status,
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
     }
+    std::io::stderr().write_all(&output.stderr)?;
     Ok(())
 }
 
-fn process_asm(output: PathBuf) -> Result<()> {
-    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+/// A command as it would be typed, for logs and errors.
+fn command_line(cmd: &Command) -> String {
+    std::iter::once(cmd.get_program())
+        .chain(cmd.get_args())
+        .map(|part| part.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn repo_root() -> Result<PathBuf> {
+    Ok(PathBuf::from(env!("CARGO_MANIFEST_DIR"))
         .parent()
         .context("expected test/ to have parent")?
-        .to_path_buf();
+        .to_path_buf())
+}
+
+/// Every `.asm` file below `root`, in a stable order.
+fn asm_files(root: &Path) -> Vec<PathBuf> {
+    WalkDir::new(root)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .filter(|entry| entry.path().extension().and_then(|s| s.to_str()) == Some("asm"))
+        .map(|entry| entry.into_path())
+        .collect()
+}
+
+fn process_asm(output: PathBuf, check: bool, in_place: bool, drop_empty: bool, comment: char) -> Result<()> {
+    let root = repo_root()?;
     let output = root.join(output);
-    for entry in WalkDir::new(&root).into_iter().filter_map(Result::ok) {
-        if !entry.file_type().is_file() {
-            continue;
-        }
-        if entry.path().extension().and_then(|s| s.to_str()) != Some("asm") {
+    for path in asm_files(&root) {
+        let rel = path.strip_prefix(&root).unwrap();
+        if in_place {
+            process_asm_in_place(&path, check, drop_empty, comment)
+                .with_context(|| format!("rewriting {}", rel.display()))?;
+            println!("Rewrote: {}", rel.display());
             continue;
         }
-        let rel = entry.path().strip_prefix(&root).unwrap();
         let dest = output.join(rel);
         if let Some(parent) = dest.parent() {
             fs::create_dir_all(parent)?;
         }
-        process_one_asm(entry.path(), &dest)?;
+        process_one_asm(&path, &dest, drop_empty, comment)?;
         println!("Processed: {} -> {}", rel.display(), dest.display());
+        if check {
+            check_processed_asm(&path, &dest)
+                .with_context(|| format!("checking {}", rel.display()))?;
+        }
+    }
+    Ok(())
+}
+
+/// CI gate: report every .asm file that processing would rewrite as a
+/// unified diff, touching nothing, and fail when any would change.
+fn diff_asm(drop_empty: bool, comment: char) -> Result<()> {
+    let root = repo_root()?;
+    let files = asm_files(&root);
+    let mut dirty = 0;
+    for path in &files {
+        let rel = path.strip_prefix(&root).unwrap().display().to_string();
+        let content = fs::read_to_string(path).with_context(|| format!("reading {rel}"))?;
+        let (processed, _) = strip_asm_source(&content, drop_empty, comment);
+        if let Some(diff) = asm_unified_diff(&rel, &content, &processed) {
+            print!("{diff}");
+            dirty += 1;
+        }
+    }
+    if dirty > 0 {
+        bail!("{dirty} of {} .asm files would change", files.len());
+    }
+    println!("Clean: {} .asm files", files.len());
+    Ok(())
+}
+
+/// `diff -u` style hunks turning `original` into `processed`, or None when
+/// processing leaves the file as it is.
+fn asm_unified_diff(rel: &str, original: &str, processed: &str) -> Option<String> {
+    if original == processed {
+        return None;
+    }
+    Some(
+        similar::TextDiff::from_lines(original, processed)
+            .unified_diff()
+            .header(&format!("a/{rel}"), &format!("b/{rel}"))
+            .to_string(),
+    )
+}
+
+fn asm_stats() -> Result<()> {
+    let root = repo_root()?;
+    let mut rows = Vec::new();
+    for path in asm_files(&root) {
+        let content = fs::read_to_string(&path)?;
+        let (_, stats) = strip_asm_source(&content, false, ';');
+        rows.push((path.strip_prefix(&root).unwrap().display().to_string(), stats));
+    }
+    print!("{}", render_asm_stats(&rows));
+    Ok(())
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct AsmCommentStats {
+    lines: usize,
+    comment_only: usize,
+    trailing: usize,
+    saved_bytes: usize,
+}
+
+impl AsmCommentStats {
+    fn add(&mut self, other: &AsmCommentStats) {
+        self.lines += other.lines;
+        self.comment_only += other.comment_only;
+        self.trailing += other.trailing;
+        self.saved_bytes += other.saved_bytes;
+    }
+}
+
+fn render_asm_stats(rows: &[(String, AsmCommentStats)]) -> String {
+    let mut total = AsmCommentStats::default();
+    for (_, stats) in rows {
+        total.add(stats);
+    }
+    let width = rows
+        .iter()
+        .map(|(name, _)| name.len())
+        .chain(["File".len(), "TOTAL".len()])
+        .max()
+        .unwrap_or(0);
+    let mut out = format!(
+        "{:<width$}  {:>7}  {:>12}  {:>8}  {:>11}\n",
+        "File", "Lines", "Comment-only", "Trailing", "Saved bytes"
+    );
+    for (name, stats) in rows.iter().map(|(n, s)| (n.as_str(), s)).chain([("TOTAL", &total)]) {
+        out.push_str(&format!(
+            "{:<width$}  {:>7}  {:>12}  {:>8}  {:>11}\n",
+            name, stats.lines, stats.comment_only, stats.trailing, stats.saved_bytes
+        ));
+    }
+    out
+}
+
+/// Process `src` into a temp sibling and rename it over the original, so an
+/// interrupted run never leaves a half-written source behind.
+fn process_asm_in_place(src: &Path, check: bool, drop_empty: bool, comment: char) -> Result<()> {
+    let parent = src.parent().context("source has no parent directory")?;
+    let tmp = NamedTempFile::new_in(parent)?;
+    process_one_asm(src, tmp.path(), drop_empty, comment)?;
+    if check {
+        check_processed_asm(src, tmp.path())?;
+    }
+    fs::set_permissions(tmp.path(), fs::metadata(src)?.permissions())?;
+    tmp.persist(src)?;
+    Ok(())
+}
+
+/// Assemble `processed` to a scratch object, but only when `original` itself
+/// assembles; sources that never built are reported and skipped.
+fn check_processed_asm(original: &Path, processed: &Path) -> Result<()> {
+    let nasm = which::which("nasm")
+        .context("nasm not found in PATH: --check re-assembles processed files with it")?;
+    let tmp = TempDir::new()?;
+    let obj = tmp.path().join("check.o");
+    let mut probe = Command::new(&nasm);
+    probe
+        .args(["-f", "elf64"])
+        .arg(original)
+        .arg("-o")
+        .arg(&obj)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    if !probe.status()?.success() {
+        println!("Skipped check: {} does not assemble as-is", original.display());
+        return Ok(());
     }
+    let mut assemble = Command::new(&nasm);
+    assemble.args(["-f", "elf64"]).arg(processed).arg("-o").arg(&obj);
+    run_status(assemble)
+        .with_context(|| format!("processed file {} no longer assembles", processed.display()))?;
+    println!("Checked: {}", processed.display());
     Ok(())
 }
 
-fn process_one_asm(src: &Path, dest: &Path) -> Result<()> {
+fn process_one_asm(src: &Path, dest: &Path, drop_empty: bool, comment: char) -> Result<()> {
     let content = fs::read_to_string(src)?;
+    let (out, _) = strip_asm_source(&content, drop_empty, comment);
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(dest, out)?;
+    Ok(())
+}
+
+/// Strip trailing comments (from `comment` on, `;` for NASM) from assembly
+/// source, keeping comment-only lines, and count what was found along the way.  With `drop_empty`, comment-only lines
+/// are removed too and any line left empty by stripping is omitted; lines that
+/// were already blank (or whitespace-only) in the source are always kept.
+fn strip_asm_source(content: &str, drop_empty: bool, comment: char) -> (String, AsmCommentStats) {
     let mut out = String::with_capacity(content.len());
-    for line in content.lines() {
-        let trimmed_lead = line.trim_start();
-        if trimmed_lead.starts_with(';') {
-            out.push_str(line.trim_end_matches(['\r', '\n']));
-            out.push('\n');
+    let mut stats = AsmCommentStats::default();
+    let mut lines = content.lines();
+    while let Some(first) = lines.next() {
+        if first.trim_start().starts_with(comment) {
+            // Comment-only lines stay verbatim, including any lines a
+            // trailing backslash pulls into the same comment.
+            let mut line = first;
+            loop {
+                stats.lines += 1;
+                stats.comment_only += 1;
+                if !drop_empty {
+                    out.push_str(line.trim_end_matches(['\r', '\n']));
+                    out.push('\n');
+                }
+                if !ends_with_continuation(line) {
+                    break;
+                }
+                match lines.next() {
+                    Some(next) => line = next,
+                    None => break,
+                }
+            }
             continue;
         }
-        let mut buf = String::new();
-        for ch in line.chars() {
-            if ch == ';' {
+        // A trailing `\` joins physical lines into one logical line, so string
+        // and comment state carry over; each physical line is still emitted
+        // on its own line to keep the continuation (and line numbers) intact.
+        let mut scanner = AsmScanner {
+            comment,
+            ..AsmScanner::default()
+        };
+        let mut in_comment = false;
+        let mut line = first;
+        loop {
+            stats.lines += 1;
+            let continued = ends_with_continuation(line);
+            let mut kept = "";
+            if !in_comment {
+                kept = match scanner.comment_start(line) {
+                    Some(idx) => {
+                        stats.trailing += 1;
+                        in_comment = true;
+                        line[..idx].trim_end_matches([' ', '\t'])
+                    }
+                    None => line.trim_end_matches([' ', '\t', '\r', '\n']),
+                };
+            }
+            if !(drop_empty && kept.is_empty() && !line.trim().is_empty()) {
+                out.push_str(kept);
+                out.push('\n');
+            }
+            if !continued || scanner.quote.is_some() {
                 break;
             }
-            buf.push(ch);
+            match lines.next() {
+                Some(next) => line = next,
+                None => break,
+            }
         }
-        let cleaned = buf.trim_end_matches([' ', '\t', '\r', '\n']).to_string();
-        out.push_str(&cleaned);
-        out.push('\n');
     }
-    if let Some(parent) = dest.parent() {
-        fs::create_dir_all(parent)?;
+    stats.saved_bytes = content.len().saturating_sub(out.len());
+    (out, stats)
+}
+
+fn ends_with_continuation(line: &str) -> bool {
+    line.trim_end_matches('\r').ends_with('\\')
+}
+
+/// Tracks NASM string-literal state while looking for a comment start.
+/// NASM only honours backslash escapes inside backquoted strings, so a `\`
+/// in a '...' or "..." literal is an ordinary character.
+#[derive(Default)]
+struct AsmScanner {
+    comment: char,
+    quote: Option<char>,
+    escaped: bool,
+}
+
+impl AsmScanner {
+    /// Byte offset of the first comment character outside a string literal, if any.
+    fn comment_start(&mut self, line: &str) -> Option<usize> {
+        for (idx, ch) in line.char_indices() {
+            match self.quote {
+                Some('`') if self.escaped => self.escaped = false,
+                Some('`') if ch == '\\' => self.escaped = true,
+                Some(q) if ch == q => self.quote = None,
+                Some(_) => {}
+                None if ch == self.comment => return Some(idx),
+                None if matches!(ch, '\'' | '"' | '`') => self.quote = Some(ch),
+                None => {}
+            }
+        }
+        None
     }
-    fs::write(dest, out)?;
-    Ok(())
 }
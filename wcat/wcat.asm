@@ -4,7 +4,22 @@
 ; Supported GNU options: -n, -b, -s, -v, -E/-T and the composites -A/-e/-t,
 ; plus long options --number, --number-nonblank, --squeeze-blank, --show-ends,
 ; --show-tabs, --show-nonprinting, and --show-all.
+; wcat-only extensions: -w WIDTH (-wWIDTH, also last in a cluster: -nw6),
+; --squeeze-limit=K, --stats, --checksum, --null,
+; --line-range=A,B, --preallocate, --progress[=always], --files-from=PATH,
+; --binary-safe, --merge-stdin, --since-mtime=EPOCH, --quiet-errors,
+; --ignore-missing, --reverse, --dedupe, --color[=WHEN],
+; --nonprinting-style=caret|hex, --buffer=line|full, --dry-run,
+; --count-only[=lines|lines,bytes], --epipe-exit[=CODE],
+; --strip-trailing-cr, --repeat=N, --headers, --tab-marker=STR,
+; --ensure-newline, --number-width=WIDTH, --pager, --byte-offset,
+; --end-marker=STR, --normalize, --exit-ok; a mandatory long option value
+; may also be the next word (--repeat 3); default options may be given in
+; $WCAT_OPTIONS. With $POSIXLY_CORRECT set, the first operand ends the
+; options, as it does for cat.
 ; All other flags fall back to the same error message GNU cat would print.
+; wcat is locale-agnostic: LANG/LC_* are never read, -v treats every byte
+; as it would in the C locale, and the --version date is a fixed string.
 ; -----------------------------------------------------------------------------
 
 ; --- Linux syscall numbers we rely on ----------------------------------------
@@ -24,6 +39,15 @@
 %define SYS_fadvise64   221            ; syscall number for posix_fadvise()
 %define SYS_splice      275            ; syscall number for splice()
 %define SYS_pipe2       293            ; syscall number for pipe2()
+%define SYS_poll        7              ; syscall number for poll()
+%define SYS_fallocate   285            ; syscall number for fallocate()
+%define SYS_ioctl       16             ; syscall number for ioctl()
+%define SYS_mremap      25             ; syscall number for mremap()
+%define SYS_rt_sigaction 13            ; syscall number for rt_sigaction()
+%define SYS_dup2        33             ; syscall number for dup2()
+%define SYS_fork        57             ; syscall number for fork()
+%define SYS_execve      59             ; syscall number for execve()
+%define SYS_wait4       61             ; syscall number for wait4()
 
 %define EINTR           4              ; errno for interrupted syscall
 %define EAGAIN          11             ; errno for would-block / try again
@@ -47,10 +71,24 @@
 %define EROFS           30             ; errno for read-only filesystem
 %define EFBIG           27             ; errno for file too large
 %define ENOSPC          28             ; errno for no space left on device
+%define EPERM           1              ; errno for operation not permitted
+%define ENXIO           6              ; errno for no such device (e.g. a socket)
+%define EBUSY           16             ; errno for device or resource busy
+%define ENODEV          19             ; errno for no such device
+%define ETXTBSY         26             ; errno for text file busy
 
 %define PROT_READ       1              ; mmap protection: read
+%define PROT_WRITE      2              ; mmap protection: write
 %define MAP_SHARED      1              ; mmap mapping: shared
 %define MAP_PRIVATE     2              ; mmap mapping: private
+%define MAP_ANONYMOUS   0x20           ; mmap mapping: not backed by a file
+%define MREMAP_MAYMOVE  1              ; mremap: the mapping may be relocated
+
+%define POLLIN          1              ; poll: data available to read
+%define FALLOC_FL_KEEP_SIZE 1          ; fallocate: reserve blocks, size unchanged
+%define MFD_CLOEXEC     1              ; memfd_create: close-on-exec
+%define TCGETS          0x5401         ; ioctl: get termios (fails on non-ttys)
+%define PROGRESS_STEP   (64 << 20)     ; --progress: report every 64 MiB written
 
 %define SEEK_SET        0              ; lseek: from start
 %define SEEK_CUR        1              ; lseek: from current position
@@ -58,6 +96,9 @@
 
 ; --- Misc constants ----------------------------------------------------------
 %define AT_FDCWD        -100          ; openat() “current working dir”
+%define PATH_MAX        4096          ; longest path open() accepts, NUL included
+%define LIST_BUF_SIZE   65536         ; --files-from: read() chunk for the name list
+%define HOLD_MIN_CAP    65536         ; --dedupe/--normalize: first hold allocation
 %define BUFFER_SIZE     262144        ; I/O buffer size (256 KiB chunk to cut syscalls)
 %define SENDFILE_CHUNK  1048576       ; how much we ask kernel to move at once
 %define CFR_CHUNK_MIN   262144        ; minimum chunk size for copy_file_range path (256 KiB)
@@ -70,8 +111,10 @@
 %define STAT_MODE_OFFSET 24           ; offset of st_mode inside struct stat
 %define STAT_SIZE_OFFSET 48           ; offset of st_size in struct stat
 %define STAT_BLKSIZE_OFFSET 56        ; offset of st_blksize in struct stat
+%define STAT_MTIME_OFFSET 88          ; offset of st_mtime (seconds) in struct stat
 %define S_IFMT          0xF000        ; mask for file type bits
 %define S_IFREG         0x8000        ; regular file bit pattern
+%define S_IFDIR         0x4000        ; directory bit pattern
 %define S_IFCHR         0x2000        ; character device bit pattern
 %define S_IFIFO         0x1000        ; FIFO / pipe bit pattern
 %define S_IFSOCK        0xC000        ; socket bit pattern
@@ -84,10 +127,45 @@
 %define OPT_SQUEEZE_BLANK    16       ; -s: squeeze multiple blank lines
 %define OPT_SHOW_NONPRINTING 32       ; -v: show nonprinting chars visibly
 
+; Bit-mask flags for wcat-only extensions (kept apart from the GNU set above)
+%define EXT_STATS             1       ; --stats: summary on stderr at exit
+%define EXT_CHECKSUM          2       ; --checksum: CRC32 of stdout on stderr at exit
+%define EXT_NULL              4       ; --null: write NUL instead of newline
+%define EXT_LINE_RANGE        8       ; --line-range: only emit lines A..B
+%define EXT_PREALLOCATE      16       ; --preallocate: reserve stdout blocks first
+%define EXT_PROGRESS         32       ; --progress: running byte count on stderr
+%define EXT_BINARY_SAFE      64       ; --binary-safe (stdout is a tty): refuse NULs
+%define EXT_MERGE_STDIN     128       ; --merge-stdin: every "-" replays all of stdin
+%define EXT_SINCE_MTIME     256       ; --since-mtime: skip files modified before EPOCH
+%define EXT_QUIET_ERRORS    512       ; --quiet-errors: no per-file diagnostics
+%define EXT_IGNORE_MISSING 1024       ; --ignore-missing: ENOENT operands are no-ops
+%define EXT_REVERSE        2048       ; --reverse: each file's lines last to first
+%define EXT_DEDUPE         4096       ; --dedupe: drop a line equal to the one before
+%define EXT_COLOR          8192       ; --color: line numbers in ANSI green
+%define EXT_HEX_NONPRINTING 16384     ; --nonprinting-style=hex: -v writes \xNN
+%define EXT_LINE_BUFFERED 32768       ; --buffer=line: flush stdout at each line end
+%define EXT_DRY_RUN       65536       ; --dry-run: check operands, copy nothing
+%define EXT_COUNT_ONLY   131072       ; --count-only: print the -n/-b line count only
+%define EXT_COUNT_BYTES  262144       ; --count-only=lines,bytes: and the bytes read
+%define EXT_EPIPE_EXIT   524288       ; --epipe-exit: exit with a code on a broken pipe
+%define EXT_STRIP_CR    1048576       ; --strip-trailing-cr: drop the CR of each CRLF
+%define EXT_REPEAT      2097152       ; --repeat: copy each operand N times
+%define EXT_HEADERS     4194304       ; --headers: "==> FILE <==" before each operand
+%define EXT_ENSURE_NL   8388608       ; --ensure-newline: end the output with a line end
+%define EXT_COLOR_ERRORS 16777216     ; --color: file diagnostics in ANSI red
+%define EXT_PAGER      33554432       ; --pager: stdout is a terminal, send it to $PAGER
+%define EXT_BYTE_OFFSET 67108864      ; --byte-offset: hex input offset before each line
+%define EXT_END_MARKER 134217728      ; --end-marker: -E writes end_marker, not $
+%define EXT_NORMALIZE  268435456      ; --normalize: -s, and trailing whitespace dropped
+%define EXT_EXIT_OK    536870912      ; --exit-ok: operand errors still exit 0
+%define EXT_REWRITE    (EXT_NULL | EXT_LINE_RANGE | EXT_STRIP_CR | EXT_ENSURE_NL | EXT_BYTE_OFFSET | EXT_END_MARKER) ; extensions that change stdout bytes
+%define EXT_PASSIVE    (EXT_PREALLOCATE | EXT_MERGE_STDIN | EXT_SINCE_MTIME | EXT_QUIET_ERRORS | EXT_IGNORE_MISSING | EXT_COLOR | EXT_HEX_NONPRINTING | EXT_LINE_BUFFERED | EXT_EPIPE_EXIT | EXT_REPEAT | EXT_HEADERS | EXT_PAGER | EXT_EXIT_OK) ; extensions the plain copy paths honour
+%define MAX_NUMBER_WIDTH 32           ; -w limit: a padded number fits numbuf
+%define CRC32_POLY   0xEDB88320       ; reflected IEEE 802.3 polynomial
+
 ; --- Read-only data ----------------------------------------------------------
 section .rodata                  ; read-only data section
-err_prefix      db "cat: ",0                 ; common diagnostics prefix
-err_open_sep    db ": ",0                    ; separator before strerror text
+err_open_sep    db ": ",0                    ; after argv[0] and before strerror text
 err_enoent      db "No such file or directory",0
 err_eacces      db "Permission denied",0
 err_eisdir      db "Is a directory",0
@@ -103,9 +181,17 @@ err_einval      db "Invalid argument",0
 err_ebadf       db "Bad file descriptor",0
 err_efbig       db "File too large",0
 err_enomem      db "Cannot allocate memory",0
+err_eperm       db "Operation not permitted",0
+err_enxio       db "No such device or address",0
+err_ebusy       db "Device or resource busy",0
+err_enodev      db "No such device",0
+err_etxtbsy     db "Text file busy",0
+err_epipe       db "Broken pipe",0
 err_unknown     db "Unknown error",0
-err_write_prefix db "cat: write error: ",0
+err_write_mid   db ": write error: ",0
 err_invalid_option_mid db ": invalid option -- '",0
+err_short_needs_arg_mid db ": option requires an argument -- '",0
+err_invalid_width_mid db ": invalid line number field width: '",0
 err_unrecognized_option_mid db ": unrecognized option '",0
 err_option_close db "'",10,0
 err_try_prefix db "Try '",0
@@ -113,7 +199,31 @@ err_try_suffix db " --help' for more information.",10,0
 err_option_arg_mid db ": option '",0
 err_option_arg_dashes db "--",0
 err_option_arg_tail db "' doesn't allow an argument",10,0
+err_option_needs_arg_tail db "' requires an argument",10,0
+err_invalid_arg_mid db ": invalid argument '",0
+err_invalid_arg_for db "' for '--",0
+err_option_conflict_mid db "' cannot be combined with '--",0
+dry_run_ok      db ": ok",10,0               ; --dry-run: operand would be copied
+err_env_not_option db ": WCAT_OPTIONS may only hold options, not '",0
+err_env_files_from db ": WCAT_OPTIONS may not hold '--files-from'",10,0
+env_wcat_options db "WCAT_OPTIONS",0          ; matched up to the '='
+env_posixly_correct db "POSIXLY_CORRECT",0
+env_pager       db "PAGER",0
+pager_shell     db "/bin/sh",0               ; runs $PAGER, which may hold arguments
+pager_shell_c   db "-c",0
+pager_less      db "/usr/bin/less",0         ; without $PAGER
+pager_less_alt  db "/bin/less",0
+pager_less_arg0 db "less",0
+stats_bytes_mid db ": ",0                   ; --stats summary pieces
+stats_lines_mid db " bytes, ",0
+stats_longest_mid db " lines, longest line ",0
+stats_tail      db " bytes",10,0
+checksum_mid    db ": crc32 ",0              ; --checksum summary prefix
+progress_mid    db ": progress ",0           ; --progress line pieces
+binary_refused_tail db ": binary file not written to a terminal (--binary-safe)",10,0
+hex_digits      db "0123456789abcdef"
 newline         db 10,0                     ; newline string
+space_str       db " ",0                    ; separator for --count-only=lines,bytes
 help_keyword    db "help",0                 ; "--help" keyword
 version_keyword db "version",0              ; "--version" keyword
 long_number     db "number",0
@@ -123,20 +233,144 @@ long_show_ends  db "show-ends",0
 long_show_tabs  db "show-tabs",0
 long_show_nonprinting db "show-nonprinting",0
 long_show_all   db "show-all",0
-help_text       db "Usage: wcat [OPTION]... [FILE]...",10             ; help text header
-                db "Concatenate FILEs, or standard input, to standard output.",10,10
-                db "  -b        number nonempty output lines",10
-                db "  -n        number all output lines",10
-                db "  -s        squeeze multiple blank lines",10
-                db "  -E        show $ at end of each line",10
-                db "  -T        show TAB characters as ^I",10
-                db "  -v        use ^ and M- notation, -A/-e/-t behave like GNU cat",10
-                db "  -u        (ignored for compatibility)",10
-                db "      --help     display this help and exit",10
-                db "      --version  output version information and exit",10,0
+long_squeeze_limit db "squeeze-limit",0
+long_stats      db "stats",0
+long_checksum   db "checksum",0
+long_null       db "null",0
+long_line_range db "line-range",0
+long_preallocate db "preallocate",0
+long_progress   db "progress",0
+long_files_from db "files-from",0
+long_binary_safe db "binary-safe",0
+long_merge_stdin db "merge-stdin",0
+long_since_mtime db "since-mtime",0
+long_quiet_errors db "quiet-errors",0
+long_ignore_missing db "ignore-missing",0
+long_reverse    db "reverse",0
+long_dedupe     db "dedupe",0
+long_color      db "color",0
+color_lines     db "lines",0
+color_auto      db "auto",0
+color_never     db "never",0
+long_nonprinting_style db "nonprinting-style",0
+style_caret     db "caret",0
+style_hex       db "hex",0
+long_buffer     db "buffer",0
+long_dry_run    db "dry-run",0
+long_count_only db "count-only",0
+count_lines     db "lines",0
+count_lines_bytes db "lines,bytes",0
+long_epipe_exit db "epipe-exit",0
+long_strip_trailing_cr db "strip-trailing-cr",0
+long_repeat     db "repeat",0
+long_headers    db "headers",0
+header_open     db "==> ",0                 ; --headers: tail -v's header line
+header_close    db " <==",10,0
+header_stdin    db "standard input",0       ; --headers: how "-" is named
+long_tab_marker db "tab-marker",0
+long_ensure_newline db "ensure-newline",0
+long_number_width db "number-width",0
+long_pager      db "pager",0
+long_byte_offset db "byte-offset",0
+long_end_marker db "end-marker",0
+long_normalize  db "normalize",0
+long_exit_ok    db "exit-ok",0
+tab_marker_default db "^I"                  ; what -T writes for a tab, as cat does
+cr_byte         db 13                        ; --strip-trailing-cr: a held CR, replayed
+buffer_line     db "line",0
+buffer_full     db "full",0
+progress_always db "always",0
+color_number_on db 27,"[32m"                ; --color: before a line number
+color_number_off db 27,"[0m",9              ; --color: after it, then the tab
+color_error_on  db 27,"[31m",0              ; --color: before a file diagnostic
+color_error_off db 27,"[0m",10,0            ; --color: after it, then the newline
+sigpipe_ignore  dq 1, 0, 0, 0                ; --epipe-exit: sigaction {SIG_IGN}
+long_value_options:                          ; long options with a mandatory value,
+                dq long_squeeze_limit, long_line_range ; which may be the next word
+                dq long_files_from, long_since_mtime
+                dq long_nonprinting_style, long_buffer
+                dq long_repeat, long_tab_marker, long_number_width
+                dq long_end_marker
+                dq 0
+help_text       db "Usage: wcat [OPTION]... [FILE]...",10             ; help text, laid out so
+                db "Concatenate FILE(s) to standard output.",10,10   ; help2man can parse it
+                db "With no FILE, or when FILE is -, read standard input.",10,10
+                db "Options:",10
+                db "  -A, --show-all           equivalent to -vET",10
+                db "  -b, --number-nonblank    number nonempty output lines, overrides -n",10
+                db "  -e                       equivalent to -vE",10
+                db "  -E, --show-ends          display $ at end of each line",10
+                db "  -n, --number             number all output lines",10
+                db "  -s, --squeeze-blank      suppress repeated empty output lines",10
+                db "  -t                       equivalent to -vT",10
+                db "  -T, --show-tabs          display TAB characters as ^I",10
+                db "  -u                       (ignored)",10
+                db "  -v, --show-nonprinting   use ^ and M- notation, except for LFD and TAB",10
+                db "      --help               display this help and exit",10
+                db "      --version            output version information and exit",10,10
+                db "Extensions:",10
+                db "  -w, --number-width=WIDTH  pad -n/-b line numbers to WIDTH (1-32)",10
+                db "                 columns, not 6",10
+                db "      --squeeze-limit=K  keep at most K consecutive blank lines",10
+                db "      --stats    print byte/line/longest-line totals to stderr",10
+                db "      --checksum  print the CRC32 of everything written to stdout",10
+                db "      --null     end output lines with NUL instead of newline",10
+                db "      --line-range=A,B  only output input lines A through B",10
+                db "      --preallocate  reserve disk space for a plain file-to-file copy",10
+                db "      --progress[=always]  report bytes written to stderr every 64 MiB",10
+                db "                 (only when stderr is a terminal, unless =always)",10
+                db "      --files-from=PATH  also read file names, one per line, from PATH",10
+                db "                 (- for stdin), at that point among the operands",10
+                db "      --binary-safe  stop instead of writing a file with NUL bytes to a terminal",10
+                db "      --merge-stdin  keep standard input so that every - operand repeats it",10
+                db "      --since-mtime=EPOCH  skip files last modified before EPOCH seconds",10
+                db "      --quiet-errors  do not report unreadable files (still exit 1)",10
+                db "      --ignore-missing  skip nonexistent files without an error",10
+                db "      --reverse  write each file's lines last to first, like tac",10
+                db "      --dedupe   drop lines identical to the line before, like uniq",10
+                db "      --normalize  drop the spaces, tabs, CRs, VTs and FFs that end each",10
+                db "                 line, then squeeze blank lines as -s does",10
+                db "      --color[=WHEN]  color -n/-b line numbers green and file errors red;",10
+                db "                 WHEN is auto (the default: each only on a terminal),",10
+                db "                 lines (line numbers only, on a terminal), always or never",10
+                db "      --nonprinting-style=STYLE  how -v shows bytes: caret (^A, M-a,",10
+                db "                 the default) or hex (\x01, \xe1)",10
+                db "      --buffer=MODE  when decorated output is written: line (at each",10
+                db "                 line end) or full (when the buffer fills, the default)",10
+                db "      --dry-run  only check each FILE: report it as ok or with the error",10
+                db "                 a real run would give; nothing is read or written",10
+                db "      --count-only[=WHAT]  print only how many lines -n (or -b) would",10
+                db "                 number; WHAT is lines (the default) or lines,bytes",10
+                db "                 to add the number of bytes read",10
+                db "      --epipe-exit[=CODE]  when the reader of stdout goes away, exit",10
+                db "                 with CODE (0-255, default 0) instead of dying of SIGPIPE",10
+                db "      --strip-trailing-cr  drop the CR of each CRLF line end before the",10
+                db "                 other options see the line; lone CRs are kept",10
+                db "      --repeat=N  copy each FILE N times in a row; pipes and",10
+                db "                 standard input are read once and replayed",10
+                db "      --headers  write a ==> FILE <== line before each FILE, like",10
+                db "                 tail -v; standard input is only named when given as -",10
+                db "      --tab-marker=STR  make -T show each TAB as STR (printable ASCII)",10
+                db "                 instead of ^I",10
+                db "      --end-marker=STR  make -E end each line with STR (printable ASCII)",10
+                db "                 instead of $",10
+                db "      --ensure-newline  end the output with a newline if the last",10
+                db "                 line has none",10
+                db "      --pager    when stdout is a terminal, show the output through",10
+                db "                 $PAGER (or less); otherwise write it directly",10
+                db "      --byte-offset  start each line with the offset of its first byte",10
+                db "                 in the input, in hex, before any -n/-b number",10
+                db "      --exit-ok  exit 0 even when a FILE could not be read; its error",10
+                db "                 is still reported",10
+                db 10,"Examples:",10
+                db "  wcat f - g  Output f's contents, then standard input, then g's contents.",10
+                db "  wcat        Copy standard input to standard output.",10,0
 version_text    db "wcat 0.1  (November 2025)",10,0  ; version string
 stdin_label     db "-",0                            ; label used for stdin
 memfd_name      db "wcat-fast",0                    ; name for memfd_create()
+stdin_memfd_name db "wcat-stdin",0                  ; --merge-stdin copy of stdin
+reverse_memfd_name db "wcat-reverse",0              ; --reverse copy of one input
+repeat_memfd_name db "wcat-repeat",0                ; --repeat copy of an unseekable input
 align 16                                           ; align following data to 16 bytes
 newline_vec     times 16 db 10                     ; 16 newlines (vector-friendly)
 align 16                                           ; align to 16 bytes
@@ -157,21 +391,72 @@ outbuf       resb BUFFER_SIZE         ; buffered stdout writer
 outpos       resq 1                   ; current byte count in outbuf
 errflag      resb 1                   ; latched open/IO error indicator
 opt_flags    resb 1                   ; combination of OPT_* bits
+ext_flags    resd 1                   ; combination of EXT_* bits
+line_sep     resb 1                   ; byte written where a newline was read
+range_skipping resb 1                 ; --line-range: inside a dropped line
+cr_held      resb 1                   ; --strip-trailing-cr: a chunk ended in CR
+range_done   resb 1                   ; --line-range: past line B, stop reading
 options_done resb 1                   ; set once “--” or first operand seen
+posixly_correct resb 1                ; $POSIXLY_CORRECT: no options after operands
 files_seen   resb 1                   ; track whether we got any file args
+headers_seen resb 1                   ; --headers: one header has been written
+read_failed  resb 1                   ; --repeat: this pass hit a read error
 line_start   resb 1                   ; true iff we’re at beginning of a line
 line_blank   resb 1                   ; true while current line has no bytes yet
-prev_blank   resb 1                   ; remembers whether previous line was blank
 alignb 8                             ; align next qword
 line_no      resq 1                   ; next line number for -n
+blank_run    resq 1                   ; consecutive blank lines emitted so far
+squeeze_limit resq 1                  ; max blank lines per run under -s (default 1)
+stat_bytes   resq 1                   ; --stats: bytes written to stdout
+progress_bytes resq 1                 ; --progress: bytes written to stdout
+stat_lines   resq 1                   ; --stats: line_sep bytes in the chunks written so far
+stat_cur_len resq 1                   ; --stats: length of the line being written
+stat_max_len resq 1                   ; --stats: longest line seen so far
+src_lines    resq 1                   ; --line-range: input lines started so far
+range_first  resq 1                   ; --line-range: first line to keep (A)
+range_last   resq 1                   ; --line-range: last line to keep (B)
+progress_next resq 1                  ; --progress: byte count of the next report
+since_mtime  resq 1                   ; --since-mtime: oldest mtime still copied
+count_bytes  resq 1                   ; --count-only: bytes read from the inputs
+repeat_count resq 1                   ; --repeat: passes over each operand
+tab_marker   resq 1                   ; -T: what a tab becomes (^I or --tab-marker)
+tab_marker_len resq 1                 ; -T: its length in bytes
+end_marker   resq 1                   ; --end-marker: what -E writes before a line end
+end_marker_len resq 1                 ; --end-marker: its length in bytes
+epipe_code   resd 1                   ; --epipe-exit: exit status on a broken pipe
+pager_pid    resd 1                   ; --pager: the pager to wait for at exit, or 0
+alignb 8                             ; align next qword
+offset_total resq 1                   ; --byte-offset: input bytes formatted so far
+offset_bias  resq 1                   ; --byte-offset: add to a chunk pointer for its offset
+cr_offset    resq 1                   ; --byte-offset: where the held CR was read
+crc_state    resd 1                   ; --checksum: running CRC32 (pre-inverted)
+crc_table    resd 256                 ; --checksum: byte-at-a-time lookup table
 numbuf       resb 64                  ; scratch buffer for decimal rendering
+number_width resq 1                   ; -w: columns the line number is padded to
+number_fast_limit resq 1              ; numbers below this use line_ascii (6 wide)
 tmp_char     resb 1                   ; preserves AL across buffer flushes
+first_chunk  resb 1                   ; --binary-safe: next read is a file's first
+stdin_saved  resb 1                   ; --merge-stdin: stdin_copy holds all of stdin
+alignb 4                             ; align next dword
+stdin_copy   resd 1                   ; --merge-stdin: memfd with stdin's bytes
+dd_have_ref  resb 1                   ; --dedupe: a previous line has been seen
+dd_diverged  resb 1                   ; --dedupe: current line already differs
+alignb 8                             ; align next qword
+dd_buf       resq 1                   ; --dedupe: reference line (mmap'd, grows)
+dd_cap       resq 1                   ; --dedupe: bytes mapped at dd_buf
+dd_ref_len   resq 1                   ; --dedupe: length of the reference line
+dd_held      resq 1                   ; --dedupe: bytes of the current line so far
+nz_buf       resq 1                   ; --normalize: held whitespace (mmap'd, grows)
+nz_cap       resq 1                   ; --normalize: bytes mapped at nz_buf
+nz_held      resq 1                   ; --normalize: whitespace held back so far
 opt_char_buf resb 2                   ; single-char buffer for option errors
 prog_name   resq 1                   ; argv[0] pointer for option diagnostics
 line_ascii   resb 7                   ; cached "######" string with trailing tab
 alignb 16                            ; 16-byte alignment
 stat_in      resb 144                 ; struct stat scratch for fast paths (input)
 stat_out     resb 144                 ; struct stat scratch for fast paths (output)
+list_buf     resb LIST_BUF_SIZE       ; --files-from: raw name list
+list_path    resb PATH_MAX            ; --files-from: current name, NUL-terminated
 
 ; --- Text segment ------------------------------------------------------------
 section .text                        ; code segment
@@ -188,28 +473,38 @@ _start:
     ; Default runtime state mirrors GNU cat startup.
     mov byte [rel errflag], 0        ; clear error flag
     mov byte [rel opt_flags], 0      ; clear options bitmask
+    mov dword [rel ext_flags], 0     ; no wcat-only extensions yet
+    mov byte [rel line_sep], 10      ; records end in newline by default
     mov byte [rel options_done], 0   ; not done parsing options yet
     mov byte [rel files_seen], 0     ; no file operands seen yet
     mov byte [rel line_start], 1     ; start at beginning of a line
     mov byte [rel line_blank], 1     ; current line considered blank initially
-    mov byte [rel prev_blank], 0     ; previous line not blank yet
+    mov qword [rel blank_run], 0     ; no blank lines emitted yet
+    mov qword [rel squeeze_limit], 1 ; -s keeps a single blank line
     mov qword [rel line_no], 1       ; start numbering at line 1
     mov dword [rel line_ascii], 0x20202020 ; "    "
     mov word  [rel line_ascii + 4], 0x3120  ; " 1"
     mov byte  [rel line_ascii + 6], 9       ; trailing tab
+    mov qword [rel number_width], 6  ; cat's "%6d\t" field
+    mov qword [rel number_fast_limit], 1000000
+    lea rax, [rel tab_marker_default]
+    mov [rel tab_marker], rax
+    mov qword [rel tab_marker_len], 2
     mov qword [rel outpos], 0        ; output buffer is empty
+    mov qword [rel count_bytes], 0   ; nothing read yet
+    mov rsi, env_posixly_correct
+    call find_env
+    test rax, rax
+    setne byte [rel posixly_correct] ; set at all, even to "", as for getopt
+    call apply_env_options           ; $WCAT_OPTIONS before any argv option
 
     ; Fast path: “wcat” with no extra args just copies stdin.
     cmp r12, 1                   ; argc == 1 ?
-    jg  .process_args            ; if >1, go parse args
-
-    xor edi, edi                ; edi = 0 (stdin fd)
-    lea rsi, [rel stdin_label]  ; rsi = "-" label for diagnostics
-    call copy_fd                ; copy stdin to stdout
-    jmp .finish                 ; then finish
+    jle .options_ready           ; yes -> straight to the stdin fallback
 
 .process_args:
-    ; Pass 1: parse options (GNU-style permutation; operands do not stop parsing).
+    ; Pass 1: parse options (GNU-style permutation; operands do not stop parsing
+    ; unless POSIXLY_CORRECT is set).
     mov rbx, 1                  ; argv index
     mov byte [rel options_done], 0
 .pass1_loop:
@@ -220,27 +515,81 @@ _start:
     jne .pass1_done             ; stop parsing after "--"
     mov al, [rsi]
     cmp al, '-'
-    jne .pass1_next             ; non-option operand
+    jne .pass1_operand          ; non-option operand
     cmp byte [rsi+1], '-'
     jne .pass1_short
     cmp byte [rsi+2], 0
     je  .pass1_end_options
-    mov rdi, rsi
-    call parse_long_option
-    jmp .pass1_next
+    jmp .pass1_option
 .pass1_short:
     cmp byte [rsi+1], 0
-    je  .pass1_next             ; "-" operand
+    je  .pass1_operand          ; "-" operand
+.pass1_option:
     mov rdi, rsi
+    xor ecx, ecx                ; rcx = next argv word, if any, for a value
+    lea rax, [rbx + 1]
+    cmp rax, r12
+    jge .pass1_parse
+    mov rcx, [r13 + rax*8]
+.pass1_parse:
+    cmp byte [rdi+1], '-'
+    je  .pass1_long
     call parse_option_string
+    add rbx, rax                ; skip a word taken as -w's value
+    jmp .pass1_next
+.pass1_long:
+    call parse_long_option
+    add rbx, rax                ; skip a word taken as the option's value
     jmp .pass1_next
 .pass1_end_options:
     mov byte [rel options_done], 1
     jmp .pass1_done
+.pass1_operand:
+    cmp byte [rel posixly_correct], 0
+    jne .pass1_done             ; POSIX: the rest are all operands
 .pass1_next:
     inc rbx
     jmp .pass1_loop
 .pass1_done:
+.options_ready:
+    ; --byte-offset counts the bytes process_buffer is handed, which are the
+    ; input only while no stage holds lines back or reorders them.
+    test dword [rel ext_flags], EXT_BYTE_OFFSET
+    je  .options_checked
+    lea rdx, [rel long_reverse]
+    test dword [rel ext_flags], EXT_REVERSE
+    jne .options_conflict
+    lea rdx, [rel long_dedupe]
+    test dword [rel ext_flags], EXT_DEDUPE
+    jne .options_conflict
+    lea rdx, [rel long_normalize]
+    test dword [rel ext_flags], EXT_NORMALIZE
+    je  .options_checked
+.options_conflict:
+    lea rsi, [rel long_byte_offset]
+    call report_option_conflict
+.options_checked:
+    ; --count-only counts what -n would number unless -b asked for nonblank.
+    test dword [rel ext_flags], EXT_COUNT_ONLY
+    je  .pass2_start
+    test byte [rel opt_flags], (OPT_NUMBER | OPT_NUMBER_NONBLANK)
+    jne .pass2_start
+    or  byte [rel opt_flags], OPT_NUMBER
+.pass2_start:
+    ; --epipe-exit: take EPIPE from write() instead of being killed by SIGPIPE.
+    test dword [rel ext_flags], EXT_EPIPE_EXIT
+    je  .pass2_begin
+    mov eax, SYS_rt_sigaction
+    mov edi, 13                  ; SIGPIPE
+    lea rsi, [rel sigpipe_ignore]
+    xor edx, edx                 ; old action not needed
+    mov r10d, 8                  ; sizeof(sigset_t)
+    syscall
+.pass2_begin:
+    test dword [rel ext_flags], EXT_PAGER
+    je  .pass2_setup
+    call start_pager             ; before any output: stdout may become a pipe
+.pass2_setup:
     ; Pass 2: process operands in original order.
     mov rbx, 1
     mov byte [rel options_done], 0
@@ -258,59 +607,65 @@ _start:
     jne .pass2_short_or_dash
     cmp byte [rsi+2], 0
     je  .pass2_end_options
-    jmp .pass2_next             ; long option, already parsed
+    jmp .pass2_long
 .pass2_short_or_dash:
     cmp byte [rsi+1], 0
     je  .pass2_operand          ; "-" operand
-    jmp .pass2_next             ; short option, already parsed
+    call short_takes_next       ; short option, already parsed; but its
+    add rbx, rax                ; -w value may be the next word
+    jmp .pass2_next
 .pass2_end_options:
     mov byte [rel options_done], 1
     jmp .pass2_next
+.pass2_long:
+    ; Long options were applied in pass 1; only --files-from has a position.
+    push rbx                    ; str_compare clobbers bl
+    push rsi
+    lea rdi, [rsi + 2]
+    mov rsi, long_files_from
+    call str_compare
+    pop rsi
+    pop rbx
+    test eax, eax
+    jne .pass2_long_skip
+    mov byte [rel files_seen], 1
+    add rsi, 13                 ; skip "--files-from="
+    cmp byte [rsi - 1], '='
+    je  .pass2_files_from
+    mov rsi, [r13 + rbx*8 + 8]  ; "--files-from PATH"
+.pass2_files_from:
+    call cat_file_list
+.pass2_long_skip:
+    mov rsi, [r13 + rbx*8]
+    call long_takes_next        ; step over a value given as the next word
+    add rbx, rax
+    jmp .pass2_next
 .pass2_operand:
     mov byte [rel files_seen], 1
+    mov al, [rel posixly_correct]
+    or  [rel options_done], al  ; POSIX: the rest are all operands
     cmp byte [rsi], '-'
     jne .proc_file
     cmp byte [rsi+1], 0
     je  .proc_stdin
 .proc_file:
-    mov eax, SYS_openat             ; openat syscall
-    mov edi, AT_FDCWD               ; dirfd = current working directory
-    ; rsi already points to the pathname
-    xor edx, edx                    ; O_RDONLY (flags=0)
-    xor r10d, r10d                  ; mode = 0 (unused)
-    syscall                         ; openat(AT_FDCWD, path, 0, 0)
-    cmp rax, 0
-    jl  .open_failed_operands       ; error -> report
-
-    mov r14, rax                    ; save fd
-    mov edi, eax                    ; edi = fd
-    ; rsi already operand pointer for diagnostics
-    call copy_fd                    ; copy file
-
-.close_retry_operands:
-    mov eax, SYS_close
-    mov edi, r14d
-    syscall
-    cmp eax, 0
-    jge .pass2_next
-    cmp eax, -EINTR
-    je  .close_retry_operands
-    cmp eax, -EAGAIN
-    je  .close_retry_operands
-    neg eax
-    mov edx, eax
-    call fatal_write_error
-
-.open_failed_operands:
-    neg rax
-    mov edx, eax
-    call report_open_error
+    call cat_path               ; open, copy, close (or report)
     jmp .pass2_next
 
 .proc_stdin:
+    test dword [rel ext_flags], EXT_DRY_RUN
+    je  .proc_stdin_copy
+    call report_dry_run_ok      ; stdin is never read ahead of time
+    jmp .pass2_next
+.proc_stdin_copy:
+    test byte [rel ext_flags], EXT_MERGE_STDIN
+    je  .proc_stdin_direct
+    call replay_stdin           ; rsi already "-"
+    jmp .pass2_next
+.proc_stdin_direct:
     xor edi, edi                ; fd 0
     ; rsi already "-"
-    call copy_fd
+    call copy_operand
 
 .pass2_next:
     inc rbx
@@ -323,17 +678,213 @@ _start:
     ; No file operands?  GNU cat falls back to stdin.
     xor edi, edi                ; fd 0
     lea rsi, [rel stdin_label]  ; label "-"
-    call copy_fd                ; copy stdin
+    test dword [rel ext_flags], EXT_DRY_RUN
+    je  .post_args_copy
+    call report_dry_run_ok
+    jmp .finish
+.post_args_copy:
+    call copy_operand           ; copy stdin
     jmp .finish                 ; then finish
 
 .finish:
+    test dword [rel ext_flags], EXT_DEDUPE
+    je  .finish_flush
+    call dedupe_finish          ; settle an unterminated last line
+.finish_flush:
+    call release_held_cr        ; input ended right after a CR: keep it
+    test dword [rel ext_flags], EXT_ENSURE_NL
+    je  .finish_write
+    cmp byte [rel line_start], 0
+    jne .finish_write           ; empty, or already ends with a line end
+    lea rsi, [rel newline]      ; as if the input had ended the line, so
+    mov ecx, 1                  ; -E marks it and --null writes a NUL
+    call process_buffer
+.finish_write:
     call flush_outbuf           ; ensure buffered output is written
+    test dword [rel ext_flags], EXT_COUNT_ONLY
+    je  .finish_progress
+    call print_count            ; the only thing --count-only writes
+.finish_progress:
+    test byte [rel ext_flags], EXT_PROGRESS
+    je  .finish_stats
+    call print_progress         ; final --progress total
+.finish_stats:
+    test byte [rel ext_flags], EXT_STATS
+    je  .finish_exit
+    call print_stats            ; --stats summary goes to stderr last
+.finish_exit:
+    test byte [rel ext_flags], EXT_CHECKSUM
+    je  .finish_checksum_done
+    call print_checksum         ; --checksum line follows any stats
+.finish_checksum_done:
     movzx edi, byte [rel errflag] ; edi = exit status (0 or 1)
+    test dword [rel ext_flags], EXT_EXIT_OK
+    je  .finish_status
+    xor edi, edi                ; --exit-ok: operand errors were only reported
+.finish_status:
     call exit_with_code         ; exit program
 
+; -----------------------------------------------------------------------------
+; find_env
+;   Input : rsi -> the name of an environment variable.
+;   Output: rax -> its value (after the '='), or 0 when it is not set.
+; -----------------------------------------------------------------------------
+find_env:
+    push rbx                     ; str_compare clobbers bl
+    mov r8, rsi                  ; r8 = name
+    lea r9, [r13 + r12*8 + 8]    ; r9 = envp, past argv's NULL
+.fe_loop:
+    mov rdi, [r9]
+    xor eax, eax
+    test rdi, rdi
+    je  .fe_done                 ; not set
+    add r9, 8
+    mov rsi, r8
+    call str_compare
+    test eax, eax
+    jne .fe_loop
+    cmp byte [rdi], '='          ; a longer name that starts the same
+    jne .fe_loop
+    lea rax, [rdi + 1]
+.fe_done:
+    pop rbx
+    ret
+
+; -----------------------------------------------------------------------------
+; apply_env_options
+;   Effect: parses the blank-separated words of $WCAT_OPTIONS (split in place)
+;           as options ahead of argv, so argv wins wherever the last spelling
+;           counts. Bad options get the usual usage errors; words that are not
+;           options at all ("file", "-", "--") are rejected the same way.
+; -----------------------------------------------------------------------------
+apply_env_options:
+    push rbx
+    push rbp
+    mov rsi, env_wcat_options
+    call find_env
+    test rax, rax
+    je  .aeo_done                ; not set
+    mov rbp, rax                 ; rbp = value after "WCAT_OPTIONS="
+    mov rdi, rbp                 ; split every word first, so that -w can
+.aeo_split:                      ; take the word after it as its value
+    mov al, [rdi]
+    test al, al
+    je  .aeo_split_done
+    cmp al, ' '
+    je  .aeo_blank
+    cmp al, 9
+    je  .aeo_blank
+    cmp al, 10
+    jne .aeo_split_next
+.aeo_blank:
+    mov byte [rdi], 0
+.aeo_split_next:
+    inc rdi
+    jmp .aeo_split
+.aeo_split_done:
+    push rdi                     ; [rsp] = end of the value
+.aeo_word:
+    cmp rbp, [rsp]
+    jae .aeo_words_done
+    cmp byte [rbp], 0
+    jne .aeo_found
+    inc rbp                      ; a blank between words
+    jmp .aeo_word
+.aeo_found:
+    mov rbx, rbp                 ; rbx = start of this word
+.aeo_scan:
+    inc rbp
+    cmp byte [rbp], 0
+    jne .aeo_scan                ; rbp = the word's terminator
+.aeo_apply:
+    cmp byte [rbx], '-'
+    jne .aeo_not_option
+    mov al, [rbx + 1]
+    test al, al
+    je  .aeo_not_option          ; "-"
+    cmp al, '-'
+    jne .aeo_option
+    cmp byte [rbx + 2], 0
+    je  .aeo_not_option          ; "--"
+.aeo_option:
+    mov rcx, rbp                 ; rcx = next word, if any, for a value
+.aeo_next_word:
+    cmp rcx, [rsp]
+    jae .aeo_no_next
+    cmp byte [rcx], 0
+    jne .aeo_parse
+    inc rcx
+    jmp .aeo_next_word
+.aeo_no_next:
+    xor ecx, ecx
+.aeo_parse:
+    mov rdi, rbx
+    cmp byte [rbx + 1], '-'
+    je  .aeo_long
+    call parse_option_string
+    jmp .aeo_parsed
+.aeo_long:
+    push rbx                     ; str_compare clobbers bl
+    lea rdi, [rbx + 2]
+    mov rsi, long_files_from
+    call str_compare
+    pop rbx
+    test eax, eax
+    je  .aeo_files_from          ; pass 2 only walks argv: refuse it here
+    mov rdi, rbx
+    call parse_long_option
+.aeo_parsed:
+    test eax, eax
+    je  .aeo_word
+.aeo_skip_value:                 ; the option took the next word: step over it
+    cmp byte [rbp], 0
+    jne .aeo_in_value
+    inc rbp
+    jmp .aeo_skip_value
+.aeo_in_value:
+    inc rbp
+    cmp byte [rbp], 0
+    jne .aeo_in_value
+    jmp .aeo_word
+.aeo_files_from:
+    mov rdi, 2                   ; fd = stderr
+    call write_prog_name
+    mov rsi, err_env_files_from
+    call write_cstr
+    mov rsi, err_try_prefix
+    call write_cstr
+    call write_prog_name
+    mov rsi, err_try_suffix
+    call write_cstr
+    mov edi, 1                   ; exit code = 1
+    call exit_with_code
+.aeo_not_option:
+    mov rdi, 2                   ; fd = stderr
+    call write_prog_name
+    mov rsi, err_env_not_option
+    call write_cstr
+    mov rsi, rbx
+    call write_cstr
+    mov rsi, err_option_close
+    call write_cstr
+    mov rsi, err_try_prefix
+    call write_cstr
+    call write_prog_name
+    mov rsi, err_try_suffix
+    call write_cstr
+    mov edi, 1                   ; exit code = 1
+    call exit_with_code
+.aeo_words_done:
+    pop rdi
+.aeo_done:
+    pop rbp
+    pop rbx
+    ret
+
 ; ----------------------------------------------------------------------------- 
 ; parse_option_string
-;   Input : rdi -> string that begins with '-'
+;   Input : rdi -> string that begins with '-'; rcx -> the word after it, or 0.
+;   Output: eax = 1 when that word was taken as -w's value, else 0.
 ;   Effect: updates opt_flags or exits on unsupported switches.
 ; -----------------------------------------------------------------------------
 parse_option_string:
@@ -365,6 +916,8 @@ parse_option_string:
     je  .set_show_ends
     cmp al, 'T'                 ; "-T"?
     je  .set_show_tabs
+    cmp al, 'w'                 ; "-w WIDTH"?
+    je  .set_width
 
     mov dil, al                 ; offending option character
     call report_bad_short_option ; unknown option -> error and exit
@@ -390,6 +943,7 @@ parse_option_string:
 
 .set_squeeze:
     or  byte [rel opt_flags], OPT_SQUEEZE_BLANK ; set squeeze flag
+    mov qword [rel squeeze_limit], 1 ; plain -s keeps one blank line
     inc rsi                      ; next char
     jmp .opt_loop                ; loop
 
@@ -428,18 +982,116 @@ parse_option_string:
     inc rsi                      ; next char
     jmp .opt_loop                ; loop
 
+.set_width:
+    xor ebx, ebx                 ; ebx = 1 once the next word is used
+    inc rsi                      ; the rest of the cluster is the value
+    cmp byte [rsi], 0
+    jne .width_value             ; -w6, -nw6
+    mov rsi, rcx                 ; -w 6
+    test rsi, rsi
+    je  .width_missing
+    mov ebx, 1
+.width_value:
+    push rsi
+    mov rdi, rsi
+    call parse_decimal
+    pop rsi                      ; rsi = value, for the diagnostic
+    test edx, edx
+    jne .width_bad
+    test rax, rax
+    je  .width_bad
+    cmp rax, MAX_NUMBER_WIDTH
+    ja  .width_bad
+    call set_number_width
+    mov eax, ebx
+    pop rbx
+    ret
+.width_missing:
+    mov dil, 'w'
+    call report_short_option_missing
+.width_bad:
+    call report_invalid_width
+
 .opt_done:
+    xor eax, eax                 ; no value taken from the next word
     pop rbx                      ; restore rbx
     ret                          ; return
 
+; -----------------------------------------------------------------------------
+; short_takes_next
+;   Input : rsi -> a short option cluster already accepted by
+;           parse_option_string.
+;   Output: eax = 1 when it ends in a bare -w, whose value is the next word.
+; -----------------------------------------------------------------------------
+short_takes_next:
+    lea rdi, [rsi + 1]
+.stn_loop:
+    mov al, [rdi]
+    test al, al
+    je  .stn_no
+    inc rdi
+    cmp al, 'w'
+    jne .stn_loop
+    xor eax, eax
+    cmp byte [rdi], 0            ; nothing attached: -w 6
+    sete al
+    ret
+.stn_no:
+    xor eax, eax
+    ret
+
+; -----------------------------------------------------------------------------
+; set_number_width
+;   Input : rax = a validated -w width (1..MAX_NUMBER_WIDTH).
+; -----------------------------------------------------------------------------
+set_number_width:
+    mov [rel number_width], rax
+    mov qword [rel number_fast_limit], 1000000
+    cmp rax, 6                   ; line_ascii only holds the 6-wide field
+    je  .snw_done
+    mov qword [rel number_fast_limit], 0
+.snw_done:
+    ret
+
+; -----------------------------------------------------------------------------
+; long_takes_next
+;   Input : rsi -> a long option ("--NAME" or "--NAME=VALUE").
+;   Output: eax = 1 when NAME is in long_value_options and has no "=VALUE",
+;           so its value is the next word.
+; -----------------------------------------------------------------------------
+long_takes_next:
+    push rbx                     ; str_compare clobbers bl
+    lea r8, [rsi + 2]            ; r8 = NAME
+    lea r9, [rel long_value_options]
+.ltn_loop:
+    mov rsi, [r9]
+    test rsi, rsi
+    je  .ltn_no
+    add r9, 8
+    mov rdi, r8
+    call str_compare
+    test eax, eax
+    jne .ltn_loop
+    mov eax, 1
+    cmp byte [rdi], '='          ; rdi stopped at '=' or the terminator
+    jne .ltn_done
+.ltn_no:
+    xor eax, eax
+.ltn_done:
+    pop rbx
+    ret
+
 ; -----------------------------------------------------------------------------
 ; parse_long_option
-;   Input : rdi -> string that begins with "--"
+;   Input : rdi -> string that begins with "--"; rcx -> the next word, or 0.
 ;   Effect: updates opt_flags or exits on unsupported long switches.
+;   Output: eax = 1 when the next word was taken as the option's value
+;           ("--repeat 3"); otherwise a value must follow '='.
 ; -----------------------------------------------------------------------------
 parse_long_option:
     push rbx                     ; save rbx
     push r12                     ; save r12
+    push 0                       ; [rsp] = 1 once the next word is the value
     mov r12, rdi                 ; r12 = full argument pointer
     lea r14, [r12 + 2]           ; r14 = pointer after "--"
     xor r11d, r11d               ; r11 = 0 means no '='
@@ -454,7 +1106,17 @@ parse_long_option:
     jmp .plo_eq_scan
 .plo_eq_found:
     mov r11, rdi                 ; r11 = pointer to '='
+    jmp .plo_named
 .plo_eq_done:
+    test rcx, rcx
+    je  .plo_named               ; no next word to take
+    mov rsi, r12
+    call long_takes_next
+    test eax, eax
+    je  .plo_named
+    lea r11, [rcx - 1]           ; as if the next word followed '='
+    mov qword [rsp], 1
+.plo_named:
 
     mov rdi, r14                 ; option name pointer
     mov rsi, help_keyword        ; "help"
@@ -504,73 +1166,1541 @@ parse_long_option:
     mov [rel opt_flags], r10b
     jmp .return
 
-.check_squeeze:
-    mov rdi, r14
-    mov rsi, long_squeeze_blank
-    call str_compare
-    test eax, eax
-    jne .check_show_ends
-    test r11, r11
-    jne .bad_long_arg
-    or  byte [rel opt_flags], OPT_SQUEEZE_BLANK
-    jmp .return
+.check_squeeze:
+    mov rdi, r14
+    mov rsi, long_squeeze_blank
+    call str_compare
+    test eax, eax
+    jne .check_show_ends
+    test r11, r11
+    jne .bad_long_arg
+    or  byte [rel opt_flags], OPT_SQUEEZE_BLANK
+    mov qword [rel squeeze_limit], 1
+    jmp .return
+
+.check_show_ends:
+    mov rdi, r14
+    mov rsi, long_show_ends
+    call str_compare
+    test eax, eax
+    jne .check_show_tabs
+    test r11, r11
+    jne .bad_long_arg
+    or  byte [rel opt_flags], OPT_SHOW_ENDS
+    jmp .return
+
+.check_show_tabs:
+    mov rdi, r14
+    mov rsi, long_show_tabs
+    call str_compare
+    test eax, eax
+    jne .check_show_nonprinting
+    test r11, r11
+    jne .bad_long_arg
+    or  byte [rel opt_flags], OPT_SHOW_TABS
+    jmp .return
+
+.check_show_nonprinting:
+    mov rdi, r14
+    mov rsi, long_show_nonprinting
+    call str_compare
+    test eax, eax
+    jne .check_show_all
+    test r11, r11
+    jne .bad_long_arg
+    or  byte [rel opt_flags], OPT_SHOW_NONPRINTING
+    jmp .return
+
+.check_show_all:
+    mov rdi, r14
+    mov rsi, long_show_all
+    call str_compare
+    test eax, eax
+    jne .check_squeeze_limit
+    test r11, r11
+    jne .bad_long_arg
+    or  byte [rel opt_flags], OPT_SHOW_NONPRINTING | OPT_SHOW_ENDS | OPT_SHOW_TABS
+    jmp .return
+
+.check_squeeze_limit:
+    mov rdi, r14
+    mov rsi, long_squeeze_limit
+    call str_compare
+    test eax, eax
+    jne .check_stats
+    test r11, r11
+    je  .missing_long_arg        ; value is mandatory
+    lea rdi, [r11 + 1]           ; rdi = text after '='
+    call parse_decimal
+    test edx, edx
+    jne .bad_long_value
+    mov [rel squeeze_limit], rax ; K blank lines per run (0 drops them all)
+    or  byte [rel opt_flags], OPT_SQUEEZE_BLANK
+    jmp .return
+
+.check_stats:
+    mov rdi, r14
+    mov rsi, long_stats
+    call str_compare
+    test eax, eax
+    jne .check_checksum
+    test r11, r11
+    jne .bad_long_arg
+    or  byte [rel ext_flags], EXT_STATS
+    jmp .return
+
+.check_checksum:
+    mov rdi, r14
+    mov rsi, long_checksum
+    call str_compare
+    test eax, eax
+    jne .check_null
+    test r11, r11
+    jne .bad_long_arg
+    test byte [rel ext_flags], EXT_CHECKSUM
+    jne .return                  ; repeated flag: table already built
+    or  byte [rel ext_flags], EXT_CHECKSUM
+    call crc32_init
+    jmp .return
+
+.check_null:
+    mov rdi, r14
+    mov rsi, long_null
+    call str_compare
+    test eax, eax
+    jne .check_line_range
+    test r11, r11
+    jne .bad_long_arg
+    or  byte [rel ext_flags], EXT_NULL
+    mov byte [rel line_sep], 0   ; NUL-terminated records
+    jmp .return
+
+.check_line_range:
+    mov rdi, r14
+    mov rsi, long_line_range
+    call str_compare
+    test eax, eax
+    jne .check_preallocate
+    test r11, r11
+    je  .missing_long_arg
+    lea rdi, [r11 + 1]           ; rdi = "A,B"
+    mov r9b, ','
+    call parse_decimal_until
+    test edx, edx
+    jne .bad_long_value
+    cmp byte [rdi], ','          ; both bounds are required
+    jne .bad_long_value
+    test rax, rax                ; lines are 1-based
+    je  .bad_long_value
+    mov [rel range_first], rax
+    inc rdi
+    call parse_decimal
+    test edx, edx
+    jne .bad_long_value
+    cmp rax, [rel range_first]   ; empty ranges are rejected
+    jb  .bad_long_value
+    mov [rel range_last], rax
+    or  byte [rel ext_flags], EXT_LINE_RANGE
+    jmp .return
+
+.check_preallocate:
+    mov rdi, r14
+    mov rsi, long_preallocate
+    call str_compare
+    test eax, eax
+    jne .check_progress
+    test r11, r11
+    jne .bad_long_arg
+    or  byte [rel ext_flags], EXT_PREALLOCATE
+    jmp .return
+
+.check_progress:
+    mov rdi, r14
+    mov rsi, long_progress
+    call str_compare
+    test eax, eax
+    jne .check_files_from
+    test r11, r11
+    je  .progress_tty            ; bare flag: only for a terminal
+    lea rdi, [r11 + 1]
+    mov rsi, progress_always
+    call str_compare
+    test eax, eax
+    jne .bad_long_value
+    jmp .progress_on
+.progress_tty:
+    and byte [rel ext_flags], ~EXT_PROGRESS ; last spelling wins
+    mov eax, SYS_ioctl
+    mov edi, 2
+    mov esi, TCGETS
+    lea rdx, [rel stat_out]      ; termios scratch
+    syscall
+    test rax, rax
+    jl  .return                  ; stderr is not a tty: stay quiet
+.progress_on:
+    or  byte [rel ext_flags], EXT_PROGRESS
+    mov qword [rel progress_next], PROGRESS_STEP
+    jmp .return
+
+.check_files_from:
+    mov rdi, r14
+    mov rsi, long_files_from
+    call str_compare
+    test eax, eax
+    jne .check_binary_safe
+    test r11, r11
+    je  .missing_long_arg
+    cmp byte [r11 + 1], 0        ; the list is read in pass 2; just insist on a name
+    je  .bad_long_value
+    jmp .return
+
+.check_binary_safe:
+    mov rdi, r14
+    mov rsi, long_binary_safe
+    call str_compare
+    test eax, eax
+    jne .check_merge_stdin
+    test r11, r11
+    jne .bad_long_arg
+    mov eax, SYS_ioctl           ; only a terminal stdout needs guarding
+    mov edi, 1
+    mov esi, TCGETS
+    lea rdx, [rel stat_out]      ; termios scratch
+    syscall
+    test rax, rax
+    jl  .return                  ; redirected: behave exactly like cat
+    or  byte [rel ext_flags], EXT_BINARY_SAFE
+    jmp .return
+
+.check_merge_stdin:
+    mov rdi, r14
+    mov rsi, long_merge_stdin
+    call str_compare
+    test eax, eax
+    jne .check_since_mtime
+    test r11, r11
+    jne .bad_long_arg
+    or  byte [rel ext_flags], EXT_MERGE_STDIN
+    jmp .return
+
+.check_since_mtime:
+    mov rdi, r14
+    mov rsi, long_since_mtime
+    call str_compare
+    test eax, eax
+    jne .check_quiet_errors
+    test r11, r11
+    je  .missing_long_arg
+    lea rdi, [r11 + 1]           ; rdi = EPOCH
+    call parse_decimal
+    test edx, edx
+    jne .bad_long_value
+    test rax, rax                ; must fit a signed time_t
+    js  .bad_long_value
+    mov [rel since_mtime], rax
+    or  dword [rel ext_flags], EXT_SINCE_MTIME
+    jmp .return
+
+.check_quiet_errors:
+    mov rdi, r14
+    mov rsi, long_quiet_errors
+    call str_compare
+    test eax, eax
+    jne .check_ignore_missing
+    test r11, r11
+    jne .bad_long_arg
+    or  dword [rel ext_flags], EXT_QUIET_ERRORS
+    jmp .return
+
+.check_ignore_missing:
+    mov rdi, r14
+    mov rsi, long_ignore_missing
+    call str_compare
+    test eax, eax
+    jne .check_reverse
+    test r11, r11
+    jne .bad_long_arg
+    or  dword [rel ext_flags], EXT_IGNORE_MISSING
+    jmp .return
+
+.check_reverse:
+    mov rdi, r14
+    mov rsi, long_reverse
+    call str_compare
+    test eax, eax
+    jne .check_dedupe
+    test r11, r11
+    jne .bad_long_arg
+    or  dword [rel ext_flags], EXT_REVERSE
+    jmp .return
+
+.check_dedupe:
+    mov rdi, r14
+    mov rsi, long_dedupe
+    call str_compare
+    test eax, eax
+    jne .check_color
+    test r11, r11
+    jne .bad_long_arg
+    or  dword [rel ext_flags], EXT_DEDUPE
+    jmp .return
+
+.check_color:
+    mov rdi, r14
+    mov rsi, long_color
+    call str_compare
+    test eax, eax
+    jne .check_nonprinting_style
+    and dword [rel ext_flags], ~(EXT_COLOR | EXT_COLOR_ERRORS) ; last spelling wins
+    test r11, r11
+    je  .color_auto              ; bare flag: auto
+    lea rdi, [r11 + 1]
+    mov rsi, color_lines
+    call str_compare
+    test eax, eax
+    je  .color_tty               ; line numbers only
+    lea rdi, [r11 + 1]
+    mov rsi, color_auto
+    call str_compare
+    test eax, eax
+    je  .color_auto
+    lea rdi, [r11 + 1]
+    mov rsi, color_never
+    call str_compare
+    test eax, eax
+    je  .return
+    lea rdi, [r11 + 1]
+    mov rsi, progress_always
+    call str_compare
+    test eax, eax
+    jne .bad_long_value
+    or  dword [rel ext_flags], EXT_COLOR_ERRORS
+    jmp .color_on
+.color_auto:
+    mov eax, SYS_ioctl           ; diagnostics: when stderr is a terminal
+    mov edi, 2
+    mov esi, TCGETS
+    lea rdx, [rel stat_out]      ; termios scratch
+    syscall
+    test rax, rax
+    jl  .color_tty
+    or  dword [rel ext_flags], EXT_COLOR_ERRORS
+.color_tty:
+    mov eax, SYS_ioctl
+    mov edi, 1
+    mov esi, TCGETS
+    lea rdx, [rel stat_out]      ; termios scratch
+    syscall
+    test rax, rax
+    jl  .return                  ; redirected: plain -n output
+.color_on:
+    or  dword [rel ext_flags], EXT_COLOR
+    jmp .return
+
+.check_nonprinting_style:
+    mov rdi, r14
+    mov rsi, long_nonprinting_style
+    call str_compare
+    test eax, eax
+    jne .check_buffer
+    test r11, r11
+    je  .missing_long_arg
+    and dword [rel ext_flags], ~EXT_HEX_NONPRINTING
+    lea rdi, [r11 + 1]
+    mov rsi, style_caret
+    call str_compare
+    test eax, eax
+    je  .return
+    lea rdi, [r11 + 1]
+    mov rsi, style_hex
+    call str_compare
+    test eax, eax
+    jne .bad_long_value
+    or  dword [rel ext_flags], EXT_HEX_NONPRINTING
+    jmp .return
+
+.check_buffer:
+    mov rdi, r14
+    mov rsi, long_buffer
+    call str_compare
+    test eax, eax
+    jne .check_dry_run
+    test r11, r11
+    je  .missing_long_arg
+    and dword [rel ext_flags], ~EXT_LINE_BUFFERED
+    lea rdi, [r11 + 1]
+    mov rsi, buffer_full
+    call str_compare
+    test eax, eax
+    je  .return
+    lea rdi, [r11 + 1]
+    mov rsi, buffer_line
+    call str_compare
+    test eax, eax
+    jne .bad_long_value
+    or  dword [rel ext_flags], EXT_LINE_BUFFERED
+    jmp .return
+
+.check_dry_run:
+    mov rdi, r14
+    mov rsi, long_dry_run
+    call str_compare
+    test eax, eax
+    jne .check_count_only
+    test r11, r11
+    jne .bad_long_arg
+    or  dword [rel ext_flags], EXT_DRY_RUN
+    jmp .return
+
+.check_count_only:
+    mov rdi, r14
+    mov rsi, long_count_only
+    call str_compare
+    test eax, eax
+    jne .check_epipe_exit
+    and dword [rel ext_flags], ~EXT_COUNT_BYTES ; last spelling wins
+    test r11, r11
+    je  .count_on                ; bare flag: lines
+    lea rdi, [r11 + 1]
+    mov rsi, count_lines
+    call str_compare
+    test eax, eax
+    je  .count_on
+    lea rdi, [r11 + 1]
+    mov rsi, count_lines_bytes
+    call str_compare
+    test eax, eax
+    jne .bad_long_value
+    or  dword [rel ext_flags], EXT_COUNT_BYTES
+.count_on:
+    or  dword [rel ext_flags], EXT_COUNT_ONLY
+    jmp .return
+
+.check_epipe_exit:
+    mov rdi, r14
+    mov rsi, long_epipe_exit
+    call str_compare
+    test eax, eax
+    jne .check_strip_trailing_cr
+    xor eax, eax                 ; bare flag: exit 0
+    test r11, r11
+    je  .epipe_code
+    lea rdi, [r11 + 1]           ; rdi = CODE
+    call parse_decimal
+    test edx, edx
+    jne .bad_long_value
+    cmp rax, 255                 ; must be a valid exit status
+    ja  .bad_long_value
+.epipe_code:
+    mov [rel epipe_code], eax
+    or  dword [rel ext_flags], EXT_EPIPE_EXIT
+    jmp .return
+
+.check_strip_trailing_cr:
+    mov rdi, r14
+    mov rsi, long_strip_trailing_cr
+    call str_compare
+    test eax, eax
+    jne .check_repeat
+    test r11, r11
+    jne .bad_long_arg
+    or  dword [rel ext_flags], EXT_STRIP_CR
+    jmp .return
+
+.check_repeat:
+    mov rdi, r14
+    mov rsi, long_repeat
+    call str_compare
+    test eax, eax
+    jne .check_headers
+    test r11, r11
+    je  .missing_long_arg        ; N is mandatory
+    lea rdi, [r11 + 1]           ; rdi = N
+    call parse_decimal
+    test edx, edx
+    jne .bad_long_value
+    test rax, rax                ; at least one pass
+    je  .bad_long_value
+    mov [rel repeat_count], rax
+    or  dword [rel ext_flags], EXT_REPEAT
+    jmp .return
+
+.check_headers:
+    mov rdi, r14
+    mov rsi, long_headers
+    call str_compare
+    test eax, eax
+    jne .check_tab_marker
+    test r11, r11
+    jne .bad_long_arg
+    or  dword [rel ext_flags], EXT_HEADERS
+    jmp .return
+
+.check_tab_marker:
+    mov rdi, r14
+    mov rsi, long_tab_marker
+    call str_compare
+    test eax, eax
+    jne .check_ensure_newline
+    call .marker_value           ; rdi = STR, rcx = its length
+    mov [rel tab_marker], rdi
+    mov [rel tab_marker_len], rcx
+    jmp .return
+
+.check_ensure_newline:
+    mov rdi, r14
+    mov rsi, long_ensure_newline
+    call str_compare
+    test eax, eax
+    jne .check_number_width
+    test r11, r11
+    jne .bad_long_arg
+    or  dword [rel ext_flags], EXT_ENSURE_NL
+    jmp .return
+
+.check_number_width:
+    mov rdi, r14
+    mov rsi, long_number_width
+    call str_compare
+    test eax, eax
+    jne .check_pager
+    test r11, r11
+    je  .missing_long_arg
+    lea rdi, [r11 + 1]           ; rdi = WIDTH, as for -w
+    call parse_decimal
+    test edx, edx
+    jne .bad_long_value
+    test rax, rax
+    je  .bad_long_value
+    cmp rax, MAX_NUMBER_WIDTH
+    ja  .bad_long_value
+    call set_number_width
+    jmp .return
+
+.check_pager:
+    mov rdi, r14
+    mov rsi, long_pager
+    call str_compare
+    test eax, eax
+    jne .check_byte_offset
+    test r11, r11
+    jne .bad_long_arg
+    or  dword [rel ext_flags], EXT_PAGER
+    jmp .return
+
+.check_end_marker:
+    mov rdi, r14
+    mov rsi, long_end_marker
+    call str_compare
+    test eax, eax
+    jne .check_normalize
+    call .marker_value           ; rdi = STR, rcx = its length
+    mov [rel end_marker], rdi
+    mov [rel end_marker_len], rcx
+    or  dword [rel ext_flags], EXT_END_MARKER
+    jmp .return
+
+.check_normalize:
+    mov rdi, r14
+    mov rsi, long_normalize
+    call str_compare
+    test eax, eax
+    jne .check_exit_ok
+    test r11, r11
+    jne .bad_long_arg
+    or  dword [rel ext_flags], EXT_NORMALIZE
+    or  byte [rel opt_flags], OPT_SQUEEZE_BLANK
+    jmp .return
+
+.check_byte_offset:
+    mov rdi, r14
+    mov rsi, long_byte_offset
+    call str_compare
+    test eax, eax
+    jne .check_end_marker
+    test r11, r11
+    jne .bad_long_arg
+    or  dword [rel ext_flags], EXT_BYTE_OFFSET
+    jmp .return
+
+.check_exit_ok:
+    mov rdi, r14
+    mov rsi, long_exit_ok
+    call str_compare
+    test eax, eax
+    jne .unknown
+    test r11, r11
+    jne .bad_long_arg
+    or  dword [rel ext_flags], EXT_EXIT_OK
+    jmp .return
+
+.unknown:
+    mov rsi, r12                 ; rsi = full option string
+    call report_bad_long_option
+
+.return:
+    pop rax                      ; eax = 1 when the next word was taken
+    pop r12                      ; restore r12
+    pop rbx                      ; restore rbx
+    ret
+
+.bad_long_arg:
+    mov rsi, r14                 ; rsi = option name (after "--")
+    call report_long_option_argument
+
+.missing_long_arg:
+    mov rsi, r14                 ; rsi = option name (after "--")
+    call report_long_option_missing
+
+.bad_long_value:
+    mov rsi, r14                 ; rsi = option name
+    lea rdx, [r11 + 1]           ; rdx = its value
+    call report_invalid_long_value
+
+; --tab-marker/--end-marker value: non-empty printable ASCII, so the output
+; stays free of raw control bytes and the marker cannot vanish. Returns
+; rdi = STR, rcx = its length; the error paths exit, so the return address
+; they leave on the stack does not matter.
+.marker_value:
+    test r11, r11
+    je  .missing_long_arg
+    lea rdi, [r11 + 1]           ; rdi = STR
+    xor ecx, ecx                 ; rcx = its length
+.marker_scan:
+    mov al, [rdi + rcx]
+    test al, al
+    je  .marker_end
+    cmp al, 0x20
+    jb  .bad_long_value
+    cmp al, 0x7E
+    ja  .bad_long_value
+    inc rcx
+    jmp .marker_scan
+.marker_end:
+    test rcx, rcx
+    je  .bad_long_value
+    ret
+
+; -----------------------------------------------------------------------------
+; parse_decimal / parse_decimal_until
+;   Input : rdi -> NUL-terminated ASCII string; parse_decimal_until also stops
+;           at the byte in r9b.
+;   Output: rax = value, rdi -> terminator, edx = 0 on success or 1 when the
+;           number is empty, contains a non-digit, or overflows 64 bits.
+; -----------------------------------------------------------------------------
+parse_decimal:
+    xor r9d, r9d                 ; only NUL ends the number
+parse_decimal_until:
+    xor eax, eax                 ; accumulator = 0
+    cmp byte [rdi], 0            ; empty string is not a number
+    je  .pd_bad
+    cmp [rdi], r9b               ; neither is an empty field
+    je  .pd_bad
+    mov r8d, 10                  ; decimal base
+.pd_loop:
+    movzx ecx, byte [rdi]        ; next character
+    test ecx, ecx                ; end of string?
+    je  .pd_done
+    cmp cl, r9b                  ; caller's separator?
+    je  .pd_done
+    sub ecx, '0'                 ; map '0'..'9' to 0..9
+    cmp ecx, 9
+    ja  .pd_bad                  ; anything else is rejected
+    mul r8                       ; rdx:rax = rax * 10
+    jc  .pd_bad                  ; overflowed 64 bits
+    add rax, rcx                 ; append digit
+    jc  .pd_bad
+    inc rdi
+    jmp .pd_loop
+.pd_done:
+    xor edx, edx                 ; success
+    ret
+.pd_bad:
+    mov edx, 1                   ; failure
+    ret
+
+; -----------------------------------------------------------------------------
+; cat_path
+;   Input : rsi -> NUL-terminated file name.
+;   Effect: opens, copies and closes one named operand, or reports the open
+;           error the way cat does and carries on. Under --since-mtime an
+;           operand modified before the cutoff is closed without copying;
+;           under --ignore-missing a nonexistent one is passed over.
+; -----------------------------------------------------------------------------
+cat_path:
+    push r14
+    mov eax, SYS_openat          ; openat syscall
+    mov edi, AT_FDCWD            ; dirfd = current working directory
+    xor edx, edx                 ; O_RDONLY (flags=0)
+    xor r10d, r10d               ; mode = 0 (unused)
+    syscall                      ; openat(AT_FDCWD, path, 0, 0)
+    cmp rax, 0
+    jl  .cp_open_failed          ; error -> report
+    mov r14, rax                 ; save fd
+    test dword [rel ext_flags], EXT_SINCE_MTIME
+    je  .cp_copy
+    push rsi
+    mov eax, SYS_fstat
+    mov edi, r14d
+    lea rsi, [rel stat_in]
+    syscall
+    pop rsi
+    test rax, rax
+    jl  .cp_copy                 ; no mtime to judge by: copy it
+    mov rax, [rel stat_in + STAT_MTIME_OFFSET]
+    cmp rax, [rel since_mtime]
+    jl  .cp_close                ; older than EPOCH: skip silently
+.cp_copy:
+    test dword [rel ext_flags], EXT_DRY_RUN
+    jne .cp_dry_run
+    mov edi, r14d                ; edi = fd
+    call copy_operand            ; rsi still names the file for diagnostics
+    jmp .cp_close
+.cp_dry_run:
+    push rsi
+    mov eax, SYS_fstat
+    mov edi, r14d
+    lea rsi, [rel stat_in]
+    syscall
+    pop rsi
+    test rax, rax
+    jl  .cp_dry_ok               ; nothing more to learn without reading
+    mov eax, [rel stat_in + STAT_MODE_OFFSET]
+    and eax, S_IFMT
+    cmp eax, S_IFDIR
+    jne .cp_dry_ok
+    mov edx, EISDIR              ; what the first read() would return
+    call report_open_error
+    jmp .cp_close
+.cp_dry_ok:
+    call report_dry_run_ok
+.cp_close:
+    mov eax, SYS_close
+    mov edi, r14d
+    syscall
+    cmp eax, 0
+    jge .cp_done
+    cmp eax, -EINTR
+    je  .cp_close
+    cmp eax, -EAGAIN
+    je  .cp_close
+    neg eax
+    mov edx, eax
+    call fatal_write_error
+.cp_open_failed:
+    neg rax
+    mov edx, eax
+    cmp edx, ENOENT              ; --ignore-missing only forgives ENOENT
+    jne .cp_report
+    test dword [rel ext_flags], EXT_IGNORE_MISSING
+    jne .cp_done
+.cp_report:
+    call report_open_error
+.cp_done:
+    pop r14
+    ret
+
+; -----------------------------------------------------------------------------
+; cat_file_list
+;   Input : rsi -> --files-from value: a path, or "-" for stdin.
+;   Effect: reads newline-separated file names and feeds each to cat_path in
+;           order. Empty lines are skipped and the last name needs no
+;           trailing newline. Names are always files, so a "-" line opens a
+;           file called "-". A name too long for open() is reported as
+;           ENAMETOOLONG without being opened.
+; -----------------------------------------------------------------------------
+cat_file_list:
+    push rbx
+    push r12
+    push r13
+    push r14
+    push r15
+    mov rbx, rsi                 ; rbx = list name for diagnostics
+    xor r12d, r12d               ; r12 = list fd (stdin for "-")
+    cmp byte [rsi], '-'
+    jne .cfl_open
+    cmp byte [rsi+1], 0
+    je  .cfl_ready
+.cfl_open:
+    mov eax, SYS_openat
+    mov edi, AT_FDCWD
+    xor edx, edx                 ; O_RDONLY
+    xor r10d, r10d
+    syscall
+    cmp rax, 0
+    jl  .cfl_open_failed
+    mov r12, rax
+.cfl_ready:
+    xor r15d, r15d               ; r15 = length of the name being collected
+.cfl_read:
+    mov eax, SYS_read
+    mov edi, r12d
+    lea rsi, [rel list_buf]
+    mov edx, LIST_BUF_SIZE
+    syscall
+    cmp rax, 0
+    je  .cfl_eof
+    jl  .cfl_read_check
+    lea r13, [rel list_buf]      ; r13 = scan cursor
+    lea r14, [r13 + rax]         ; r14 = end of chunk
+.cfl_scan:
+    cmp r13, r14
+    je  .cfl_read
+    mov al, [r13]
+    inc r13
+    cmp al, 10
+    je  .cfl_line
+    cmp r15, PATH_MAX - 1        ; room left for this byte and the NUL?
+    jae .cfl_overflow
+    lea rdx, [rel list_path]
+    mov [rdx + r15], al
+    inc r15
+    jmp .cfl_scan
+.cfl_overflow:
+    mov r15, PATH_MAX            ; sentinel: name no longer fits
+    jmp .cfl_scan
+.cfl_line:
+    call .cfl_emit
+    jmp .cfl_scan
+
+.cfl_read_check:
+    cmp rax, -EINTR
+    je  .cfl_read
+    cmp rax, -EAGAIN
+    jne .cfl_read_error
+    mov edi, r12d
+    call wait_readable
+    jmp .cfl_read
+.cfl_read_error:
+    neg rax
+    mov edx, eax
+    mov rsi, rbx
+    call report_read_error
+    jmp .cfl_close
+
+.cfl_eof:
+    call .cfl_emit               ; unterminated last name
+.cfl_close:
+    test r12, r12                ; never close stdin
+    je  .cfl_return
+    mov eax, SYS_close
+    mov edi, r12d
+    syscall
+    jmp .cfl_return
+
+.cfl_open_failed:
+    neg rax
+    mov edx, eax
+    mov rsi, rbx
+    call report_open_error
+.cfl_return:
+    pop r15
+    pop r14
+    pop r13
+    pop r12
+    pop rbx
+    ret
+
+.cfl_emit:
+    test r15, r15                ; empty line -> nothing to do
+    je  .cfl_emit_done
+    lea rsi, [rel list_path]
+    cmp r15, PATH_MAX
+    je  .cfl_too_long
+    mov byte [rsi + r15], 0
+    call cat_path
+    jmp .cfl_emit_done
+.cfl_too_long:
+    mov byte [rsi + PATH_MAX - 1], 0
+    mov edx, ENAMETOOLONG
+    call report_open_error
+.cfl_emit_done:
+    xor r15d, r15d
+    ret
+
+; -----------------------------------------------------------------------------
+; replay_stdin
+;   Input : rsi -> "-" label for diagnostics.
+;   Effect: --merge-stdin. The first call drains stdin into a memfd; every
+;           call then copies that memfd from its start, so each "-" operand
+;           emits the same bytes. A read error is reported once and whatever
+;           arrived before it is still replayed. Without memfd support stdin
+;           is copied directly, as cat would.
+; -----------------------------------------------------------------------------
+replay_stdin:
+    push r12
+    mov r12, rsi                 ; r12 = label
+    cmp byte [rel stdin_saved], 0
+    jne .rs_rewind
+    mov eax, SYS_memfd_create
+    lea rdi, [rel stdin_memfd_name]
+    mov esi, MFD_CLOEXEC
+    syscall
+    cmp rax, 0
+    jl  .rs_direct               ; no memfd: a single pass like plain cat
+    mov [rel stdin_copy], eax
+    mov byte [rel stdin_saved], 1
+    xor edi, edi                 ; stdin
+    mov esi, eax
+    mov rdx, r12
+    call spool_fd
+.rs_rewind:
+    mov eax, SYS_lseek
+    mov edi, [rel stdin_copy]
+    xor esi, esi
+    mov edx, SEEK_SET
+    syscall
+    mov edi, [rel stdin_copy]
+    mov rsi, r12
+    call copy_operand
+    jmp .rs_done
+.rs_direct:
+    xor edi, edi
+    mov rsi, r12
+    call copy_operand
+.rs_done:
+    pop r12
+    ret
+
+; -----------------------------------------------------------------------------
+; copy_operand
+;   Input : rdi = file descriptor, rsi -> label (as for copy_fd).
+;   Effect: copy_fd, once or, under --repeat=N, N times. A seekable source is
+;           rewound to its starting offset before each pass. Anything else
+;           (a pipe, a tty, a FIFO) is spooled into a memfd first and the
+;           memfd is replayed, so every pass sees the same bytes; without
+;           memfd support it is copied once. A pass that hits a read error
+;           ends the repeats, so the error is reported once. Under --headers the operand's
+;           header comes first, except for the stdin_label that stands for
+;           stdin when there are no operands.
+; -----------------------------------------------------------------------------
+copy_operand:
+    test dword [rel ext_flags], EXT_HEADERS
+    je  .co_repeat
+    lea rax, [rel stdin_label]
+    cmp rsi, rax
+    je  .co_repeat
+    push rdi
+    push rsi
+    call emit_header
+    pop rsi
+    pop rdi
+.co_repeat:
+    test dword [rel ext_flags], EXT_REPEAT
+    je  copy_fd
+    push rbx
+    push r12
+    push r13
+    push r14
+    push r15
+    mov ebx, edi                 ; rbx = source fd
+    mov r12, rsi                 ; r12 = label
+    mov r13, [rel repeat_count]  ; r13 = passes left
+    mov r14, -1                  ; r14 = memfd, once one is made
+    mov eax, SYS_lseek
+    xor esi, esi
+    mov edx, SEEK_CUR
+    syscall
+    mov r15, rax                 ; r15 = offset each pass starts from
+    cmp rax, 0
+    jge .co_passes
+    mov eax, SYS_memfd_create
+    lea rdi, [rel repeat_memfd_name]
+    mov esi, MFD_CLOEXEC
+    syscall
+    cmp rax, 0
+    jl  .co_once
+    mov r14, rax
+    mov edi, ebx
+    mov esi, eax
+    mov rdx, r12
+    call spool_fd                ; a read error is reported once, here
+    mov ebx, r14d
+    xor r15d, r15d
+.co_passes:
+    mov byte [rel read_failed], 0
+.co_pass:
+    mov eax, SYS_lseek
+    mov edi, ebx
+    mov rsi, r15
+    mov edx, SEEK_SET
+    syscall
+    mov edi, ebx
+    mov rsi, r12
+    call copy_fd
+    cmp byte [rel read_failed], 0
+    jne .co_passes_done          ; a directory fails every pass: report it once
+    dec r13
+    jne .co_pass
+.co_passes_done:
+    test r14, r14
+    js  .co_done
+    mov eax, SYS_close
+    mov edi, r14d
+    syscall
+    jmp .co_done
+.co_once:
+    mov edi, ebx
+    mov rsi, r12
+    call copy_fd
+.co_done:
+    pop r15
+    pop r14
+    pop r13
+    pop r12
+    pop rbx
+    ret
+
+; -----------------------------------------------------------------------------
+; emit_header
+;   Input : rsi -> operand name, "-" for stdin.
+;   Effect: --headers. Buffers tail -v's "==> NAME <==" line, preceded by a
+;           newline after the first header. It ends whatever line was open,
+;           so the next input byte starts a new line: -n numbers it, a -s
+;           blank run, --dedupe's previous line and --normalize's held
+;           whitespace do not reach across it.
+;           Nothing is written under --count-only or once --line-range is
+;           done.
+; -----------------------------------------------------------------------------
+emit_header:
+    push r12
+    mov r12, rsi                 ; r12 = name
+    test dword [rel ext_flags], EXT_COUNT_ONLY
+    jne .eh_done
+    cmp byte [rel range_done], 0
+    jne .eh_done
+    call release_held_cr
+    test dword [rel ext_flags], EXT_DEDUPE
+    je  .eh_separator
+    call dedupe_finish           ; settle an unterminated line first
+    mov byte [rel dd_have_ref], 0
+    mov byte [rel dd_diverged], 0
+    mov qword [rel dd_held], 0
+.eh_separator:
+    mov qword [rel nz_held], 0   ; --normalize: the open line's blanks go too
+    cmp byte [rel headers_seen], 0
+    je  .eh_open
+    lea rsi, [rel newline]
+    mov ecx, 1
+    call emit_block
+.eh_open:
+    mov byte [rel headers_seen], 1
+    mov rsi, header_open
+    mov ecx, 4
+    call emit_block
+    cmp byte [r12], '-'
+    jne .eh_name
+    cmp byte [r12 + 1], 0
+    jne .eh_name
+    lea r12, [rel header_stdin]
+.eh_name:
+    mov rsi, r12
+    xor ecx, ecx
+.eh_len:
+    cmp byte [rsi + rcx], 0
+    je  .eh_write_name
+    inc rcx
+    jmp .eh_len
+.eh_write_name:
+    call emit_block
+    mov rsi, header_close
+    mov ecx, 5
+    call emit_block
+    mov byte [rel line_start], 1
+    mov byte [rel line_blank], 1
+    mov qword [rel blank_run], 0
+.eh_done:
+    pop r12
+    ret
+
+; -----------------------------------------------------------------------------
+; spool_fd
+;   Input : edi = source fd, esi = destination fd, rdx -> source label.
+;   Output: rax = bytes stored in the destination.
+;   Effect: copies the source to EOF through `buffer`. A read or store error
+;           is reported against the label and ends the copy early; what was
+;           stored up to then stays usable.
+; -----------------------------------------------------------------------------
+spool_fd:
+    push rbx
+    push rbp
+    push r12
+    push r13
+    push r14
+    push r15
+    mov r12d, edi                ; r12 = source
+    mov r13d, esi                ; r13 = destination
+    mov r14, rdx                 ; r14 = label
+    xor r15d, r15d               ; r15 = bytes stored so far
+.spl_fill:
+    xor eax, eax                 ; SYS_read
+    mov edi, r12d
+    lea rsi, [rel buffer]
+    mov edx, BUFFER_SIZE
+    syscall
+    cmp rax, 0
+    je  .spl_done
+    jl  .spl_fill_check
+    lea rbx, [rel buffer]        ; rbx = unwritten bytes
+    mov rbp, rax                 ; rbp = how many
+.spl_store:
+    mov eax, SYS_write
+    mov edi, r13d
+    mov rsi, rbx
+    mov rdx, rbp
+    syscall
+    cmp rax, 0
+    jl  .spl_store_check
+    add rbx, rax
+    add r15, rax
+    sub rbp, rax
+    jne .spl_store
+    jmp .spl_fill
+.spl_store_check:
+    cmp rax, -EINTR
+    je  .spl_store
+    jmp .spl_error               ; e.g. ENOSPC/ENOMEM: keep what fitted
+.spl_fill_check:
+    cmp rax, -EINTR
+    je  .spl_fill
+    cmp rax, -EAGAIN
+    jne .spl_error
+    mov edi, r12d
+    call wait_readable
+    jmp .spl_fill
+.spl_error:
+    neg rax
+    mov edx, eax
+    mov rsi, r14
+    call report_read_error
+.spl_done:
+    mov rax, r15
+    pop r15
+    pop r14
+    pop r13
+    pop r12
+    pop rbp
+    pop rbx
+    ret
+
+; -----------------------------------------------------------------------------
+; reverse_fd
+;   Input : ebx = source fd, r15 = label (copy_fd's registers).
+;   Effect: --reverse. Spools the whole source into a memfd, maps it and
+;           hands its records to process_buffer last to first, like tac: a
+;           record runs up to and including its newline, so an unterminated
+;           final line comes out first and without one. Decorations then
+;           apply to the reversed stream, as with `tac FILE | cat -n`.
+; -----------------------------------------------------------------------------
+reverse_fd:
+    push r12
+    push r13
+    push r14
+    mov eax, SYS_memfd_create
+    lea rdi, [rel reverse_memfd_name]
+    mov esi, MFD_CLOEXEC
+    syscall
+    cmp rax, 0
+    jl  .rv_failed
+    mov r12, rax                 ; r12 = memfd
+    mov edi, ebx
+    mov esi, eax
+    mov rdx, r15
+    call spool_fd
+    mov r14, rax                 ; r14 = bytes spooled
+    add [rel count_bytes], rax   ; --count-only=lines,bytes total
+    test rax, rax
+    je  .rv_close
+    mov eax, SYS_mmap
+    xor edi, edi                 ; addr = NULL
+    mov rsi, r14                 ; length = everything spooled
+    mov edx, PROT_READ
+    mov r10d, MAP_PRIVATE
+    mov r8, r12                  ; fd
+    xor r9d, r9d                 ; offset = 0
+    syscall
+    cmp rax, 0
+    jl  .rv_map_failed
+    mov r13, rax
+    mov eax, SYS_close           ; the mapping outlives the descriptor
+    mov edi, r12d
+    syscall
+    mov r12, r13                 ; r12 = mapped base
+    add r13, r14                 ; r13 = end of the record to emit next
+    test byte [rel ext_flags], EXT_BINARY_SAFE
+    je  .rv_record
+    mov rsi, r12                 ; --binary-safe judges the input's first chunk
+    mov rcx, r14
+    cmp rcx, BUFFER_SIZE
+    jbe .rv_check_binary
+    mov ecx, BUFFER_SIZE
+.rv_check_binary:
+    call refuse_binary_chunk
+.rv_record:
+    cmp r13, r12
+    jbe .rv_unmap
+    mov rdx, r12                 ; rdx = record start if no newline precedes
+    lea rcx, [r13 - 1]           ; rcx = bytes before the record's last byte
+    sub rcx, r12
+    je  .rv_emit
+    lea rdi, [r13 - 2]           ; scan backwards for the previous newline
+    mov al, 10
+    std
+    repne scasb
+    cld
+    jne .rv_emit
+    lea rdx, [rdi + 2]           ; record starts just past that newline
+.rv_emit:
+    mov rsi, rdx
+    mov rcx, r13
+    sub rcx, rdx
+    mov r13, rdx
+    test dword [rel ext_flags], EXT_NORMALIZE
+    jne .rv_normalize
+    test dword [rel ext_flags], EXT_DEDUPE
+    jne .rv_dedupe
+    call process_buffer
+    jmp .rv_emitted
+.rv_normalize:
+    call normalize_buffer
+    jmp .rv_emitted
+.rv_dedupe:
+    call dedupe_buffer
+.rv_emitted:
+    cmp byte [rel range_done], 0 ; --line-range already satisfied?
+    je  .rv_record
+.rv_unmap:
+    mov eax, SYS_munmap
+    mov rdi, r12
+    mov rsi, r14
+    syscall
+    jmp .rv_done
+
+.rv_map_failed:
+    mov r13, rax
+    mov eax, SYS_close
+    mov edi, r12d
+    syscall
+    mov rax, r13
+.rv_failed:
+    neg rax
+    mov edx, eax
+    mov rsi, r15
+    call report_read_error
+    jmp .rv_done
+.rv_close:
+    mov eax, SYS_close
+    mov edi, r12d
+    syscall
+.rv_done:
+    pop r14
+    pop r13
+    pop r12
+    ret
 
-.check_show_ends:
-    mov rdi, r14
-    mov rsi, long_show_ends
-    call str_compare
-    test eax, eax
-    jne .check_show_tabs
-    test r11, r11
-    jne .bad_long_arg
-    or  byte [rel opt_flags], OPT_SHOW_ENDS
-    jmp .return
+; -----------------------------------------------------------------------------
+; dedupe_buffer
+;   Input : rsi -> input bytes, rcx = count, r15 = label for diagnostics.
+;   Effect: --dedupe. Splits the stream into lines and drops any line equal
+;           to the line before it, like uniq, passing the rest on to
+;           process_buffer. dd_buf holds that previous (reference) line. The
+;           current line is matched against it as bytes arrive and held back
+;           while it matches; once it differs, the held prefix is emitted and
+;           the rest of the line overwrites the reference from there on, since
+;           both share that prefix. State carries across reads and files, and
+;           dedupe_finish settles an unterminated last line.
+; -----------------------------------------------------------------------------
+dedupe_buffer:
+    push rbx
+    push r12
+    push r13
+    push r14
+    mov r12, rsi                 ; r12 = cursor
+    lea r13, [rsi + rcx]         ; r13 = end of input
+.dd_line:
+    cmp r12, r13
+    jae .dd_return
+    mov rdi, r12                 ; look for the end of this line
+    mov rcx, r13
+    sub rcx, r12
+    mov al, 10
+    repne scasb
+    mov r14, r13                 ; r14 = end of the line's bytes in this chunk
+    jne .dd_segment
+    lea r14, [rdi - 1]           ; stop at the newline
+.dd_segment:
+    mov rsi, r12
+    mov rdx, r14
+    sub rdx, r12
+    je  .dd_segment_done
+    call dedupe_segment
+.dd_segment_done:
+    mov r12, r14
+    cmp r12, r13
+    jae .dd_return               ; the line goes on in the next chunk
+    call dedupe_newline
+    inc r12                      ; step over the newline
+    jmp .dd_line
+.dd_return:
+    pop r14
+    pop r13
+    pop r12
+    pop rbx
+    ret
 
-.check_show_tabs:
-    mov rdi, r14
-    mov rsi, long_show_tabs
-    call str_compare
-    test eax, eax
-    jne .check_show_nonprinting
-    test r11, r11
-    jne .bad_long_arg
-    or  byte [rel opt_flags], OPT_SHOW_TABS
-    jmp .return
+; dedupe_segment: rsi -> rdx (> 0) bytes of the current line, no newline.
+dedupe_segment:
+    push rbx
+    push r12
+    mov rbx, rsi                 ; rbx = bytes not yet accounted for
+    mov r12, rdx                 ; r12 = how many
+    cmp byte [rel dd_diverged], 0
+    jne .ds_store
+    mov rcx, [rel dd_ref_len]    ; rcx = reference bytes still to match,
+    sub rcx, [rel dd_held]       ; capped at what we have
+    cmp rcx, r12
+    jbe .ds_compare
+    mov rcx, r12
+.ds_compare:
+    test rcx, rcx
+    je  .ds_diverge              ; longer than the reference
+    mov rsi, rbx
+    mov rdi, [rel dd_buf]
+    add rdi, [rel dd_held]
+    repe cmpsb
+    je  .ds_matched
+    dec rsi                      ; rsi = first differing byte
+.ds_matched:
+    mov rax, rsi
+    sub rax, rbx                 ; rax = bytes that matched
+    add [rel dd_held], rax
+    add rbx, rax
+    sub r12, rax
+    je  .ds_done                 ; still a possible repeat: keep holding
+.ds_diverge:
+    mov byte [rel dd_diverged], 1
+    mov rcx, [rel dd_held]
+    test rcx, rcx
+    je  .ds_store
+    mov rsi, [rel dd_buf]        ; the held prefix goes out first
+    call process_buffer
+.ds_store:
+    mov rdx, [rel dd_held]
+    add rdx, r12
+    call dedupe_reserve
+    mov rdi, [rel dd_buf]        ; extend the reference with the new bytes
+    add rdi, [rel dd_held]
+    mov rsi, rbx
+    mov rcx, r12
+    rep movsb
+    add [rel dd_held], r12
+    mov rsi, rbx
+    mov rcx, r12
+    call process_buffer
+.ds_done:
+    pop r12
+    pop rbx
+    ret
 
-.check_show_nonprinting:
-    mov rdi, r14
-    mov rsi, long_show_nonprinting
-    call str_compare
-    test eax, eax
-    jne .check_show_all
-    test r11, r11
-    jne .bad_long_arg
-    or  byte [rel opt_flags], OPT_SHOW_NONPRINTING
-    jmp .return
+; dedupe_newline: the current line ends; drop it or make it the reference.
+dedupe_newline:
+    cmp byte [rel dd_diverged], 0
+    jne .dn_emit_newline         ; already written up to here
+    mov rax, [rel dd_held]
+    cmp byte [rel dd_have_ref], 0
+    je  .dn_new
+    cmp rax, [rel dd_ref_len]
+    je  .dn_drop                 ; same bytes as the line before
+.dn_new:
+    test rax, rax
+    jne .dn_emit_held
+    test byte [rel opt_flags], OPT_SQUEEZE_BLANK
+    je  .dn_emit_newline
+    mov rdx, [rel blank_run]     ; -s goes first: a blank line it is about to
+    cmp rdx, [rel squeeze_limit] ; drop must not become the reference
+    jae .dn_drop
+    jmp .dn_emit_newline
+.dn_emit_held:
+    mov rsi, [rel dd_buf]
+    mov rcx, rax
+    call process_buffer
+.dn_emit_newline:
+    lea rsi, [rel newline]
+    mov ecx, 1
+    call process_buffer
+    mov rax, [rel dd_held]
+    mov [rel dd_ref_len], rax
+    mov byte [rel dd_have_ref], 1
+    mov byte [rel dd_diverged], 0
+.dn_drop:
+    mov qword [rel dd_held], 0
+    ret
 
-.check_show_all:
-    mov rdi, r14
-    mov rsi, long_show_all
-    call str_compare
-    test eax, eax
-    jne .unknown
-    test r11, r11
-    jne .bad_long_arg
-    or  byte [rel opt_flags], OPT_SHOW_NONPRINTING | OPT_SHOW_ENDS | OPT_SHOW_TABS
-    jmp .return
+; dedupe_finish: at exit, an unterminated last line equal to the line before
+; is a repeat (uniq agrees); otherwise its held bytes are still owed.
+dedupe_finish:
+    cmp byte [rel dd_diverged], 0
+    jne .dfin_done
+    mov rcx, [rel dd_held]
+    test rcx, rcx
+    je  .dfin_done
+    cmp rcx, [rel dd_ref_len]
+    je  .dfin_done
+    mov rsi, [rel dd_buf]
+    call process_buffer
+.dfin_done:
+    ret
 
-.unknown:
-    mov rsi, r12                 ; rsi = full option string
-    call report_bad_long_option
+; dedupe_reserve: make dd_buf hold at least rdx bytes.
+dedupe_reserve:
+    lea rdi, [rel dd_buf]
+    ; fall through
+
+; reserve_mapping: rdi -> a {buffer, capacity} pair of qwords; make the
+; anonymous mapping hold at least rdx bytes, doubling as it grows. Running out
+; of memory is fatal, reported against the label in r15.
+reserve_mapping:
+    cmp rdx, [rdi + 8]
+    jbe .rm_done
+    push rbx
+    push r12
+    mov r12, rdi                 ; r12 = the pair
+    mov rbx, [r12 + 8]
+    shl rbx, 1
+    cmp rbx, HOLD_MIN_CAP
+    jae .rm_fit
+    mov ebx, HOLD_MIN_CAP
+.rm_fit:
+    cmp rbx, rdx
+    jae .rm_sized
+    shl rbx, 1
+    jmp .rm_fit
+.rm_sized:
+    mov rdi, [r12]
+    test rdi, rdi
+    jne .rm_remap
+    mov eax, SYS_mmap
+    xor edi, edi                 ; addr = NULL
+    mov rsi, rbx
+    mov edx, PROT_READ | PROT_WRITE
+    mov r10d, MAP_PRIVATE | MAP_ANONYMOUS
+    mov r8, -1                   ; no file
+    xor r9d, r9d
+    syscall
+    jmp .rm_check
+.rm_remap:
+    mov eax, SYS_mremap
+    mov rsi, [r12 + 8]           ; old size
+    mov rdx, rbx                 ; new size
+    mov r10d, MREMAP_MAYMOVE
+    syscall
+.rm_check:
+    cmp rax, 0
+    jl  .rm_failed
+    mov [r12], rax
+    mov [r12 + 8], rbx
+    pop r12
+    pop rbx
+.rm_done:
+    ret
+.rm_failed:
+    neg rax
+    mov edx, eax
+    mov rsi, r15
+    call report_read_error
+    mov edi, 1
+    call exit_with_code
 
-.return:
-    pop r12                      ; restore r12
-    pop rbx                      ; restore rbx
+; -----------------------------------------------------------------------------
+; normalize_buffer
+;   Input : rsi -> input bytes, rcx = count, r15 = label for diagnostics.
+;   Effect: --normalize. Drops the spaces, tabs, CRs, VTs and FFs that end
+;           each line and passes the rest on to --dedupe or process_buffer,
+;           whose -s squeezes the blank lines this leaves. A whitespace run
+;           is held in nz_buf until a later byte of its line shows it is not
+;           trailing after all. State carries across reads and files; what
+;           is held when the line or the input ends is dropped.
+; -----------------------------------------------------------------------------
+normalize_buffer:
+    push rbx
+    push r12
+    push r13
+    push r14
+    mov r12, rsi                 ; r12 = cursor
+    lea r13, [rsi + rcx]         ; r13 = end of input
+.nz_line:
+    cmp r12, r13
+    jae .nz_return
+    mov rdi, r12                 ; look for the end of this line
+    mov rcx, r13
+    sub rcx, r12
+    mov al, 10
+    repne scasb
+    mov r14, r13                 ; r14 = end of the line's bytes in this chunk
+    jne .nz_trim
+    lea r14, [rdi - 1]           ; stop at the newline
+.nz_trim:
+    mov rbx, r14                 ; rbx = where the segment's whitespace tail starts
+.nz_trim_loop:
+    cmp rbx, r12
+    jbe .nz_hold                 ; all whitespace: hold all of it
+    movzx eax, byte [rbx - 1]
+    cmp al, ' '
+    je  .nz_trim_next
+    sub al, 9                    ; tab, VT, FF and CR are 9, 11, 12 and 13
+    cmp al, 13 - 9               ; (no newline can be here)
+    ja  .nz_text
+.nz_trim_next:
+    dec rbx
+    jmp .nz_trim_loop
+.nz_text:
+    mov rcx, [rel nz_held]       ; text follows: the held run was no tail
+    test rcx, rcx
+    je  .nz_emit_text
+    mov rsi, [rel nz_buf]
+    call normalize_emit
+    mov qword [rel nz_held], 0
+.nz_emit_text:
+    mov rsi, r12
+    mov rcx, rbx
+    sub rcx, r12
+    call normalize_emit
+.nz_hold:
+    mov rdx, r14
+    sub rdx, rbx                 ; rdx = whitespace to hold
+    je  .nz_segment_done
+    add rdx, [rel nz_held]
+    lea rdi, [rel nz_buf]
+    call reserve_mapping
+    mov rdi, [rel nz_buf]        ; append it to what is already held
+    add rdi, [rel nz_held]
+    mov rsi, rbx
+    mov rcx, r14
+    sub rcx, rbx
+    add [rel nz_held], rcx
+    rep movsb
+.nz_segment_done:
+    mov r12, r14
+    cmp r12, r13
+    jae .nz_return               ; the line goes on in the next chunk
+    mov qword [rel nz_held], 0   ; the line ends here: its tail is dropped
+    mov rsi, r12                 ; and the newline goes on
+    mov ecx, 1
+    call normalize_emit
+    inc r12
+    jmp .nz_line
+.nz_return:
+    pop r14
+    pop r13
+    pop r12
+    pop rbx
     ret
 
-.bad_long_arg:
-    mov rsi, r14                 ; rsi = option name (after "--")
-    call report_long_option_argument
+; normalize_emit: rsi -> rcx (> 0) bytes on to the next stage.
+normalize_emit:
+    test dword [rel ext_flags], EXT_DEDUPE
+    jne dedupe_buffer
+    jmp process_buffer
 
 ; -----------------------------------------------------------------------------
 ; copy_fd
@@ -583,34 +2713,32 @@ copy_fd:
     push r15                     ; save r15
     mov rbx, rdi                 ; rbx = source fd
     mov r15, rsi                 ; r15 = label pointer (path/ "-" ) for errors
-    mov al, [rel opt_flags]      ; al = option flags
-    test al, al                  ; any flags set?
+    mov eax, SYS_fadvise64       ; posix_fadvise(fd, 0, 0, SEQUENTIAL) for
+    xor esi, esi                 ; every copy path; pipes and ttys answer
+    xor edx, edx                 ; ESPIPE, which is ignored
+    mov r10d, POSIX_FADV_SEQUENTIAL
+    syscall
+    mov rdi, rbx                 ; restore fd and label
+    mov rsi, r15
+    mov eax, [rel ext_flags]     ; extensions need to see every byte too,
+    and eax, ~EXT_PASSIVE        ; except those the plain path handles
+    or  al, [rel opt_flags]      ; al = option flags
+    test eax, eax                ; any flags set?
     jne .decorated_path          ; yes -> decorated path
     call copy_fd_plain           ; zero-overhead path when no transforms needed
     jmp .leave                   ; done
 
 .decorated_path:
 .decorated_full_path:            ; (label alias) decorated path entry
-.decorated_fast_try:
-    mov eax, SYS_fstat           ; hint sequential reads for fallback path
-    mov edi, ebx                 ; fd = source
-    lea rsi, [rel stat_in]       ; &stat_in
-    syscall
-    cmp rax, 0                   ; ignore failures
-    jl  .decorated_read_loop
-    mov eax, [rel stat_in + STAT_MODE_OFFSET]
-    and eax, S_IFMT
-    cmp eax, S_IFREG             ; only hint regular files
-    jne .decorated_read_loop
-    mov eax, SYS_fadvise64       ; posix_fadvise(fd, 0, 0, SEQUENTIAL)
-    mov edi, ebx
-    xor rsi, rsi
-    xor rdx, rdx
-    xor r10d, r10d
-    mov r10d, POSIX_FADV_SEQUENTIAL
-    syscall
-    jmp .decorated_read_loop     ; otherwise stream via read()
+    test dword [rel ext_flags], EXT_REVERSE
+    je  .decorated_stream
+    call reverse_fd              ; needs the whole input before any output
+    jmp .decorated_done
+.decorated_stream:
+    mov byte [rel first_chunk], 1
 .decorated_read_loop:
+    cmp byte [rel range_done], 0 ; --line-range already satisfied?
+    jne .decorated_done
     mov eax, SYS_read            ; read() syscall
     mov edi, ebx                 ; edi = source fd
     lea rsi, [rel buffer]        ; rsi = buffer
@@ -620,8 +2748,20 @@ copy_fd:
     je  .decorated_done          ; 0 -> EOF, done
     jl  .decorated_read_check    ; <0 -> error, handle
 
+    add [rel count_bytes], rax   ; --count-only=lines,bytes total
     mov rcx, rax                 ; rcx = bytes read
     lea rsi, [rel buffer]        ; rsi = buffer start
+    test byte [rel ext_flags], EXT_BINARY_SAFE
+    je  .decorated_checked
+    cmp byte [rel first_chunk], 0
+    je  .decorated_checked
+    mov byte [rel first_chunk], 0
+    call refuse_binary_chunk     ; exits if the chunk holds a NUL
+.decorated_checked:
+    test dword [rel ext_flags], EXT_NORMALIZE
+    jne .decorated_normalize
+    test dword [rel ext_flags], EXT_DEDUPE
+    jne .decorated_dedupe
     test byte [rel opt_flags], OPT_SHOW_NONPRINTING
     je  .decorated_process
     mov rdx, rcx                 ; save length
@@ -632,6 +2772,8 @@ copy_fd:
     pop rcx
     test eax, eax
     je  .decorated_process
+    test dword [rel ext_flags], EXT_REWRITE
+    jne .decorated_process       ; output rewriting needs the general path
     mov al, [rel opt_flags]      ; al = current flags (includes OPT_SHOW_NONPRINTING)
     mov r8b, al                  ; r8b = original flags
     mov r9b, al
@@ -650,11 +2792,23 @@ copy_fd:
     call process_buffer          ; use faster non- -v paths
     pop r8
     mov [rel opt_flags], r8b     ; restore flags
-    jmp .decorated_read_loop
+    jmp .decorated_chunk_done
 
 .decorated_process:
     call process_buffer          ; decorate and emit
-    jmp .decorated_read_loop     ; continue reading
+    jmp .decorated_chunk_done
+
+.decorated_normalize:
+    call normalize_buffer        ; trim line ends, then on as below
+    jmp .decorated_chunk_done
+
+.decorated_dedupe:
+    call dedupe_buffer           ; drop repeats, decorate and emit the rest
+.decorated_chunk_done:
+    test dword [rel ext_flags], EXT_LINE_BUFFERED
+    je  .decorated_read_loop     ; continue reading
+    call flush_outbuf_lines      ; --buffer=line: complete lines go out now
+    jmp .decorated_read_loop
 
 .decorated_done:
     call flush_outbuf            ; flush any pending output
@@ -664,13 +2818,14 @@ copy_fd:
     cmp rax, -EINTR              ; interrupted?
     je  .decorated_read_loop     ; retry
     cmp rax, -EAGAIN             ; would block?
-    je  .decorated_read_loop     ; retry
-    jmp .decorated_io_error      ; other error
+    jne .decorated_io_error      ; other error
+    mov edi, ebx                 ; O_NONBLOCK source: sleep until readable
+    call wait_readable
+    jmp .decorated_read_loop     ; retry
 
 .decorated_io_error:
     neg rax                      ; rax = errno
     mov edx, eax                 ; edx = errno
-    call flush_outbuf            ; flush whatever we have
     mov rsi, r15                 ; rsi = label for diagnostics
     call report_read_error       ; report error for this file
 
@@ -692,6 +2847,10 @@ copy_fd_plain:
     push r14                     ; save r14
     lea r12, [rel buffer]        ; r12 = address of buffer
     call flush_outbuf            ; ensure output buffer is empty
+    test byte [rel ext_flags], EXT_PREALLOCATE
+    je  .plain_copy
+    call preallocate_stdout      ; --preallocate: reserve room for the copy
+.plain_copy:
     call maybe_sendfile_plain    ; try sendfile fast path
     test eax, eax                ; eax == 0 => done
     jne .try_splice_plain        ; if 1, try splice
@@ -716,23 +2875,7 @@ copy_fd_plain:
     and eax, S_IFMT              ; mask file type bits
     cmp eax, S_IFREG             ; is regular file?
     jne .plain_read_loop         ; if not, go to read loop
-    mov r11b, 1                  ; mark input as regular
-    mov eax, SYS_fadvise64       ; posix_fadvise
-    mov edi, ebx                 ; fd
-    xor rsi, rsi                 ; offset = 0
-    xor rdx, rdx                 ; len = 0 (entire file)
-    xor r10d, r10d               ; clear r10d first
-    mov r10d, POSIX_FADV_SEQUENTIAL ; hint sequential access
-    syscall
-    jne .plain_read_loop         ; ignore error, go to streaming
-    mov r11b, 1                  ; keep regular flag set
-    mov eax, SYS_fadvise64       ; (duplicated, but harmless)
-    mov edi, ebx                 ; fd
-    xor rsi, rsi                 ; offset 0
-    xor rdx, rdx                 ; len 0
-    xor r10d, r10d               ; clear r10d
-    mov r10d, POSIX_FADV_SEQUENTIAL ; hint sequential
-    syscall
+    mov r11b, 1                  ; mark input as regular (DONTNEED hints)
 
 .plain_read_loop:
     mov eax, SYS_read            ; read() syscall
@@ -778,10 +2921,13 @@ copy_fd_plain:
     cmp rax, -EINTR              ; interrupted?
     je  .plain_read_loop         ; retry
     cmp rax, -EAGAIN             ; would block?
-    je  .plain_read_loop         ; retry
+    jne .plain_read_error
+    mov edi, ebx                 ; O_NONBLOCK source: sleep until readable
+    call wait_readable
+    jmp .plain_read_loop         ; retry
+.plain_read_error:
     neg rax                      ; rax = errno
     mov edx, eax                 ; edx = errno for reporter
-    call flush_outbuf            ; flush outbuf before error
     mov rsi, r15                 ; rsi = label for error
     call report_read_error       ; print read error
     jmp .plain_done              ; exit path
@@ -810,6 +2956,9 @@ copy_fd_plain:
 ;   When input is a regular file, mmap the whole thing and run process_buffer
 ;   in one shot to skip per-chunk read() overhead. Returns 0 on success (all
 ;   bytes processed) or 1 to let callers fall back to the usual streaming path.
+;   A zero st_size falls back too: /proc and sysfs files report it and still
+;   return data from read(). Character devices never get here: /dev/zero has
+;   no size to map and no end, so they stream like pipes.
 ; -----------------------------------------------------------------------------
 decorated_try_mmap:
     push r12
@@ -827,7 +2976,7 @@ decorated_try_mmap:
     jne .dtm_fallback            ; only mmap regular files
     mov r13, [rel stat_in + STAT_SIZE_OFFSET] ; r13 = file size
     test r13, r13
-    je  .dtm_success             ; empty file -> nothing to do
+    je  .dtm_fallback            ; /proc and sysfs files claim 0 but have data
     mov eax, SYS_mmap
     xor edi, edi                 ; addr = NULL
     mov rsi, r13                 ; length = file size
@@ -849,10 +2998,6 @@ decorated_try_mmap:
     xor eax, eax                 ; signal success
     jmp .dtm_done
 
-.dtm_success:
-    xor eax, eax                 ; nothing to process but still success
-    jmp .dtm_done
-
 .dtm_fallback:
     mov eax, 1                   ; ask caller to fall back
 
@@ -955,15 +3100,6 @@ decorated_try_cfr_chunks:
     cmp rax, 0                   ; error?
     jl  .dtcc_close_fail         ; close and fallback
     mov rbx, rax                 ; rbx = mapped base address
-    test r11b, r11b              ; input regular?
-    je  .dtcc_copy_loop          ; if not, skip SEQUENTIAL hint
-    mov eax, SYS_fadvise64       ; posix_fadvise
-    mov edi, r12d                ; source fd
-    xor rsi, rsi                 ; offset 0
-    xor rdx, rdx                 ; len 0 (whole file)
-    xor r10d, r10d               ; clear
-    mov r10d, POSIX_FADV_SEQUENTIAL ; hint sequential
-    syscall
 
 .dtcc_copy_loop:
     sub rsp, 8                   ; allocate space for offset arg (unused)
@@ -1049,7 +3185,6 @@ decorated_try_cfr_chunks:
     je  .dtcc_cleanup_fallback   ; fallback
     neg rax                      ; errno -> positive
     mov edx, eax                 ; save errno
-    call flush_outbuf            ; flush before error
     mov rsi, r13                 ; rsi = label
     call report_read_error       ; report read error
     xor eax, eax                 ; consider handled (no fallback)
@@ -1093,6 +3228,62 @@ decorated_try_cfr_chunks:
     pop rbp                      ; restore rbp
     ret                          ; return to caller
 
+; -----------------------------------------------------------------------------
+; preallocate_stdout
+;   Input : ebx = source fd.
+;   When both the source and stdout are regular files, fallocate() the part of
+;   stdout the copy is about to write (current offset, bytes left in the
+;   source) with FALLOC_FL_KEEP_SIZE, so the file gets contiguous blocks but
+;   its size only grows as data lands. Best effort: every failure is ignored.
+;   Clobbers rax, rcx, rdx, rsi, rdi, r8, r10, r11.
+; -----------------------------------------------------------------------------
+preallocate_stdout:
+    mov eax, SYS_fstat
+    mov edi, ebx
+    lea rsi, [rel stat_in]
+    syscall
+    test rax, rax
+    jl  .pa_done
+    mov eax, [rel stat_in + STAT_MODE_OFFSET]
+    and eax, S_IFMT
+    cmp eax, S_IFREG
+    jne .pa_done
+    mov eax, SYS_fstat
+    mov edi, 1
+    lea rsi, [rel stat_out]
+    syscall
+    test rax, rax
+    jl  .pa_done
+    mov eax, [rel stat_out + STAT_MODE_OFFSET]
+    and eax, S_IFMT
+    cmp eax, S_IFREG
+    jne .pa_done
+    mov eax, SYS_lseek           ; where the source will be read from
+    mov edi, ebx
+    xor esi, esi
+    mov edx, SEEK_CUR
+    syscall
+    test rax, rax
+    jl  .pa_done
+    mov r8, [rel stat_in + STAT_SIZE_OFFSET]
+    sub r8, rax                  ; r8 = bytes left to copy
+    jle .pa_done
+    mov eax, SYS_lseek           ; where they will land in stdout
+    mov edi, 1
+    xor esi, esi
+    mov edx, SEEK_CUR
+    syscall
+    test rax, rax
+    jl  .pa_done
+    mov rdx, rax                 ; offset
+    mov r10, r8                  ; len
+    mov eax, SYS_fallocate
+    mov edi, 1
+    mov esi, FALLOC_FL_KEEP_SIZE
+    syscall                      ; fallocate(1, KEEP_SIZE, offset, len)
+.pa_done:
+    ret
+
 ; -----------------------------------------------------------------------------
 ; maybe_sendfile_plain
 ;   Attempt a zero-copy handoff for copy_fd_plain by verifying that input is a
@@ -1224,7 +3415,7 @@ maybe_splice_plain:
     syscall
     cmp rax, 0                   ; 0 => EOF
     je  .msp_success             ; done
-    jl  .msp_splice_error        ; handle errors
+    jl  .msp_splice_in_error     ; handle errors
     mov r15, rax                 ; r15 = bytes to drain to stdout
 .msp_drain:
     mov eax, SYS_splice          ; splice from pipe read end to stdout
@@ -1303,6 +3494,13 @@ maybe_splice_plain:
 .msp_drain_sigpipe:
     call handle_sigpipe
 
+.msp_splice_in_error:
+    cmp rax, -EAGAIN             ; empty O_NONBLOCK source?
+    jne .msp_splice_error
+    mov edi, ebx                 ; wait for data instead of spinning
+    call wait_readable
+    jmp .msp_loop
+
 .msp_splice_error:
     cmp rax, -EINTR              ; interrupted?
     je  .msp_loop                ; retry
@@ -1361,8 +3559,20 @@ process_buffer:
     mov r13, rcx                 ; r13 = remaining byte count
     lea rbx, [r12 + r13]         ; rbx = end pointer for remaining calculation
     mov r15b, [rel opt_flags]    ; r15b = options flags
+    test dword [rel ext_flags], EXT_BYTE_OFFSET
+    je  .offset_ready
+    lea rax, [rel cr_byte]       ; a replayed CR was counted with its chunk
+    cmp rsi, rax
+    je  .offset_ready
+    mov rax, [rel offset_total]  ; chunk pointer + bias = input offset
+    sub rax, rsi
+    mov [rel offset_bias], rax
+    add [rel offset_total], rcx
+.offset_ready:
     test r15b, OPT_SHOW_NONPRINTING
     jne .visible_path            ; -v variants
+    test dword [rel ext_flags], EXT_REWRITE
+    jne .visible_path            ; wcat extensions live in the general path
     test r15b, OPT_SHOW_TABS
     jne .tabs_path               ; -T/-t/-A fast path
 
@@ -1384,8 +3594,9 @@ process_buffer:
     jne .nl_nonblank_start
     test r15b, OPT_SQUEEZE_BLANK
     je  .nl_blank_emit
-    cmp byte [rel prev_blank], 1
-    jne .nl_blank_emit
+    mov rax, [rel blank_run]
+    cmp rax, [rel squeeze_limit]
+    jb .nl_blank_emit
     inc r12
     dec r13
     jmp .nl_loop
@@ -1394,8 +3605,10 @@ process_buffer:
     mov byte [rel line_start], 0
     test r15b, OPT_NUMBER
     je  .nl_blank_no_number
+    test dword [rel ext_flags], EXT_COLOR
+    jne .nl_blank_number_slow    ; escapes go through emit_line_number
     mov rax, [rel line_no]
-    cmp rax, 1000000
+    cmp rax, [rel number_fast_limit]
     jae .nl_blank_number_slow
     mov eax, BUFFER_SIZE - 9
     cmp r14d, eax
@@ -1433,7 +3646,7 @@ process_buffer:
 .nl_blank_no_dollar:
     mov byte [r10 + r14], 10
     inc r14
-    mov byte [rel prev_blank], 1
+    inc qword [rel blank_run]
     mov byte [rel line_start], 1
     mov byte [rel line_blank], 1
     inc r12
@@ -1445,8 +3658,10 @@ process_buffer:
     mov byte [rel line_blank], 0
     test r15b, (OPT_NUMBER | OPT_NUMBER_NONBLANK)
     je  .nl_after_number
+    test dword [rel ext_flags], EXT_COLOR
+    jne .nl_number_slow          ; escapes go through emit_line_number
     mov rax, [rel line_no]
-    cmp rax, 1000000
+    cmp rax, [rel number_fast_limit]
     jae .nl_number_slow
     mov eax, BUFFER_SIZE - 7
     cmp r14d, eax
@@ -1520,8 +3735,9 @@ process_buffer:
     je  .nl_emit_nl_after_copy
     cmp byte [rel line_blank], 1
     jne .nl_emit_nl_after_copy
-    cmp byte [rel prev_blank], 1
-    jne .nl_emit_nl_after_copy
+    mov rax, [rel blank_run]
+    cmp rax, [rel squeeze_limit]
+    jb .nl_emit_nl_after_copy
     inc r12
     dec r13
     jmp .nl_loop
@@ -1542,7 +3758,7 @@ process_buffer:
 .nl_emit_nl_only_fast:
     mov byte [r10 + r14], 10
     inc r14
-    mov byte [rel prev_blank], 0
+    mov qword [rel blank_run], 0
     mov byte [rel line_start], 1
     mov byte [rel line_blank], 1
     inc r12
@@ -1602,8 +3818,9 @@ process_buffer:
     jne .tabs_not_blank_start
     test r15b, OPT_SQUEEZE_BLANK
     je  .tabs_emit_blank
-    cmp byte [rel prev_blank], 1
-    jne .tabs_emit_blank
+    mov rax, [rel blank_run]
+    cmp rax, [rel squeeze_limit]
+    jb .tabs_emit_blank
     inc r12
     jmp .tabs_loop
 
@@ -1611,10 +3828,7 @@ process_buffer:
     mov byte [rel line_start], 0
     test r15b, OPT_NUMBER
     je  .tabs_blank_num_done
-    mov [rel outpos], r14
-    call emit_line_number
-    mov r14, [rel outpos]
-    lea r10, [rel outbuf]
+    call emit_line_number        ; outpos is live here; r14 is not cached
 .tabs_blank_num_done:
     test r15b, OPT_SHOW_ENDS
     je  .tabs_blank_emit_nl
@@ -1623,7 +3837,7 @@ process_buffer:
 .tabs_blank_emit_nl:
     mov dil, 10
     call emit_byte
-    mov byte [rel prev_blank], 1
+    inc qword [rel blank_run]
     mov byte [rel line_start], 1
     mov byte [rel line_blank], 1
     inc r12
@@ -1673,10 +3887,9 @@ process_buffer:
     mov al, [r12]
     cmp al, 9
     jne .tabs_handle_nl
-    mov dil, '^'
-    call emit_byte
-    mov dil, 'I'
-    call emit_byte
+    mov rsi, [rel tab_marker]    ; ^I, or --tab-marker
+    mov rcx, [rel tab_marker_len]
+    call emit_block
     mov byte [rel line_blank], 0
     inc r12
     jmp .tabs_loop
@@ -1686,8 +3899,9 @@ process_buffer:
     je  .tabs_nl_emit
     cmp byte [rel line_blank], 1
     jne .tabs_nl_emit
-    cmp byte [rel prev_blank], 1
-    jne .tabs_nl_emit
+    mov rax, [rel blank_run]
+    cmp rax, [rel squeeze_limit]
+    jb .tabs_nl_emit
     inc r12
     jmp .tabs_loop
 .tabs_nl_emit:
@@ -1698,8 +3912,7 @@ process_buffer:
 .tabs_nl_emit_only:
     mov dil, 10
     call emit_byte
-    mov al, [rel line_blank]
-    mov [rel prev_blank], al
+    call update_blank_run
     mov byte [rel line_start], 1
     mov byte [rel line_blank], 1
     inc r12
@@ -1726,6 +3939,78 @@ process_buffer:
     mov r14, [rel outpos]
     lea r10, [rel outbuf]
 .vis_after_guard:
+    test dword [rel ext_flags], EXT_STRIP_CR
+    jne .vis_strip_cr            ; before anything looks at the line
+.vis_cr_done:
+    test byte [rel ext_flags], EXT_LINE_RANGE
+    je  .vis_range_ok
+    cmp byte [rel range_skipping], 1
+    je  .vis_range_skip          ; still dropping a line from a previous chunk
+    cmp byte [rel line_start], 1
+    jne .vis_range_ok
+    mov rax, [rel src_lines]     ; a new input line starts here
+    inc rax
+    mov [rel src_lines], rax
+    cmp rax, [rel range_first]
+    jb  .vis_range_drop
+    cmp rax, [rel range_last]
+    jbe .vis_range_ok
+    mov byte [rel range_done], 1 ; past B: discard the rest of the input
+    jmp .vis_store_outpos
+.vis_range_drop:
+    mov byte [rel range_skipping], 1
+    cmp byte [r12], 10           ; keep -n/-b numbering aligned with the input
+    jne .vis_range_drop_text
+    test r15b, OPT_NUMBER
+    je  .vis_range_skip
+    call bump_line_ascii
+    jmp .vis_range_skip
+.vis_range_drop_text:
+    test r15b, (OPT_NUMBER | OPT_NUMBER_NONBLANK)
+    je  .vis_range_skip
+    call bump_line_ascii
+.vis_range_skip:
+    cmp r12, rbx                 ; chunk exhausted mid-line?
+    jae .visible_loop
+    mov al, [r12]
+    inc r12
+    cmp al, 10
+    jne .vis_range_skip
+    mov byte [rel range_skipping], 0 ; newline ends the dropped line
+    jmp .visible_loop
+
+.vis_strip_cr:
+    cmp byte [rel cr_held], 0
+    jne .vis_cr_release
+    cmp byte [r12], 13
+    jne .vis_cr_done
+    cmp r13, 1
+    ja  .vis_cr_peek
+    lea rax, [rel cr_byte]       ; a replayed CR is output, never held again
+    cmp r12, rax
+    je  .vis_cr_done
+    mov byte [rel cr_held], 1    ; chunk ends in CR: the next byte decides
+    mov rax, [rel offset_bias]   ; --byte-offset: remember where it was read
+    add rax, r12
+    mov [rel cr_offset], rax
+    jmp .vis_cr_drop
+.vis_cr_peek:
+    cmp byte [r12 + 1], 10
+    jne .vis_cr_done             ; lone CR: left alone
+.vis_cr_drop:
+    inc r12
+    dec r13
+    jmp .visible_loop
+.vis_cr_release:
+    cmp byte [r12], 10
+    jne .vis_cr_lone
+    mov byte [rel cr_held], 0    ; it was a CRLF after all
+    jmp .vis_cr_done
+.vis_cr_lone:
+    mov [rel outpos], r14
+    call release_held_cr         ; emit the CR, then carry on from here
+    jmp .visible_loop
+.vis_range_ok:
 
     cmp byte [rel line_start], 1
     jne .vis_not_blank_start
@@ -1734,14 +4019,23 @@ process_buffer:
     jne .vis_not_blank_start
     test r15b, OPT_SQUEEZE_BLANK
     je  .vis_emit_blank
-    cmp byte [rel prev_blank], 1
-    jne  .vis_emit_blank
+    mov rax, [rel blank_run]
+    cmp rax, [rel squeeze_limit]
+    jb  .vis_emit_blank
     inc r12
     dec r13
     jmp .visible_loop
 
 .vis_emit_blank:
     mov byte [rel line_start], 0
+    test dword [rel ext_flags], EXT_BYTE_OFFSET
+    je  .vis_blank_offset_done
+    mov [rel outpos], r14
+    mov rdi, r12
+    call emit_byte_offset
+    mov r14, [rel outpos]
+    lea r10, [rel outbuf]
+.vis_blank_offset_done:
     test r15b, OPT_NUMBER
     je  .vis_blank_num_done
     mov [rel outpos], r14
@@ -1759,13 +4053,19 @@ process_buffer:
 .vis_blank_space_ok:
     test r15b, OPT_SHOW_ENDS
     je  .vis_blank_no_dollar
+    test dword [rel ext_flags], EXT_END_MARKER
+    jne .vis_blank_marker
     mov byte [r10 + r14], '$'
     inc r14
+    jmp .vis_blank_no_dollar
+.vis_blank_marker:
+    call emit_end_marker
 .vis_blank_no_dollar:
-    mov byte [r10 + r14], 10
+    mov al, [rel line_sep]       ; newline, or NUL under --null
+    mov [r10 + r14], al
     inc r14
     mov [rel outpos], r14
-    mov byte [rel prev_blank], 1
+    inc qword [rel blank_run]
     mov byte [rel line_start], 1
     mov byte [rel line_blank], 1
     inc r12
@@ -1777,6 +4077,14 @@ process_buffer:
     je  .vis_scan
     mov byte [rel line_start], 0
     mov byte [rel line_blank], 0
+    test dword [rel ext_flags], EXT_BYTE_OFFSET
+    je  .vis_offset_done
+    mov [rel outpos], r14
+    mov rdi, r12
+    call emit_byte_offset
+    mov r14, [rel outpos]
+    lea r10, [rel outbuf]
+.vis_offset_done:
     test r15b, OPT_NUMBER_NONBLANK
     je  .vis_check_number_all
     mov [rel outpos], r14
@@ -1828,6 +4136,8 @@ process_buffer:
     mov byte [rel line_blank], 0
     mov r14, [rel outpos]       ; keep local outpos in sync after emit_block
     lea r10, [rel outbuf]       ; refresh base pointer
+    test dword [rel ext_flags], EXT_STRIP_CR
+    jne .visible_loop           ; a CR after the run gets the CRLF check
     jmp .vis_handle_special
 
 .vis_handle_special:
@@ -1840,18 +4150,10 @@ process_buffer:
     jne .vis_check_control
     test r15b, OPT_SHOW_TABS
     je  .vis_emit_plain_tab
-    mov eax, BUFFER_SIZE - 2
-    cmp r14d, eax
-    jle .vis_tab_space_ok
-    mov [rel outpos], r14
-    call flush_outbuf
-    mov r14, [rel outpos]
-    lea r10, [rel outbuf]
-.vis_tab_space_ok:
-    mov byte [r10 + r14], '^'
-    mov byte [r10 + r14 + 1], 'I'
-    add r14, 2
     mov [rel outpos], r14
+    mov rsi, [rel tab_marker]    ; ^I, or --tab-marker
+    mov rcx, [rel tab_marker_len]
+    call emit_block
     mov byte [rel line_blank], 0
     inc r12
     dec r13
@@ -1946,8 +4248,9 @@ process_buffer:
     je  .vis_emit_nl
     cmp byte [rel line_blank], 1
     jne .vis_emit_nl
-    cmp byte [rel prev_blank], 1
-    jne .vis_emit_nl
+    mov rax, [rel blank_run]
+    cmp rax, [rel squeeze_limit]
+    jb .vis_emit_nl
     inc r12
     dec r13
     jmp .visible_loop
@@ -1962,14 +4265,19 @@ process_buffer:
 .vis_nl_space_ok:
     test r15b, OPT_SHOW_ENDS
     je  .vis_emit_nl_only
+    test dword [rel ext_flags], EXT_END_MARKER
+    jne .vis_nl_marker
     mov byte [r10 + r14], '$'
     inc r14
+    jmp .vis_emit_nl_only
+.vis_nl_marker:
+    call emit_end_marker
 .vis_emit_nl_only:
-    mov byte [r10 + r14], 10
+    mov al, [rel line_sep]       ; newline, or NUL under --null
+    mov [r10 + r14], al
     inc r14
     mov [rel outpos], r14
-    mov al, [rel line_blank]
-    mov [rel prev_blank], al
+    call update_blank_run
     mov byte [rel line_start], 1
     mov byte [rel line_blank], 1
     inc r12
@@ -1979,13 +4287,81 @@ process_buffer:
 .vis_store_outpos:
     jmp .done
 
-.done:
-    pop r15                      ; restore r15
-    pop r14                      ; restore r14
-    pop r13                      ; restore r13
-    pop r12                      ; restore r12
-    pop rbx                      ; restore rbx
-    ret                          ; return
+.done:
+    pop r15                      ; restore r15
+    pop r14                      ; restore r14
+    pop r13                      ; restore r13
+    pop r12                      ; restore r12
+    pop rbx                      ; restore rbx
+    ret                          ; return
+
+; -----------------------------------------------------------------------------
+; emit_end_marker
+;   --end-marker: the visible path's -E marker. Takes the cached outpos in r14
+;   and hands back r14/r10 refreshed, with room left for the line end.
+; -----------------------------------------------------------------------------
+emit_end_marker:
+    mov [rel outpos], r14
+    mov rsi, [rel end_marker]
+    mov rcx, [rel end_marker_len]
+    call emit_block
+    cmp qword [rel outpos], BUFFER_SIZE
+    jb  .eem_room
+    call flush_outbuf
+.eem_room:
+    mov r14, [rel outpos]
+    lea r10, [rel outbuf]
+    ret
+
+; -----------------------------------------------------------------------------
+; release_held_cr
+;   --strip-trailing-cr holds a CR that ends a chunk until the next byte shows
+;   whether it ends a line. When it does not (or the input ends), this runs the
+;   CR through process_buffer so it is numbered, shown and counted as usual.
+; -----------------------------------------------------------------------------
+release_held_cr:
+    cmp byte [rel cr_held], 0
+    je  .rhc_done
+    mov byte [rel cr_held], 0
+    lea rsi, [rel cr_byte]
+    mov ecx, 1
+    jmp process_buffer           ; returns to our caller
+.rhc_done:
+    ret
+
+; -----------------------------------------------------------------------------
+; wait_readable
+;   Input : edi = fd whose read just failed with EAGAIN (opened O_NONBLOCK by
+;           whoever handed it to us).
+;   Blocks in poll() until the fd is readable or hung up, so the caller's retry
+;   makes progress instead of spinning. Errors are ignored: the retried read
+;   reports them. Clobbers rax, rcx, rdx, rsi, r11.
+; -----------------------------------------------------------------------------
+wait_readable:
+    sub rsp, 8                   ; struct pollfd { int fd; short events, revents; }
+    mov [rsp], edi
+    mov dword [rsp+4], POLLIN
+.wr_poll:
+    mov eax, SYS_poll
+    mov rdi, rsp                 ; fds
+    mov esi, 1                   ; nfds
+    mov edx, -1                  ; no timeout
+    syscall
+    cmp rax, -EINTR
+    je  .wr_poll
+    mov edi, [rsp]               ; hand the fd back
+    add rsp, 8
+    ret
+
+; Track blank-line runs for -s / --squeeze-limit once a line is finished.
+update_blank_run:
+    cmp byte [rel line_blank], 1 ; was the finished line blank?
+    jne .ubr_reset
+    inc qword [rel blank_run]    ; extend the current run
+    ret
+.ubr_reset:
+    mov qword [rel blank_run], 0 ; text line ends the run
+    ret
 
 ; Decide whether the current line needs a prefix number.
 maybe_emit_number:
@@ -2007,10 +4383,13 @@ maybe_emit_number:
 .mn_exit:
     ret                          ; return
 
-; Emit control / high-bit characters using ^ and M- notation (POSIX cat -v).
+; Emit control / high-bit characters using ^ and M- notation (POSIX cat -v),
+; or as \xNN under --nonprinting-style=hex.
 emit_visible_char:
     push rbx                     ; save rbx
     mov bl, al                   ; bl = character
+    test dword [rel ext_flags], EXT_HEX_NONPRINTING
+    jne .hex
 .meta_loop:
     cmp bl, 128                  ; high-bit set?
     jb  .no_meta                 ; if <128, no meta prefix
@@ -2043,6 +4422,23 @@ emit_visible_char:
     call emit_byte
     mov dil, '?'                 ; '?' for DEL
     call emit_byte
+    jmp .visible_done
+
+.hex:
+    mov dil, '\'                 ; "\x" then two lowercase hex digits
+    call emit_byte
+    mov dil, 'x'
+    call emit_byte
+    movzx eax, bl
+    shr eax, 4
+    lea rsi, [rel hex_digits]
+    mov dil, [rsi + rax]
+    call emit_byte
+    movzx eax, bl
+    and eax, 15
+    lea rsi, [rel hex_digits]
+    mov dil, [rsi + rax]
+    call emit_byte
 .visible_done:
     pop rbx                      ; restore rbx
     ret                          ; return
@@ -2073,33 +4469,55 @@ str_compare:
     ret
 
 print_help_and_exit:
-    mov rdi, 1                   ; fd = stdout
     mov rsi, help_text           ; pointer to help text
-    call write_cstr              ; write NUL-terminated string
+    call write_stdout_cstr       ; a failed write exits 1 with a diagnostic
     xor edi, edi                 ; exit code 0
     call exit_with_code          ; exit
 
 print_version_and_exit:
-    mov rdi, 1                   ; fd = stdout
     mov rsi, version_text        ; pointer to version text
-    call write_cstr              ; write NUL-terminated string
+    call write_stdout_cstr       ; a failed write exits 1 with a diagnostic
     xor edi, edi                 ; exit code 0
     call exit_with_code          ; exit
 
+; -----------------------------------------------------------------------------
+; write_stdout_cstr
+;   Input : rsi -> NUL-terminated string
+;   Effect: writes it to stdout via write_direct_stdout, so a closed or full
+;           stdout gets cat's "write error" diagnostic.
+; -----------------------------------------------------------------------------
+write_stdout_cstr:
+    mov rcx, rsi
+.wsc_len:
+    cmp byte [rcx], 0
+    je  .wsc_have_len
+    inc rcx
+    jmp .wsc_len
+.wsc_have_len:
+    sub rcx, rsi                 ; rcx = length
+    jmp write_direct_stdout
+
 ; -----------------------------------------------------------------------------
 ; emit_line_number
 ;   Emits the current line number as a right-aligned, 6-column decimal value
-;   followed by a tab character (mirroring GNU cat -n).
+;   followed by a tab character (mirroring GNU cat -n). Under --color the
+;   number itself is wrapped in ANSI green; the tab stays outside.
 ; -----------------------------------------------------------------------------
 emit_line_number:
     push r15                     ; save r15
     push rbx                     ; save rbx
+    test dword [rel ext_flags], EXT_COLOR
+    je  .eln_plain
+    lea rsi, [rel color_number_on]
+    mov ecx, 5
+    call emit_block
+.eln_plain:
     mov rax, [rel line_no]       ; rax = current line number
-    cmp rax, 1000000             ; fast path valid up to 6 digits
+    cmp rax, [rel number_fast_limit] ; line_ascii is 6 digits wide
     jae .eln_slow
     lea rsi, [rel line_ascii]    ; cached ASCII + tab
     mov rcx, 7
-    call emit_block
+    call emit_number_field
     call bump_line_ascii         ; increment cached number + line_no
     jmp .eln_return
 
@@ -2143,11 +4561,11 @@ emit_line_number:
 
 .eln_ready:
     mov r15, rcx                 ; digit count
-    mov r8d, 6                   ; width = 6
+    mov r8, [rel number_width]   ; width: 6, or -w
     mov r9, rcx                  ; r9 = digits
     cmp r9, r8
     jge .eln_no_spaces
-    mov r10, r8                  ; r10 = 6
+    mov r10, r8                  ; r10 = width
     sub r10, r9                  ; r10 = spaces needed
     jmp .eln_build
 .eln_no_spaces:
@@ -2183,7 +4601,7 @@ emit_line_number:
     add rcx, r15
     inc rcx
     mov rsi, numbuf              ; source = formatted number
-    call emit_block              ; copy into outbuf in one shot
+    call emit_number_field       ; copy into outbuf in one shot
     mov rax, [rel line_no]
     inc rax
     mov [rel line_no], rax       ; bump counter
@@ -2193,6 +4611,50 @@ emit_line_number:
     pop r15                      ; restore r15
     ret
 
+; -----------------------------------------------------------------------------
+; emit_byte_offset
+;   --byte-offset: rdi = the first byte of a line in process_buffer's chunk.
+;   Emits its offset in the input as at least 8 lowercase hex digits and a
+;   tab, ahead of any -n/-b number. Offsets keep counting across operands.
+; -----------------------------------------------------------------------------
+emit_byte_offset:
+    lea rax, [rel cr_byte]
+    cmp rdi, rax
+    jne .ebo_biased
+    mov rax, [rel cr_offset]     ; a replayed CR keeps the offset it was read at
+    jmp .ebo_render
+.ebo_biased:
+    mov rax, [rel offset_bias]
+    add rax, rdi
+.ebo_render:
+    lea rsi, [rel numbuf + 63]   ; build digits backwards, tab last
+    mov byte [rsi], 9
+    mov ecx, 1                   ; rcx = field length so far
+    lea r8, [rel hex_digits]
+.ebo_digit:
+    dec rsi
+    mov edx, eax
+    and edx, 15
+    mov dl, [r8 + rdx]
+    mov [rsi], dl
+    inc ecx
+    shr rax, 4
+    jne .ebo_digit
+    cmp ecx, 9                   ; zero-pad to 8 digits
+    jb  .ebo_digit
+    jmp emit_block
+
+; emit_number_field: rsi/rcx = number, padding and tab. --color swaps the
+; tab for the reset sequence followed by the tab.
+emit_number_field:
+    test dword [rel ext_flags], EXT_COLOR
+    je  emit_block
+    dec rcx                      ; drop the tab
+    call emit_block
+    lea rsi, [rel color_number_off]
+    mov ecx, 5
+    jmp emit_block
+
 bump_line_ascii:
     lea rdi, [rel line_ascii + 5] ; start from least-significant digit
     mov ecx, 6                   ; six positions
@@ -2254,9 +4716,12 @@ emit_byte:
 ; emit_block
 ;   Copies RCX bytes from RSI into outbuf, flushing as needed.  Accelerates
 ;   simple decoration paths that can forward large spans without per-byte stalls.
+;   Preserves rdi and r11 (callers keep the run length there across a flush,
+;   whose write() syscall would otherwise clobber r11).
 ; -----------------------------------------------------------------------------
 emit_block:
     push rdi                     ; save rdi
+    push r11                     ; syscall in flush_outbuf clobbers r11
 .eb_loop:
     cmp rcx, 0                   ; any bytes to copy?
     je  .eb_done                 ; no -> done
@@ -2284,6 +4749,7 @@ emit_block:
     mov [rel outpos], rax        ; store new outpos
     jmp .eb_loop                 ; loop if bytes remain
 .eb_done:
+    pop r11                      ; restore r11
     pop rdi                      ; restore rdi
     ret                          ; return
 
@@ -2331,6 +4797,7 @@ write_direct_stdout:
     call flush_outbuf            ; ensure buffered data is out
     pop rsi
     pop rcx
+    call tally_output            ; account for the bytes about to go out
 .wds_loop:
     mov rdx, rcx
     mov eax, SYS_write
@@ -2370,7 +4837,10 @@ flush_outbuf:
     mov rcx, [rel outpos]        ; rcx = bytes pending
     cmp rcx, 0                   ; nothing to write?
     je  .flush_return            ; yes -> return
+    test dword [rel ext_flags], EXT_COUNT_ONLY
+    jne .all_flushed             ; --count-only: decorated text is dropped
     lea rsi, [rel outbuf]        ; rsi = buffer base
+    call tally_output            ; --stats/--checksum bookkeeping
 .flush_loop:
     mov rdx, rcx                 ; rdx = bytes to write
     mov eax, SYS_write           ; write() syscall
@@ -2410,16 +4880,308 @@ flush_outbuf:
     pop rcx                      ; restore rcx
     ret                          ; return
 
+; -----------------------------------------------------------------------------
+; flush_outbuf_lines
+;   --buffer=line. Writes outbuf up to and including its last line terminator
+;   (NUL under --null) and moves the unfinished line after it to the front.
+; -----------------------------------------------------------------------------
+flush_outbuf_lines:
+    lea r8, [rel outbuf]
+    mov rcx, [rel outpos]
+    xor eax, eax                 ; al = terminator
+    test byte [rel ext_flags], EXT_NULL
+    jne .fol_scan
+    mov al, 10
+.fol_scan:
+    test rcx, rcx
+    je  .fol_done                ; no complete line buffered
+    cmp [r8 + rcx - 1], al
+    je  .fol_found
+    dec rcx
+    jmp .fol_scan
+.fol_found:
+    mov rdx, [rel outpos]
+    sub rdx, rcx                 ; rdx = unfinished tail length
+    push rdx
+    push rcx
+    mov [rel outpos], rcx
+    call flush_outbuf
+    pop rcx
+    pop rdx
+    lea rdi, [rel outbuf]
+    lea rsi, [rdi + rcx]
+    mov [rel outpos], rdx
+    mov rcx, rdx
+    rep movsb                    ; tail to the front (regions may overlap)
+.fol_done:
+    ret
+
+; -----------------------------------------------------------------------------
+; tally_output
+;   Input : rsi -> bytes headed for stdout, rcx = count
+;   Effect: updates the --stats counters and the --checksum CRC; preserves
+;           rsi/rcx/rdi, clobbers rax/rdx.
+; -----------------------------------------------------------------------------
+tally_output:
+    test byte [rel ext_flags], EXT_STATS | EXT_CHECKSUM | EXT_PROGRESS
+    je  .to_return               ; nothing observes the output stream
+    push rdi
+    push rcx
+    push rsi
+    test byte [rel ext_flags], EXT_PROGRESS
+    je  .to_stats
+    add [rel progress_bytes], rcx
+    mov rax, [rel progress_bytes]
+    cmp rax, [rel progress_next]
+    jb  .to_stats
+.tp_step:
+    add qword [rel progress_next], PROGRESS_STEP ; one line per crossing
+    cmp rax, [rel progress_next]
+    jae .tp_step
+    push rax                     ; callers keep live state in these
+    push rdx
+    push r8
+    call print_progress
+    pop r8
+    pop rdx
+    pop rax
+    mov rsi, [rsp]               ; reload the chunk being tallied
+    mov rcx, [rsp + 8]
+.to_stats:
+    test byte [rel ext_flags], EXT_STATS
+    je  .to_crc
+    add [rel stat_bytes], rcx    ; total bytes out
+    mov rdx, [rel stat_cur_len]  ; rdx = length of the open line
+    mov rax, [rel stat_max_len]  ; rax = longest line so far
+    mov dil, [rel line_sep]      ; record separator as written
+.ts_loop:
+    test rcx, rcx
+    je  .ts_done
+    cmp [rsi], dil               ; separator closes the line
+    jne .ts_byte
+    inc qword [rel stat_lines]
+    cmp rdx, rax
+    cmova rax, rdx               ; keep the longest
+    xor edx, edx
+    jmp .ts_next
+.ts_byte:
+    inc rdx
+.ts_next:
+    inc rsi
+    dec rcx
+    jmp .ts_loop
+.ts_done:
+    cmp rdx, rax                 ; an unterminated line may already be longest
+    cmova rax, rdx
+    mov [rel stat_cur_len], rdx
+    mov [rel stat_max_len], rax
+    mov rsi, [rsp]               ; rewind for the CRC pass
+    mov rcx, [rsp + 8]
+.to_crc:
+    test byte [rel ext_flags], EXT_CHECKSUM
+    je  .to_done
+    lea rdi, [rel crc_table]
+    mov eax, [rel crc_state]
+.crc_loop:
+    test rcx, rcx
+    je  .crc_done
+    movzx edx, byte [rsi]
+    xor dl, al                   ; index = (crc ^ byte) & 0xff
+    shr eax, 8
+    xor eax, [rdi + rdx*4]
+    inc rsi
+    dec rcx
+    jmp .crc_loop
+.crc_done:
+    mov [rel crc_state], eax
+.to_done:
+    pop rsi
+    pop rcx
+    pop rdi
+.to_return:
+    ret
+
+; -----------------------------------------------------------------------------
+; crc32_init
+;   Fills crc_table for the reflected IEEE polynomial and primes crc_state.
+; -----------------------------------------------------------------------------
+crc32_init:
+    lea rdi, [rel crc_table]
+    xor ecx, ecx                 ; ecx = table index
+.ci_entry:
+    mov eax, ecx
+    mov edx, 8                   ; eight bit-steps per entry
+.ci_bit:
+    shr eax, 1
+    jnc .ci_next
+    xor eax, CRC32_POLY
+.ci_next:
+    dec edx
+    jne .ci_bit
+    mov [rdi + rcx*4], eax
+    inc ecx
+    cmp ecx, 256
+    jb  .ci_entry
+    mov dword [rel crc_state], 0xFFFFFFFF
+    ret
+
+; -----------------------------------------------------------------------------
+; print_checksum
+;   Writes "<prog>: crc32 xxxxxxxx" (lowercase hex) to stderr.
+; -----------------------------------------------------------------------------
+print_checksum:
+    mov rdi, 2                   ; fd = stderr
+    call write_prog_name
+    mov rsi, checksum_mid
+    call write_cstr
+    mov eax, [rel crc_state]
+    not eax                      ; final inversion
+    lea rsi, [rel numbuf + 8]
+    mov byte [rsi], 10           ; "xxxxxxxx\n"
+    mov byte [rsi + 1], 0
+    lea r8, [rel hex_digits]
+.pc_digit:
+    dec rsi
+    mov edx, eax
+    and edx, 15
+    mov dl, [r8 + rdx]
+    mov [rsi], dl
+    shr eax, 4
+    lea rdx, [rel numbuf]
+    cmp rsi, rdx
+    jne .pc_digit
+    jmp write_cstr
+
+; -----------------------------------------------------------------------------
+; print_stats
+;   Writes "<prog>: B bytes, L lines, longest line M bytes" to stderr.  A final
+;   line without a trailing newline still counts as a line.
+; -----------------------------------------------------------------------------
+print_stats:
+    mov rdi, 2                   ; fd = stderr
+    call write_prog_name
+    mov rsi, stats_bytes_mid
+    call write_cstr
+    mov rax, [rel stat_bytes]
+    call write_decimal
+    mov rsi, stats_lines_mid
+    call write_cstr
+    mov rax, [rel stat_lines]
+    cmp qword [rel stat_cur_len], 0
+    je  .ps_lines
+    inc rax                      ; count the unterminated last line
+.ps_lines:
+    call write_decimal
+    mov rsi, stats_longest_mid
+    call write_cstr
+    mov rax, [rel stat_max_len]
+    call write_decimal
+    mov rsi, stats_tail
+    jmp write_cstr
+
+; -----------------------------------------------------------------------------
+; refuse_binary_chunk
+;   Input : rsi -> first chunk read from a file, rcx = its length,
+;           r15 = file label.
+;   --binary-safe: a NUL byte marks the file as binary. Flush what earlier
+;   files produced, explain on stderr and exit 1 before any of it reaches the
+;   terminal. Otherwise returns with rsi/rcx intact.
+; -----------------------------------------------------------------------------
+refuse_binary_chunk:
+    push rcx
+    push rdi
+    mov rdi, rsi
+    xor eax, eax                 ; al = byte to find
+    repne scasb
+    pop rdi
+    pop rcx
+    je  .rbc_binary
+    ret
+.rbc_binary:
+    call flush_outbuf
+    mov byte [rel errflag], 1
+    mov rdi, 2                   ; fd = stderr
+    call write_prog_name
+    mov rsi, err_open_sep        ; ": "
+    call write_cstr
+    mov rsi, r15
+    call write_cstr
+    mov rsi, binary_refused_tail
+    call write_cstr
+    mov edi, 1
+    call exit_with_code
+
+; Write "<prog>: progress N bytes" to stderr for --progress.
+print_progress:
+    mov rdi, 2                   ; fd = stderr
+    call write_prog_name
+    mov rsi, progress_mid
+    call write_cstr
+    mov rax, [rel progress_bytes]
+    call write_decimal
+    mov rsi, stats_tail
+    jmp write_cstr
+
+; -----------------------------------------------------------------------------
+; print_count
+;   --count-only: "LINES\n", or "LINES BYTES\n" for lines,bytes, on stdout.
+;   line_no is the next number -n/-b would have used.
+; -----------------------------------------------------------------------------
+print_count:
+    mov rdi, 1                   ; fd = stdout
+    mov rax, [rel line_no]
+    dec rax
+    call write_decimal
+    test dword [rel ext_flags], EXT_COUNT_BYTES
+    je  .pc_newline
+    mov rsi, space_str
+    call write_cstr
+    mov rax, [rel count_bytes]
+    call write_decimal
+.pc_newline:
+    mov rsi, newline
+    jmp write_cstr
+
+; -----------------------------------------------------------------------------
+; write_decimal
+;   Input : rdi = fd, rax = unsigned value
+;   Effect: writes the value in decimal using numbuf as scratch.
+; -----------------------------------------------------------------------------
+write_decimal:
+    lea rsi, [rel numbuf + 63]   ; build digits backwards from the end
+    mov byte [rsi], 0            ; NUL terminator for write_cstr
+    mov r8d, 10
+.wd_loop:
+    xor edx, edx
+    div r8                       ; rax = rax / 10, rdx = digit
+    add dl, '0'
+    dec rsi
+    mov [rsi], dl
+    test rax, rax
+    jne .wd_loop
+    jmp write_cstr
+
 ; -----------------------------------------------------------------------------
 ; Simple helpers for consistent diagnostics / exit handling.
 ; -----------------------------------------------------------------------------
 report_open_error:
     mov byte [rel errflag], 1    ; mark that an error occurred
+    push rdx                     ; flush_outbuf clobbers rdx
+    call flush_outbuf            ; earlier output goes out before the diagnostic
+    pop rdx
+    test dword [rel ext_flags], EXT_QUIET_ERRORS
+    jne .roe_done                ; --quiet-errors: exit status only
     mov r8, rsi                  ; save filename pointer in r8
     mov r9d, edx                 ; save errno in r9d
     mov rdi, 2                   ; fd = stderr
-    mov rsi, err_prefix          ; prefix string
-    call write_cstr              ; write prefix
+    test dword [rel ext_flags], EXT_COLOR_ERRORS
+    je  .roe_prefix
+    mov rsi, color_error_on      ; --color: the whole line in red
+    call write_cstr
+.roe_prefix:
+    call write_prog_name         ; argv[0], as for every other diagnostic
+    mov rsi, err_open_sep        ; ": "
+    call write_cstr
     mov rsi, r8                  ; restore filename pointer
     call write_cstr              ; write filename / label
     mov rsi, err_open_sep        ; ": "
@@ -2429,11 +5191,28 @@ report_open_error:
     call write_errno_string      ; print errno string
 
     mov rsi, newline             ; newline string
+    test dword [rel ext_flags], EXT_COLOR_ERRORS
+    je  .roe_end
+    mov rsi, color_error_off     ; reset, then the newline
+.roe_end:
     call write_cstr              ; end line
+.roe_done:
     ret                          ; return
 
 report_read_error:
+    mov byte [rel read_failed], 1 ; --repeat: no further passes over it
     jmp report_open_error        ; same formatting as open errors
+
+report_dry_run_ok:
+    mov r8, rsi                  ; save label
+    mov rdi, 2                   ; fd = stderr
+    call write_prog_name         ; same shape as the error lines
+    mov rsi, err_open_sep
+    call write_cstr
+    mov rsi, r8
+    call write_cstr
+    mov rsi, dry_run_ok
+    jmp write_cstr
 report_bad_short_option:
     mov byte [rel errflag], 1    ; mark error
     mov [rel opt_char_buf], dil  ; store offending option character
@@ -2454,6 +5233,47 @@ report_bad_short_option:
     mov edi, 1                   ; exit code = 1
     call exit_with_code
 
+; report_short_option_missing: dil = the option whose value is missing.
+report_short_option_missing:
+    mov byte [rel errflag], 1    ; mark error
+    mov [rel opt_char_buf], dil
+    mov byte [rel opt_char_buf + 1], 0
+    mov rdi, 2                   ; fd = stderr
+    call write_prog_name
+    mov rsi, err_short_needs_arg_mid
+    call write_cstr
+    lea rsi, [rel opt_char_buf]
+    call write_cstr
+    mov rsi, err_option_close
+    call write_cstr
+    mov rsi, err_try_prefix
+    call write_cstr
+    call write_prog_name
+    mov rsi, err_try_suffix
+    call write_cstr
+    mov edi, 1                   ; exit code = 1
+    call exit_with_code
+
+; report_invalid_width: rsi = the rejected -w value.
+report_invalid_width:
+    mov byte [rel errflag], 1    ; mark error
+    mov r8, rsi                  ; save the value
+    mov rdi, 2                   ; fd = stderr
+    call write_prog_name
+    mov rsi, err_invalid_width_mid
+    call write_cstr
+    mov rsi, r8
+    call write_cstr
+    mov rsi, err_option_close
+    call write_cstr
+    mov rsi, err_try_prefix
+    call write_cstr
+    call write_prog_name
+    mov rsi, err_try_suffix
+    call write_cstr
+    mov edi, 1                   ; exit code = 1
+    call exit_with_code
+
 report_bad_long_option:
     mov byte [rel errflag], 1    ; mark error
     mov r8, rsi                  ; save option string pointer
@@ -2494,6 +5314,79 @@ report_long_option_argument:
     mov edi, 1
     call exit_with_code
 
+report_long_option_missing:
+    mov byte [rel errflag], 1    ; mark error
+    mov r8, rsi                  ; save option name pointer
+    mov rdi, 2                   ; fd = stderr
+    call write_prog_name
+    mov rsi, err_option_arg_mid
+    call write_cstr
+    mov rsi, err_option_arg_dashes
+    call write_cstr
+    mov rsi, r8                  ; option name (no "--")
+    call write_until_eq
+    mov rsi, err_option_needs_arg_tail
+    call write_cstr
+    mov rsi, err_try_prefix
+    call write_cstr
+    call write_prog_name
+    mov rsi, err_try_suffix
+    call write_cstr
+    mov edi, 1
+    call exit_with_code
+
+report_invalid_long_value:
+    mov byte [rel errflag], 1    ; mark error
+    mov r8, rsi                  ; save option name pointer
+    mov r9, rdx                  ; save value pointer
+    mov rdi, 2                   ; fd = stderr
+    call write_prog_name
+    mov rsi, err_invalid_arg_mid
+    call write_cstr
+    mov rsi, r9                  ; offending value
+    call write_cstr
+    mov rsi, err_invalid_arg_for
+    call write_cstr
+    mov rsi, r8                  ; option name (no "--")
+    call write_until_eq
+    mov rsi, err_option_close
+    call write_cstr
+    mov rsi, err_try_prefix
+    call write_cstr
+    call write_prog_name
+    mov rsi, err_try_suffix
+    call write_cstr
+    mov edi, 1
+    call exit_with_code
+
+; report_option_conflict: rsi, rdx = two option names (no "--") that cannot
+; be given together.
+report_option_conflict:
+    mov byte [rel errflag], 1    ; mark error
+    mov r8, rsi                  ; save both names
+    mov r9, rdx
+    mov rdi, 2                   ; fd = stderr
+    call write_prog_name
+    mov rsi, err_option_arg_mid
+    call write_cstr
+    mov rsi, err_option_arg_dashes
+    call write_cstr
+    mov rsi, r8
+    call write_cstr
+    mov rsi, err_option_conflict_mid
+    call write_cstr
+    mov rsi, r9
+    call write_cstr
+    mov rsi, err_option_close
+    call write_cstr
+    mov rsi, err_try_prefix
+    call write_cstr
+    call write_prog_name
+    mov rsi, err_try_suffix
+    call write_cstr
+    mov edi, 1
+    call exit_with_code
+
 write_prog_name:
     mov rsi, [rel prog_name]
     call write_cstr
@@ -2530,6 +5423,8 @@ write_until_eq:
     je  .wue_write_loop
     cmp rax, -EAGAIN
     je  .wue_write_loop
+    cmp edi, 2                   ; stderr closed: drop the diagnostic
+    je  .wue_return
     mov byte [rel errflag], 1
     mov edi, 1
     call exit_with_code
@@ -2537,20 +5432,26 @@ write_until_eq:
     ret
 
 handle_sigpipe:
+    test dword [rel ext_flags], EXT_EPIPE_EXIT
+    je  .hs_kill
+    mov edi, [rel epipe_code]    ; --epipe-exit: quietly, with the chosen code
+    call exit_with_code
+.hs_kill:
     mov eax, 39                  ; SYS_getpid
     syscall
     mov edi, eax
     mov esi, 13                  ; SIGPIPE
     mov eax, 62                  ; SYS_kill
     syscall
-    mov edi, 1                   ; exit if SIGPIPE ignored
-    call exit_with_code
+    mov edx, EPIPE               ; still here: SIGPIPE is ignored,
+    jmp fatal_write_error        ; so report it like cat and exit 1
 
 fatal_write_error:
     mov byte [rel errflag], 1    ; mark fatal I/O error
     mov r9d, edx                 ; save errno
     mov rdi, 2                   ; fd = stderr
-    mov rsi, err_write_prefix
+    call write_prog_name         ; argv[0], like GNU cat
+    mov rsi, err_write_mid
     call write_cstr
     mov edx, r9d
     call write_errno_string
@@ -2589,6 +5490,8 @@ write_cstr:
     je  .write_loop              ; retry
     cmp rax, -EAGAIN             ; would block?
     je  .write_loop              ; retry
+    cmp edi, 2                   ; a diagnostic stderr cannot take (closed:
+    je  .return                  ; EBADF) is dropped and cat's work goes on
     mov byte [rel errflag], 1    ; mark error
     mov edi, 1                   ; exit code
     call exit_with_code          ; exit immediately
@@ -2631,6 +5534,18 @@ write_errno_string:
     je  .efbig
     cmp edx, ENOMEM
     je  .enomem
+    cmp edx, EPERM
+    je  .eperm
+    cmp edx, ENXIO
+    je  .enxio
+    cmp edx, EBUSY
+    je  .ebusy
+    cmp edx, ENODEV
+    je  .enodev
+    cmp edx, ETXTBSY
+    je  .etxtbsy
+    cmp edx, EPIPE
+    je  .epipe
 
     mov rsi, err_unknown
     jmp write_cstr
@@ -2680,7 +5595,185 @@ write_errno_string:
 .enomem:
     mov rsi, err_enomem
     jmp write_cstr
+.eperm:
+    mov rsi, err_eperm
+    jmp write_cstr
+.enxio:
+    mov rsi, err_enxio
+    jmp write_cstr
+.ebusy:
+    mov rsi, err_ebusy
+    jmp write_cstr
+.enodev:
+    mov rsi, err_enodev
+    jmp write_cstr
+.etxtbsy:
+    mov rsi, err_etxtbsy
+    jmp write_cstr
+.epipe:
+    mov rsi, err_epipe
+    jmp write_cstr
 
 exit_with_code:
+    mov ebx, edi                 ; ebx = exit status
+    mov ebp, [rel pager_pid]
+    test ebp, ebp
+    je  .ewc_exit
+    mov eax, SYS_close           ; --pager: EOF for the pager, then hand the
+    mov edi, 1                   ; terminal back only once it has quit
+    syscall
+.ewc_wait:
+    mov eax, SYS_wait4
+    mov edi, ebp
+    xor esi, esi
+    xor edx, edx
+    xor r10d, r10d
+    syscall
+    cmp rax, -EINTR
+    je  .ewc_wait
+.ewc_exit:
+    mov edi, ebx
     mov eax, SYS_exit            ; exit syscall
     syscall                      ; terminate process with status in edi
+
+; -----------------------------------------------------------------------------
+; start_pager
+;   --pager: when stdout is a terminal, start $PAGER through /bin/sh -c (or
+;   less when $PAGER is unset or empty) reading a pipe, and make that pipe
+;   stdout; exit_with_code waits for it. Stdout is left alone when it is not
+;   a terminal, or when the pipe, fork or exec fails: the child reports a
+;   failed execve on a close-on-exec pipe before anything has been written.
+;   Uses r12/r13 (argc/argv) to find envp. Clobbers rax, rcx, rdx, rsi, rdi,
+;   r8-r11.
+; -----------------------------------------------------------------------------
+start_pager:
+    push rbx
+    push rbp
+    sub rsp, 24                  ; [rsp] data pipe, [rsp+8] exec status pipe
+    mov eax, SYS_ioctl
+    mov edi, 1
+    mov esi, TCGETS
+    lea rdx, [rel stat_out]      ; termios scratch
+    syscall
+    test rax, rax
+    jl  .sp_done                 ; redirected: write directly, like cat
+    mov rsi, env_pager
+    call find_env
+    mov rbx, rax                 ; rbx = $PAGER, or 0 for less
+    test rbx, rbx
+    je  .sp_pipes
+    cmp byte [rbx], 0
+    jne .sp_pipes
+    xor ebx, ebx                 ; PAGER= means the default too
+.sp_pipes:
+    mov eax, SYS_pipe2
+    lea rdi, [rsp]
+    mov esi, O_CLOEXEC
+    syscall
+    test rax, rax
+    jl  .sp_done
+    mov eax, SYS_pipe2
+    lea rdi, [rsp + 8]
+    mov esi, O_CLOEXEC
+    syscall
+    test rax, rax
+    jl  .sp_close_data
+    mov eax, SYS_fork
+    syscall
+    test rax, rax
+    jl  .sp_close_status
+    je  .sp_child
+    mov rbp, rax                 ; rbp = pager pid
+    mov eax, SYS_close           ; only the child may hold the write end
+    mov edi, [rsp + 12]
+    syscall
+.sp_status_read:
+    mov eax, SYS_read            ; EOF once execve closed the write end
+    mov edi, [rsp + 8]
+    lea rsi, [rsp + 16]
+    mov edx, 1
+    syscall
+    cmp rax, -EINTR
+    je  .sp_status_read
+    mov rbx, rax                 ; rbx > 0: the child could not exec
+    mov eax, SYS_close
+    mov edi, [rsp + 8]
+    syscall
+    test rbx, rbx
+    jg  .sp_failed
+    mov eax, SYS_dup2            ; stdout now feeds the pager
+    mov edi, [rsp + 4]
+    mov esi, 1
+    syscall
+    mov [rel pager_pid], ebp
+    jmp .sp_close_data
+.sp_failed:
+    mov eax, SYS_wait4           ; reap it and keep writing to the terminal
+    mov edi, ebp
+    xor esi, esi
+    xor edx, edx
+    xor r10d, r10d
+    syscall
+    jmp .sp_close_data
+.sp_close_status:
+    mov eax, SYS_close
+    mov edi, [rsp + 8]
+    syscall
+    mov eax, SYS_close
+    mov edi, [rsp + 12]
+    syscall
+.sp_close_data:
+    mov eax, SYS_close
+    mov edi, [rsp]
+    syscall
+    mov eax, SYS_close
+    mov edi, [rsp + 4]
+    syscall
+.sp_done:
+    add rsp, 24
+    pop rbp
+    pop rbx
+    ret
+
+.sp_child:
+    mov eax, SYS_dup2            ; the pager reads the data pipe as stdin
+    mov edi, [rsp]
+    xor esi, esi
+    syscall
+    mov ebp, [rsp + 12]          ; ebp = status write end
+    lea rdx, [r13 + r12*8 + 8]   ; rdx = envp, past argv's NULL
+    sub rsp, 32                  ; argv for execve
+    test rbx, rbx
+    je  .sp_exec_less
+    mov rax, pager_shell
+    mov [rsp], rax               ; sh -c "$PAGER"
+    mov rax, pager_shell_c
+    mov [rsp + 8], rax
+    mov [rsp + 16], rbx
+    mov qword [rsp + 24], 0
+    mov eax, SYS_execve
+    mov rdi, pager_shell
+    mov rsi, rsp
+    syscall
+    jmp .sp_exec_failed
+.sp_exec_less:
+    mov rax, pager_less_arg0
+    mov [rsp], rax
+    mov qword [rsp + 8], 0
+    mov eax, SYS_execve
+    mov rdi, pager_less
+    mov rsi, rsp
+    syscall
+    mov eax, SYS_execve          ; rdx survives a failed syscall
+    mov rdi, pager_less_alt
+    mov rsi, rsp
+    syscall
+.sp_exec_failed:
+    mov eax, SYS_write           ; tell the parent to keep stdout
+    mov edi, ebp
+    mov rsi, rsp
+    mov edx, 1
+    syscall
+    mov eax, SYS_exit
+    mov edi, 127
+    syscall